@@ -1,9 +1,45 @@
-use crate::{error, info, success, utils, Res};
+use serde::Serialize;
+
+use crate::{error, info, success, utils, warning, Res};
+
+/// An alias as printed by `gvm alias list --json`.
+#[derive(Serialize, Debug)]
+struct AliasEntry {
+    alias: String,
+    target: String,
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    via: Option<String>,
+    dangling: bool,
+}
+
+/// Options of `gvm alias`.
+#[derive(Debug, Clone, Default)]
+pub struct AliasOptions {
+    /// When `true`, the alias must be "default" and the target version is
+    /// activated, repointing the default alias, the active file and `go.env`
+    /// all-or-nothing.
+    pub force_default_switch: bool,
+    /// When `true`, the alias and the target name two existing aliases whose
+    /// targets are exchanged.
+    pub swap: bool,
+    /// An existing alias to point the new alias at instead of a version. The
+    /// new alias follows it, so repointing it later repoints the new alias as
+    /// well.
+    pub to_alias: Option<String>,
+    /// When `true` and the aliases are listed, they are printed as a JSON array.
+    pub json: bool,
+    /// When `true`, an existing alias is repointed atomically instead of
+    /// rejected, and its previous target is printed.
+    pub force: bool,
+}
 
 /// Creates an alias for a specific Go version or lists existing aliases.
 ///
 /// This function creates a symbolic link (alias) for a specified Go version,
-/// or lists all existing aliases if the alias name is "list" or "ls".
+/// or lists all existing aliases if the alias name is "list" or "ls". Reserved
+/// names (see `config::RESERVED_ALIAS_NAMES`) and names that are not plain file
+/// names are rejected.
 /// It performs several checks to ensure the alias and target version are valid
 /// before creating the alias.
 ///
@@ -11,54 +47,79 @@ use crate::{error, info, success, utils, Res};
 ///
 /// * `alias`: A `String` representing the name of the alias to be created or "list"/"ls" to list existing aliases.
 /// * `target`: An `Option<String>` representing the target Go version for which the alias is being created.
-///             If `None`, the default version will be used.
+///   If `None`, the default version will be used.
+/// * `options`: How the alias is created, see `AliasOptions`.
 ///
 /// # Returns
 ///
 /// Returns `Ok(())` if the alias is successfully created or the list is displayed,
 /// or an error wrapped in `Res<()>` if any step fails.
-pub async fn alias(alias: String, target: Option<String>) -> Res<()> {
-    if alias == "default" {
-        error!("Setting 'default' as alias is not allowed. Please choose a different alias.");
+pub async fn alias(alias: String, target: Option<String>, options: AliasOptions) -> Res<()> {
+    let AliasOptions {
+        force_default_switch,
+        swap,
+        to_alias,
+        json,
+        force,
+    } = options;
+    if let Some(ref target) = target {
+        utils::validate_name(if swap { "alias" } else { "version" }, target)?;
+    }
+    if let Some(ref to_alias) = to_alias {
+        utils::validate_name("alias", to_alias)?;
     }
 
-    if alias == "list" || alias == "ls" {
-        use colored::Colorize;
-
-        let alias_list = utils::list_aliases().await?;
-        let alias_max_length = alias_list.iter().map(|name| name.len()).max().unwrap_or(0);
-        for alias_name in alias_list {
-            let alias_dir = utils::get_alias_file_path();
-            let alias_path = alias_dir.join(&alias_name);
-            let release_path = async_fs::read_link(&alias_path).await?;
-            println!(
-                "{:<width$} ~> {}",
-                if alias_name == "default" {
-                    alias_name.clone().cyan().bold()
-                } else {
-                    alias_name.clone().normal().clear()
-                },
-                release_path
-                    .display()
-                    .to_string()
-                    .truecolor(128, 128, 128)
-                    .italic(),
-                width = alias_max_length + 1
-            );
+    if swap {
+        return swap_aliases(alias, target.unwrap_or_default()).await;
+    }
+
+    if force_default_switch {
+        if alias != "default" {
+            error!("--force-default-switch can only be used with the 'default' alias.");
         }
+        let Some(target) = target else {
+            error!("No version given. Usage: gvm alias default <version> --force-default-switch");
+        };
+        return utils::activate_version(utils::parse_version_arg(target)?, true).await;
+    }
 
-        return Ok(());
+    if alias == "list" || alias == "ls" {
+        return print_aliases(json).await;
+    }
+
+    if json {
+        error!("--json can only be used when listing aliases, e.g. 'gvm alias list --json'.");
     }
 
+    utils::validate_alias_name(&alias)?;
+
     let existing_aliases = utils::list_aliases().await?;
-    if existing_aliases.contains(&alias) {
-        error!(
-            "Alias {} already exists. Please choose a different alias.",
-            alias
-        );
+    let previous = if existing_aliases.contains(&alias) {
+        if !force {
+            error!(
+                "Alias {} already exists. Please choose a different alias or pass --force to repoint it.",
+                alias
+            );
+        }
+        let alias_path = utils::get_alias_file_path().join(&alias);
+        let link = async_fs::read_link(&alias_path).await?;
+        Some(
+            link.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| link.to_string_lossy().to_string()),
+        )
+    } else {
+        None
+    };
+
+    if let Some(to_alias) = to_alias {
+        return alias_to_alias(alias, to_alias, &existing_aliases, previous).await;
     }
 
-    let release_version = utils::get_real_version(target.unwrap_or_default());
+    let release_version = match target {
+        Some(target) => utils::parse_version_arg(target)?,
+        None => error!("No version given. Usage: gvm alias <alias> <version>"),
+    };
     let releases = utils::list_installed_versions().await?;
     if !releases.contains(&release_version) {
         error!(
@@ -67,16 +128,480 @@ pub async fn alias(alias: String, target: Option<String>) -> Res<()> {
         );
     }
 
-    info!(
-        "Creating alias {} for version {}...",
-        alias, release_version
-    );
     let release_dir = utils::get_version_file_path();
     let release_path = release_dir.join(&release_version);
     let alias_dir = utils::get_alias_file_path();
     let alias_file_path = alias_dir.join(&alias);
 
+    if let Some(previous) = previous {
+        info!(
+            "Repointing alias {} to version {}...",
+            alias, release_version
+        );
+        utils::replace_symlink(release_path, alias_file_path).await?;
+        success!(
+            "Alias {} changed from {} to {}.",
+            alias,
+            previous,
+            release_version
+        );
+        return Ok(());
+    }
+
+    info!(
+        "Creating alias {} for version {}...",
+        alias, release_version
+    );
     utils::create_symlink(release_path, alias_file_path).await?;
     success!("Alias {} created for version {}.", alias, release_version);
     Ok(())
 }
+
+/// Prints all aliases with the versions they point to.
+///
+/// Aliases of aliases show the version at the end of the chain and the alias
+/// they point to. Aliases whose version is gone, or whose symlink cannot be
+/// read, are marked as broken instead of aborting the listing.
+///
+/// # Parameters
+///
+/// * `json`: When `true`, the aliases are printed as a JSON array instead of
+///   colored, aligned text.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the aliases were printed, or an error if the alias
+/// directory cannot be read.
+async fn print_aliases(json: bool) -> Res<()> {
+    use colored::Colorize;
+
+    let entries = alias_entries().await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    let alias_max_length = entries.iter().map(|e| e.alias.len()).max().unwrap_or(0);
+    for entry in entries {
+        let mut release_path = entry.path.clone();
+        if let Some(ref via) = entry.via {
+            release_path.push_str(&format!(" (via {})", via));
+        }
+        if entry.dangling {
+            release_path.push_str(" (broken)");
+        }
+        println!(
+            "{:<width$} ~> {}",
+            if entry.alias == "default" {
+                entry.alias.cyan().bold()
+            } else {
+                entry.alias.normal().clear()
+            },
+            release_path.truecolor(128, 128, 128).italic(),
+            width = alias_max_length + 1
+        );
+    }
+
+    Ok(())
+}
+
+/// Collects every alias with the version it points to, see `print_aliases`.
+async fn alias_entries() -> Res<Vec<AliasEntry>> {
+    let alias_dir = utils::get_alias_file_path();
+    let mut entries = Vec::new();
+    for alias_name in utils::list_aliases().await? {
+        let alias_path = alias_dir.join(&alias_name);
+        // a single broken entry must not hide the remaining aliases
+        let link = match async_fs::read_link(&alias_path).await {
+            Ok(link) => link,
+            Err(e) => {
+                warning!("Cannot read alias '{}': {}", alias_name, e);
+                entries.push(AliasEntry {
+                    target: String::new(),
+                    path: alias_path.to_string_lossy().to_string(),
+                    via: None,
+                    dangling: true,
+                    alias: alias_name,
+                });
+                continue;
+            }
+        };
+        let resolved = utils::resolve_alias(&alias_name).await;
+        entries.push(AliasEntry {
+            target: utils::get_alias_target(&alias_name)
+                .await
+                .unwrap_or_default(),
+            path: resolved
+                .as_ref()
+                .unwrap_or(&link)
+                .to_string_lossy()
+                .to_string(),
+            via: utils::get_parent_alias(&alias_name).await,
+            dangling: resolved.is_err(),
+            alias: alias_name,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Creates an alias that points to another alias.
+///
+/// The new alias is a symlink to the other alias' symlink, so it follows the
+/// other alias when that is repointed. With `previous`, an existing alias
+/// that pointed at `previous` is repointed atomically.
+///
+/// # Parameters
+///
+/// * `alias`: The name of the alias to create.
+/// * `to_alias`: The name of the existing alias to point to.
+/// * `existing_aliases`: The names of all existing aliases.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the alias was created, or an error if `to_alias` does
+/// not exist or does not resolve to an installed version.
+async fn alias_to_alias(
+    alias: String,
+    to_alias: String,
+    existing_aliases: &[String],
+    previous: Option<String>,
+) -> Res<()> {
+    if to_alias != "default" {
+        utils::validate_alias_name(&to_alias)?;
+    }
+    if !existing_aliases.contains(&to_alias) {
+        error!("Alias {} does not exist. Please create it first.", to_alias);
+    }
+
+    let release_path = match utils::resolve_alias(&to_alias).await {
+        Ok(path) => path,
+        Err(e) => error!(
+            "Alias {} does not resolve to an installed version: {}",
+            to_alias, e
+        ),
+    };
+
+    let alias_dir = utils::get_alias_file_path();
+    if let Some(previous) = previous {
+        // repointing must not make the alias part of its own chain
+        let mut parent = Some(to_alias.clone());
+        while let Some(current) = parent {
+            if current == alias {
+                error!(
+                    "Alias {} cannot point at {}, which follows it.",
+                    alias, to_alias
+                );
+            }
+            parent = utils::get_parent_alias(&current).await;
+        }
+
+        info!("Repointing alias {} to alias {}...", alias, to_alias);
+        utils::replace_symlink(alias_dir.join(&to_alias), alias_dir.join(&alias)).await?;
+        success!(
+            "Alias {} changed from {} to {} ({}).",
+            alias,
+            previous,
+            to_alias,
+            release_path.display()
+        );
+        return Ok(());
+    }
+
+    info!("Creating alias {} for alias {}...", alias, to_alias);
+    utils::create_symlink(alias_dir.join(&to_alias), alias_dir.join(&alias)).await?;
+    success!(
+        "Alias {} created for alias {} ({}).",
+        alias,
+        to_alias,
+        release_path.display()
+    );
+    Ok(())
+}
+
+/// Exchanges the targets of two existing aliases.
+///
+/// Both symlinks are read first and then each one is atomically replaced by a
+/// link to the other's target, so both aliases stay valid throughout. If the
+/// second replacement fails, the first one is reverted. If the swap would make
+/// an alias of an alias point to itself, directly or through other aliases, it
+/// is reverted as well.
+///
+/// # Parameters
+///
+/// * `first`: The name of the first alias.
+/// * `second`: The name of the second alias.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the targets were swapped, or an error if either alias is
+/// `default`, does not exist or no longer resolves, or the swap would create
+/// a cycle.
+async fn swap_aliases(first: String, second: String) -> Res<()> {
+    if first == "default" || second == "default" {
+        error!("Swapping the 'default' alias is not allowed. Use 'gvm use' instead.");
+    }
+    utils::validate_alias_name(&first)?;
+    utils::validate_alias_name(&second)?;
+
+    let alias_dir = utils::get_alias_file_path();
+    let first_path = alias_dir.join(&first);
+    let second_path = alias_dir.join(&second);
+
+    let first_target = match async_fs::read_link(&first_path).await {
+        Ok(target) => target,
+        Err(_) => error!("Alias {} does not exist.", first),
+    };
+    let second_target = match async_fs::read_link(&second_path).await {
+        Ok(target) => target,
+        Err(_) => error!("Alias {} does not exist.", second),
+    };
+    // both aliases resolve before the swap, so an alias that no longer resolves
+    // afterwards can only be caused by a cycle
+    for alias in [&first, &second] {
+        if let Err(err) = utils::resolve_alias(alias).await {
+            error!(
+                "Alias {} is broken ({}). Repoint or remove it before swapping.",
+                alias, err
+            );
+        }
+    }
+
+    info!("Swapping aliases {} and {}...", first, second);
+    utils::replace_symlink(&second_target, &first_path).await?;
+    if let Err(err) = utils::replace_symlink(&first_target, &second_path).await {
+        if let Err(restore_err) = utils::replace_symlink(&first_target, &first_path).await {
+            error!(
+                "Failed to repoint alias {}: {}. Alias {} could not be restored to {}: {}",
+                second,
+                err,
+                first,
+                first_target.display(),
+                restore_err
+            );
+        }
+        error!(
+            "Failed to repoint alias {}: {}. Nothing changed.",
+            second, err
+        );
+    }
+
+    if utils::resolve_alias(&first).await.is_err() || utils::resolve_alias(&second).await.is_err() {
+        utils::replace_symlink(&first_target, &first_path).await?;
+        utils::replace_symlink(&second_target, &second_path).await?;
+        error!(
+            "Swapping aliases {} and {} would create a cycle. Nothing changed.",
+            first, second
+        );
+    }
+
+    success!(
+        "Alias {} now points to {}, alias {} now points to {}.",
+        first,
+        second_target.display(),
+        second,
+        first_target.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::{
+        alias_exists, assert_rejected, fake_alias, fake_version, MALICIOUS_NAMES,
+    };
+
+    async fn link(alias: &str) -> std::path::PathBuf {
+        async_fs::read_link(utils::get_alias_file_path().join(alias))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reserved_names_cannot_be_aliased() {
+        fake_version("go1.34.1");
+
+        // `list` and `ls` list the aliases instead
+        for name in crate::config::RESERVED_ALIAS_NAMES
+            .iter()
+            .filter(|name| !["list", "ls"].contains(name))
+        {
+            let err = alias(
+                name.to_string(),
+                Some("go1.34.1".to_string()),
+                AliasOptions::default(),
+            )
+            .await
+            .unwrap_err();
+            if *name != "default" {
+                assert!(err.to_string().contains("reserved name"), "{}", err);
+                assert!(!alias_exists(name), "{}", name);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn listing_marks_dangling_aliases_and_keeps_going() {
+        fake_version("go1.88.1");
+        fake_alias("t288-good", "go1.88.1", false);
+        fake_alias("t288-dangling", "go1.88.404", false);
+
+        let entries = alias_entries().await.unwrap();
+        let entry = |alias: &str| entries.iter().find(|e| e.alias == alias).unwrap();
+
+        assert_eq!(entry("t288-good").target, "go1.88.1");
+        assert!(!entry("t288-good").dangling);
+        assert!(entry("t288-dangling").dangling);
+        print_aliases(false).await.unwrap();
+        print_aliases(true).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn force_repoints_an_existing_alias() {
+        fake_version("go1.31.1");
+        let new_version = fake_version("go1.31.2");
+        fake_alias("t312-stable", "go1.31.1", false);
+
+        let err = alias(
+            "t312-stable".to_string(),
+            Some("go1.31.2".to_string()),
+            AliasOptions::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("already exists"), "{}", err);
+        assert_eq!(
+            utils::get_alias_target("t312-stable").await.as_deref(),
+            Some("go1.31.1")
+        );
+
+        let options = AliasOptions {
+            force: true,
+            ..AliasOptions::default()
+        };
+        alias(
+            "t312-stable".to_string(),
+            Some("go1.31.2".to_string()),
+            options,
+        )
+        .await
+        .unwrap();
+        assert_eq!(link("t312-stable").await, new_version);
+    }
+
+    #[tokio::test]
+    async fn swap_exchanges_the_targets() {
+        fake_version("go1.29.1");
+        fake_version("go1.29.2");
+        fake_alias("t229-a", "go1.29.1", false);
+        fake_alias("t229-b", "go1.29.2", false);
+
+        swap_aliases("t229-a".to_string(), "t229-b".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            utils::get_alias_target("t229-a").await.as_deref(),
+            Some("go1.29.2")
+        );
+        assert_eq!(
+            utils::get_alias_target("t229-b").await.as_deref(),
+            Some("go1.29.1")
+        );
+    }
+
+    #[tokio::test]
+    async fn swap_with_a_missing_alias_changes_nothing() {
+        fake_version("go1.29.3");
+        fake_alias("t229-c", "go1.29.3", false);
+        let before = link("t229-c").await;
+
+        let err = swap_aliases("t229-c".to_string(), "t229-missing".to_string())
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("does not exist"), "{}", err);
+        assert_eq!(link("t229-c").await, before);
+    }
+
+    #[tokio::test]
+    async fn swap_with_a_broken_alias_is_not_reported_as_a_cycle() {
+        fake_version("go1.29.4");
+        fake_alias("t229-d", "go1.29.4", false);
+        fake_alias("t229-dangling", "go1.29.99", false);
+        let before = (link("t229-d").await, link("t229-dangling").await);
+
+        let err = swap_aliases("t229-d".to_string(), "t229-dangling".to_string())
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("is broken"), "{}", err);
+        assert!(!err.to_string().contains("cycle"), "{}", err);
+        assert_eq!((link("t229-d").await, link("t229-dangling").await), before);
+    }
+
+    #[tokio::test]
+    async fn swap_creating_a_cycle_is_reverted() {
+        fake_version("go1.29.5");
+        fake_alias("t229-x", "go1.29.5", false);
+        fake_alias("t229-y", "t229-x", true);
+        let before = (link("t229-x").await, link("t229-y").await);
+
+        let err = swap_aliases("t229-x".to_string(), "t229-y".to_string())
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("cycle"), "{}", err);
+        assert_eq!((link("t229-x").await, link("t229-y").await), before);
+        assert!(utils::resolve_alias("t229-y").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn alias_rejects_malicious_names() {
+        for name in MALICIOUS_NAMES {
+            assert_rejected(
+                name,
+                alias(
+                    name.to_string(),
+                    Some("go1.22.0".to_string()),
+                    AliasOptions::default(),
+                )
+                .await,
+            );
+            assert_rejected(
+                name,
+                alias(
+                    "stable".to_string(),
+                    Some(name.to_string()),
+                    AliasOptions::default(),
+                )
+                .await,
+            );
+            assert_rejected(
+                name,
+                alias(
+                    "stable".to_string(),
+                    Some(name.to_string()),
+                    AliasOptions {
+                        swap: true,
+                        ..AliasOptions::default()
+                    },
+                )
+                .await,
+            );
+            assert_rejected(
+                name,
+                alias(
+                    "stable".to_string(),
+                    None,
+                    AliasOptions {
+                        to_alias: Some(name.to_string()),
+                        ..AliasOptions::default()
+                    },
+                )
+                .await,
+            );
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::{fs, path::Path};
+
+use indicatif::HumanBytes;
+
+use crate::{config, error, info, success, utils, Res};
+
+/// Actions of `gvm cache`.
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum CacheAction {
+    #[clap(about = "Print the cache directory")]
+    Path,
+
+    #[clap(about = "Remove the release cache, and optionally the build caches")]
+    Clear {
+        #[clap(
+            long,
+            conflicts_with_all = ["all", "archives"],
+            help = "Only clear the go-build caches and keep the release cache"
+        )]
+        build: bool,
+
+        #[clap(
+            long,
+            conflicts_with = "all",
+            help = "Only empty the archive directory (cached downloads and leftovers of interrupted installs)"
+        )]
+        archives: bool,
+
+        #[clap(
+            long,
+            help = "Clear the release cache, the go-build caches and the archive directory"
+        )]
+        all: bool,
+    },
+
+    #[clap(about = "Print the size of the cache directory")]
+    Size,
+}
+
+/// Removes the release caches written by `gvm update`.
+///
+/// Caches of foreign platforms (e.g. "releases.darwin-arm64.json") are
+/// removed as well.
+fn clear_release_caches(cache_dir: &Path) -> Res<()> {
+    let stem = Path::new(config::RELEASE_CACHE_FILE)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    let mut removed = 0;
+    for entry in fs::read_dir(cache_dir)? {
+        let path = entry?.path();
+        let is_release_cache = path.is_file()
+            && path.extension().is_some_and(|e| e == "json")
+            && path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(stem));
+        if !is_release_cache {
+            continue;
+        }
+        match fs::remove_file(&path) {
+            Ok(_) => removed += 1,
+            Err(e) => error!("Failed to remove {}: {}", path.display(), e),
+        }
+    }
+    success!("Removed {} release cache file(s).", removed);
+    Ok(())
+}
+
+/// Empties the `go-build` cache of every version.
+///
+/// Each `go-build` directory is recreated empty, so builds with the active
+/// version keep working without running `gvm use` again.
+fn clear_build_caches(cache_dir: &Path) -> Res<()> {
+    let mut cleared = 0;
+    for entry in fs::read_dir(cache_dir)? {
+        let build_cache = entry?.path().join("go-build");
+        if !build_cache.is_dir() {
+            continue;
+        }
+        info!("Clearing {} ...", build_cache.display());
+        if let Err(e) =
+            fs::remove_dir_all(&build_cache).and_then(|_| fs::create_dir_all(&build_cache))
+        {
+            error!("Failed to clear {}: {}", build_cache.display(), e);
+        }
+        cleared += 1;
+    }
+    success!("Cleared {} build cache(s).", cleared);
+    Ok(())
+}
+
+/// Removes the cached release archives, and downloads and extraction
+/// directories left behind by interrupted installs.
+///
+/// Only call this while no install is running, since it cannot tell an
+/// abandoned download from one in progress.
+fn clear_archives() -> Res<()> {
+    let archive_dir = utils::get_archive_file_path();
+    if !archive_dir.is_dir() {
+        return Ok(());
+    }
+    let mut removed = 0;
+    for entry in fs::read_dir(&archive_dir)? {
+        let path = entry?.path();
+        let result = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        match result {
+            Ok(_) => removed += 1,
+            Err(e) => error!("Failed to remove {}: {}", path.display(), e),
+        }
+    }
+    success!(
+        "Removed {} item(s) from {}.",
+        removed,
+        archive_dir.display()
+    );
+    Ok(())
+}
+
+/// Inspects or clears the GVM cache directory.
+///
+/// The cache directory holds the release cache written by `gvm update` and a
+/// `go-build` cache (GOCACHE) per version.
+///
+/// # Parameters
+///
+/// * `action`: What to do with the cache:
+///   - `Path` prints the cache directory.
+///   - `Clear` removes the release cache, only the `go-build` caches with
+///     `build`, only the cached archives and the leftovers of interrupted
+///     installs in the archive directory with `archives`, or all of them with
+///     `all`.
+///   - `Size` prints the total size of the cache directory.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` on success, or an error if the cache
+/// directory cannot be read or a cache cannot be removed.
+pub async fn cache(action: CacheAction) -> Res<()> {
+    let cache_dir = utils::get_cache_dir();
+
+    match action {
+        CacheAction::Path => println!("{}", cache_dir.display()),
+        CacheAction::Size => {
+            let size = if cache_dir.is_dir() {
+                utils::dir_size(&cache_dir)?
+            } else {
+                0
+            };
+            println!("{}\t{}", HumanBytes(size), cache_dir.display());
+        }
+        CacheAction::Clear {
+            build,
+            archives,
+            all,
+        } => {
+            if archives || all {
+                clear_archives()?;
+            }
+            if archives {
+                return Ok(());
+            }
+            if !cache_dir.is_dir() {
+                success!("Cache directory {} does not exist.", cache_dir.display());
+                return Ok(());
+            }
+            if !build {
+                clear_release_caches(&cache_dir)?;
+            }
+            if build || all {
+                clear_build_caches(&cache_dir)?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,279 @@
+use std::{fs, path::PathBuf};
+
+use clap_complete::Shell;
+
+use crate::{error, info, success, utils, Res};
+
+/// Bash wrapper around the generated `_gvm_clap` completion function.
+///
+/// Installed versions and alias names are completed by calling back into
+/// `gvm __complete_versions` and `gvm __complete_aliases`; everything else is
+/// left to the generated function.
+const BASH_DYNAMIC_COMPLETION: &str = r#"
+_gvm() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    local candidates=""
+    case "${COMP_WORDS[1]}:${COMP_CWORD}" in
+        use:2|remove:*|which:2|exec:2|env:2|export-env:2|local:2|alias:3)
+            candidates="$(gvm __complete_versions 2>/dev/null)"
+            ;;
+        remove-alias:2|rename-alias:2)
+            candidates="$(gvm __complete_aliases 2>/dev/null)"
+            ;;
+    esac
+    if [[ -n "$candidates" && "$cur" != -* ]]; then
+        COMPREPLY=($(compgen -W "$candidates" -- "$cur"))
+        return 0
+    fi
+    _gvm_clap "$@"
+}
+"#;
+
+/// Zsh counterpart of `BASH_DYNAMIC_COMPLETION`.
+const ZSH_DYNAMIC_COMPLETION: &str = r#"
+_gvm() {
+    local -a candidates
+    case "$words[2]:$CURRENT" in
+        use:3|remove:*|which:3|exec:3|env:3|export-env:3|local:3|alias:4)
+            candidates=(${(f)"$(gvm __complete_versions 2>/dev/null)"})
+            ;;
+        remove-alias:3|rename-alias:3)
+            candidates=(${(f)"$(gvm __complete_aliases 2>/dev/null)"})
+            ;;
+    esac
+    if (( ${#candidates} )) && [[ "$PREFIX" != -* ]]; then
+        compadd -a candidates
+        return
+    fi
+    _gvm_clap "$@"
+}
+"#;
+
+/// Fish completions for installed versions and alias names.
+const FISH_DYNAMIC_COMPLETION: &str = r#"
+complete -c gvm -n "__fish_seen_subcommand_from use remove which exec env export-env local" -f -a "(gvm __complete_versions)"
+complete -c gvm -n "__fish_seen_subcommand_from remove-alias rename-alias" -f -a "(gvm __complete_aliases)"
+"#;
+
+/// Adds completion of installed versions and alias names to a generated script.
+///
+/// The scripts generated by clap only know static values. For bash and zsh,
+/// the generated `_gvm` function is renamed to `_gvm_clap` and wrapped by a
+/// `_gvm` function that asks `gvm __complete_versions` or
+/// `gvm __complete_aliases` for candidates where a version or alias name is
+/// expected. For fish, the matching `complete` rules are appended. Other
+/// shells are returned unchanged.
+///
+/// # Parameters
+///
+/// * `shell`: The shell the script was generated for.
+/// * `script`: The script generated by clap.
+///
+/// # Returns
+///
+/// The completion script with dynamic completions, or an error if the bash or
+/// zsh script does not have the shape the wrapper relies on, e.g. after a
+/// `clap_complete` upgrade changed it.
+pub fn add_dynamic_completions(shell: Shell, script: String) -> Res<String> {
+    let unexpected = || -> Res<String> {
+        error!(
+            "The generated {} completion script has an unexpected format; cannot add version completion.",
+            shell
+        )
+    };
+    match shell {
+        Shell::Bash => {
+            if !script.starts_with("_gvm() {") && !script.contains("\n_gvm() {") {
+                return unexpected();
+            }
+            let script = script.replacen("_gvm() {", "_gvm_clap() {", 1);
+            Ok(format!("{}{}", script, BASH_DYNAMIC_COMPLETION))
+        }
+        Shell::Zsh => {
+            if !script.contains("\n_gvm() {") {
+                return unexpected();
+            }
+            // The dispatch at the end of the script must call the wrapper.
+            let script = script.replacen("\n_gvm() {", "\n_gvm_clap() {", 1);
+            match script.rfind("\nif [ \"$funcstack[1]\" = \"_gvm\" ]") {
+                Some(dispatch) => Ok(format!(
+                    "{}{}{}",
+                    &script[..dispatch],
+                    ZSH_DYNAMIC_COMPLETION,
+                    &script[dispatch..]
+                )),
+                None => unexpected(),
+            }
+        }
+        Shell::Fish => Ok(format!("{}{}", script, FISH_DYNAMIC_COMPLETION)),
+        _ => Ok(script),
+    }
+}
+
+/// Returns where the completion script for a shell is installed.
+///
+/// The conventional locations are the ones `gvm init` sets up:
+/// - bash: `~/.bash_completions/gvm`
+/// - zsh: `~/.zsh/completions/_gvm`, which `gvm init` adds to `fpath`
+/// - fish: `~/.config/fish/completions/gvm.fish`
+///
+/// # Parameters
+///
+/// * `shell`: The shell the script is for.
+/// * `dir`: A directory to use instead of the conventional one.
+///
+/// # Returns
+///
+/// The path of the completion file, or an error if the shell has no
+/// conventional location and no `dir` is given, or the home directory is
+/// unknown.
+fn completion_file_path(shell: Shell, dir: Option<PathBuf>) -> Res<PathBuf> {
+    let file_name = match shell {
+        Shell::Bash => "gvm",
+        Shell::Zsh => "_gvm",
+        Shell::Fish => "gvm.fish",
+        Shell::Elvish => "gvm.elv",
+        Shell::PowerShell => "_gvm.ps1",
+        _ => "gvm",
+    };
+    if let Some(dir) = dir {
+        return Ok(dir.join(file_name));
+    }
+
+    let home = match dirs::home_dir() {
+        Some(home) => home,
+        None => error!("Cannot access HOME dir. Pass --dir to choose where to write completions."),
+    };
+    let dir = match shell {
+        Shell::Bash => home.join(".bash_completions"),
+        Shell::Zsh => home.join(".zsh").join("completions"),
+        Shell::Fish => home.join(".config").join("fish").join("completions"),
+        _ => error!(
+            "There is no conventional completion directory for {}. Pass --dir to choose one.",
+            shell
+        ),
+    };
+    Ok(dir.join(file_name))
+}
+
+/// Writes a completion script to its file, creating directories as needed.
+///
+/// # Parameters
+///
+/// * `shell`: The shell the script is for.
+/// * `script`: The completion script.
+/// * `dir`: A directory to write to instead of the conventional one, see
+///   `completion_file_path`.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` once the file is written, or an error
+/// if the location cannot be determined or written.
+pub fn install_completions(shell: Shell, script: &str, dir: Option<PathBuf>) -> Res<()> {
+    let path = completion_file_path(shell, dir)?;
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, script));
+    if let Err(e) = result {
+        error!("Failed to write {}: {}", path.display(), e);
+    }
+    success!("Completions for {} written to {}.", shell, path.display());
+
+    match shell {
+        Shell::Bash => info!(
+            "Source it from ~/.bashrc if your profile doesn't already: source {}",
+            path.display()
+        ),
+        Shell::Zsh => info!(
+            "Make sure {} is in fpath before compinit runs ('gvm init' does this for the default directory).",
+            path.parent().unwrap_or(&path).display()
+        ),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Prints the installed versions, one per line, for shell completion.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is always `Ok(())`. Errors are swallowed so a
+/// missing GVM directory just yields no candidates.
+pub async fn complete_versions() -> Res<()> {
+    let mut versions = utils::list_installed_versions().await.unwrap_or_default();
+    versions.sort_by(|a, b| utils::cmp_versions(a, b));
+    for version in versions {
+        println!("{}", version);
+    }
+
+    Ok(())
+}
+
+/// Prints the alias names, one per line, for shell completion.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is always `Ok(())`. Errors are swallowed so a
+/// missing GVM directory just yields no candidates.
+pub async fn complete_aliases() -> Res<()> {
+    let mut aliases = utils::list_aliases().await.unwrap_or_default();
+    aliases.sort();
+    for alias in aliases {
+        println!("{}", alias);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completion_files_use_the_conventional_locations() {
+        let home = dirs::home_dir().unwrap();
+        for (shell, path) in [
+            (Shell::Bash, ".bash_completions/gvm"),
+            (Shell::Zsh, ".zsh/completions/_gvm"),
+            (Shell::Fish, ".config/fish/completions/gvm.fish"),
+        ] {
+            assert_eq!(completion_file_path(shell, None).unwrap(), home.join(path));
+        }
+        assert!(completion_file_path(Shell::Elvish, None).is_err());
+    }
+
+    #[test]
+    fn completion_files_can_be_written_to_any_directory() {
+        let dir = PathBuf::from("/opt/completions");
+        for (shell, file_name) in [
+            (Shell::Bash, "gvm"),
+            (Shell::Zsh, "_gvm"),
+            (Shell::Fish, "gvm.fish"),
+            (Shell::Elvish, "gvm.elv"),
+            (Shell::PowerShell, "_gvm.ps1"),
+        ] {
+            assert_eq!(
+                completion_file_path(shell, Some(dir.clone())).unwrap(),
+                dir.join(file_name)
+            );
+        }
+    }
+
+    #[test]
+    fn unexpected_scripts_are_rejected() {
+        for shell in [Shell::Bash, Shell::Zsh] {
+            let err = add_dynamic_completions(shell, "_other() {\n}\n".to_string()).unwrap_err();
+            assert!(err.to_string().contains("unexpected format"), "{}", err);
+        }
+        // zsh without the dispatch would never call the wrapper
+        let zsh = "#compdef gvm\n\n_gvm() {\n}\n".to_string();
+        assert!(add_dynamic_completions(Shell::Zsh, zsh).is_err());
+
+        let elvish = "set edit:completion:arg-completer[gvm] = {}\n".to_string();
+        assert_eq!(
+            add_dynamic_completions(Shell::Elvish, elvish.clone()).unwrap(),
+            elvish
+        );
+    }
+}
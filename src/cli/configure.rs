@@ -0,0 +1,162 @@
+use std::fs;
+
+use crate::{
+    config::{self, Config, CONFIG_FILE, CONFIG_KEYS},
+    error, success, utils, Res,
+};
+
+/// Actions of `gvm config`.
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum ConfigAction {
+    #[clap(about = "Print the effective value of a setting, or of all settings")]
+    Get {
+        #[clap(value_parser = CONFIG_KEYS)]
+        key: Option<String>,
+    },
+
+    #[clap(about = "Write a setting to ~/.gvm/config.toml")]
+    Set {
+        #[clap(value_parser = CONFIG_KEYS)]
+        key: String,
+        value: String,
+    },
+
+    #[clap(about = "Remove a setting from ~/.gvm/config.toml")]
+    Unset {
+        #[clap(value_parser = CONFIG_KEYS)]
+        key: String,
+    },
+}
+
+/// Returns the value of a setting as gvm uses it, after applying the
+/// environment variables and the defaults.
+fn effective_value(key: &str) -> Res<String> {
+    let value = match key {
+        "download_base" => utils::get_download_base()?.to_string(),
+        "releases_url" => utils::get_releases_url()?.to_string(),
+        "default_jobs" => utils::get_install_jobs().to_string(),
+        "cache_ttl_hours" => (utils::get_release_cache_max_age().as_secs() / 3600).to_string(),
+        "list_stable_by_default" => utils::list_stable_by_default().to_string(),
+        "shared_gopath" => utils::is_gopath_shared().to_string(),
+        _ => error!("Unknown setting '{}'.", key),
+    };
+    Ok(value)
+}
+
+/// Parses a number or boolean given to `gvm config set`.
+fn parse_value<T: std::str::FromStr>(key: &str, value: &str, expected: &str) -> Res<T> {
+    match value.trim().parse() {
+        Ok(parsed) => Ok(parsed),
+        Err(_) => error!(
+            "Invalid value '{}' for {}: expected {}.",
+            value, key, expected
+        ),
+    }
+}
+
+/// Sets or clears a setting.
+///
+/// # Parameters
+///
+/// * `settings`: The settings to change.
+/// * `key`: The setting to change.
+/// * `value`: The new value, or `None` to remove the setting.
+///
+/// # Returns
+///
+/// `Ok(())`, or an error if the key is unknown or the value has the wrong type.
+fn apply(settings: &mut Config, key: &str, value: Option<&str>) -> Res<()> {
+    match key {
+        "download_base" => {
+            if let Some(value) = value {
+                utils::parse_url_setting(key, value)?;
+            }
+            settings.download_base = value.map(str::to_string);
+        }
+        "releases_url" => {
+            if let Some(value) = value {
+                utils::parse_url_setting(key, value)?;
+            }
+            settings.releases_url = value.map(str::to_string);
+        }
+        "default_jobs" => {
+            settings.default_jobs = match value {
+                Some(value) => match parse_value(key, value, "a positive number")? {
+                    0 => error!(
+                        "Invalid value '{}' for {}: expected a positive number.",
+                        value, key
+                    ),
+                    jobs => Some(jobs),
+                },
+                None => None,
+            };
+        }
+        "cache_ttl_hours" => {
+            settings.cache_ttl_hours = value
+                .map(|value| parse_value(key, value, "a number of hours"))
+                .transpose()?;
+        }
+        "list_stable_by_default" => {
+            settings.list_stable_by_default = value
+                .map(|value| parse_value(key, value, "'true' or 'false'"))
+                .transpose()?;
+        }
+        "shared_gopath" => {
+            settings.shared_gopath = value
+                .map(|value| parse_value(key, value, "'true' or 'false'"))
+                .transpose()?;
+        }
+        _ => error!("Unknown setting '{}'.", key),
+    }
+    Ok(())
+}
+
+/// Reads or changes the settings in `~/.gvm/config.toml`.
+///
+/// Settings are applied in this order: environment variables (e.g.
+/// `GVM_RELEASES_URL`), then the settings file, then the built-in defaults.
+///
+/// # Parameters
+///
+/// * `action`: What to do with the settings:
+///   - `Get` prints the effective value of one setting, or `key = value` for
+///     all settings.
+///   - `Set` validates the value and writes it to the settings file.
+///   - `Unset` removes the setting from the settings file.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` on success, or an error if the value
+/// is invalid or the settings file cannot be read or written.
+pub async fn configure(action: ConfigAction) -> Res<()> {
+    let (key, value) = match action {
+        ConfigAction::Get { key: Some(key) } => {
+            println!("{}", effective_value(&key)?);
+            return Ok(());
+        }
+        ConfigAction::Get { key: None } => {
+            for key in CONFIG_KEYS {
+                println!("{} = {}", key, effective_value(key)?);
+            }
+            return Ok(());
+        }
+        ConfigAction::Set { key, value } => (key, Some(value)),
+        ConfigAction::Unset { key } => (key, None),
+    };
+
+    let base_dir = utils::get_gvm_base_file_path();
+    let path = base_dir.join(CONFIG_FILE);
+    let mut settings = config::read_config_file(&path)?;
+    apply(&mut settings, &key, value.as_deref())?;
+
+    fs::create_dir_all(&base_dir)?;
+    if let Err(e) = fs::write(&path, toml::to_string_pretty(&settings)?) {
+        error!("Failed to write {}: {}", path.display(), e);
+    }
+
+    match value {
+        Some(value) => success!("Set {} to {} in {}.", key, value, path.display()),
+        None => success!("Removed {} from {}.", key, path.display()),
+    }
+    Ok(())
+}
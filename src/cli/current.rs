@@ -0,0 +1,33 @@
+use std::io::{self, IsTerminal};
+
+use crate::{error, utils, Res};
+
+/// Prints the active version.
+///
+/// Only the version (e.g. `go1.22.3`) is printed, so the output can be used
+/// directly in scripts. Colors are disabled when stdout is not a terminal.
+///
+/// # Parameters
+///
+/// * `quiet`: When `true`, nothing is printed and only the returned flag tells
+///   whether a version is active.
+///
+/// # Returns
+///
+/// Returns `Res<bool>`, which is `Ok(true)` if a version is active and
+/// `Ok(false)` if none is active in quiet mode. Without `quiet`, an error is
+/// returned if no version is active.
+pub async fn current(quiet: bool) -> Res<bool> {
+    if !io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
+
+    match utils::get_active_version().await {
+        Some(version) if !quiet => println!("{}", version),
+        Some(_) => {}
+        None if quiet => return Ok(false),
+        None => error!("No active version found. Use 'gvm use <version>' to activate one."),
+    }
+
+    Ok(true)
+}
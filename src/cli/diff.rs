@@ -0,0 +1,57 @@
+use std::cmp::Ordering;
+
+use crate::{info, utils, Res};
+
+/// Compares two Go versions and reports which one is newer.
+///
+/// This function normalizes both versions, compares them with `cmp_versions`,
+/// reports whether they belong to the same minor line and counts the stable
+/// releases in the release cache that lie strictly between them.
+///
+/// # Parameters
+///
+/// * `first`: The first version to compare.
+/// * `second`: The second version to compare.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` if the comparison was printed, or an
+/// error if the release cache cannot be parsed.
+pub async fn diff(first: String, second: String) -> Res<()> {
+    let first = utils::parse_version_arg(first)?;
+    let second = utils::parse_version_arg(second)?;
+
+    let (older, newer) = match utils::cmp_versions(&first, &second) {
+        Ordering::Equal => {
+            println!("{} and {} are the same version.", first, second);
+            return Ok(());
+        }
+        Ordering::Less => (first, second),
+        Ordering::Greater => (second, first),
+    };
+
+    println!("{} is newer than {}", newer, older);
+
+    let (older_base, _) = utils::parse_version_parts(&older);
+    let (newer_base, _) = utils::parse_version_parts(&newer);
+    let same_minor = older_base.iter().take(2).eq(newer_base.iter().take(2));
+    println!("same minor line: {}", if same_minor { "yes" } else { "no" });
+
+    let cache_file = utils::get_release_cache_file_path();
+    if !cache_file.exists() {
+        info!("Release cache not found. Run 'gvm update' to count releases in between.");
+        return Ok(());
+    }
+
+    let releases = utils::list_cached_versions(cache_file, None, true).await?;
+    let in_between = releases
+        .iter()
+        .filter(|r| {
+            utils::cmp_versions(&r.version, &older) == Ordering::Greater
+                && utils::cmp_versions(&r.version, &newer) == Ordering::Less
+        })
+        .count();
+    println!("stable releases in between: {}", in_between);
+
+    Ok(())
+}
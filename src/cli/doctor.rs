@@ -0,0 +1,226 @@
+use std::{fs, path::Path};
+
+use crate::{config, error, info, success, utils, warning, Res};
+
+/// Prints the outcome of a single check.
+///
+/// # Parameters
+///
+/// * `ok`: Whether the check passed.
+/// * `passed`: What was verified, printed if the check passed.
+/// * `failed`: What is wrong and how to fix it, printed if the check failed.
+///
+/// # Returns
+///
+/// `ok`, so failures can be counted.
+fn report(ok: bool, passed: &str, failed: &str) -> bool {
+    if ok {
+        success!("{}", passed);
+    } else {
+        warning!("{}", failed);
+    }
+    ok
+}
+
+/// Reads the GOROOT recorded in a `go.env` file.
+///
+/// # Parameters
+///
+/// * `content`: The content of the `go.env` file.
+///
+/// # Returns
+///
+/// The value of the `GOROOT` line with the quoting of `utils::shell_quote`
+/// undone, if present.
+fn parse_goroot(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("GOROOT=")?;
+        match value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+            Some(quoted) => Some(quoted.replace("'\\''", "'")),
+            // files written before values were single-quoted
+            None => Some(value.trim_matches('"').replace("\\\"", "\"")),
+        }
+    })
+}
+
+/// Checks that the directories created by `gvm init` exist.
+fn check_directories() -> usize {
+    let directories = [
+        ("alias", utils::get_alias_file_path()),
+        ("archive", utils::get_archive_file_path()),
+        ("cache", utils::get_cache_dir()),
+        ("environment", utils::get_environment_file_path()),
+        ("package", utils::get_package_file_path()),
+        ("version", utils::get_version_file_path()),
+    ];
+
+    directories
+        .iter()
+        .filter(|(name, path)| {
+            !report(
+                path.is_dir(),
+                &format!("The {} directory {} exists.", name, path.display()),
+                &format!(
+                    "The {} directory {} is missing. Run 'gvm init' to create it.",
+                    name,
+                    path.display()
+                ),
+            )
+        })
+        .count()
+}
+
+/// Checks that every alias points at an installed version.
+async fn check_aliases() -> Res<usize> {
+    let alias_dir = utils::get_alias_file_path();
+    let mut failed = 0;
+    for alias in utils::list_aliases().await? {
+        let alias_path = alias_dir.join(&alias);
+        let resolves = fs::metadata(&alias_path).is_ok_and(|m| m.is_dir());
+        let target = utils::get_alias_target(&alias).await.unwrap_or_default();
+        let hint = if alias == "default" {
+            "Run 'gvm alias default <version> --force-default-switch' or 'gvm remove-alias default'.".to_string()
+        } else {
+            format!(
+                "Run 'gvm remove-alias {}' or point it at an installed version.",
+                alias
+            )
+        };
+        if !report(
+            resolves,
+            &format!("Alias '{}' resolves to {}.", alias, target),
+            &format!(
+                "Alias '{}' points at {}, which is not installed. {}",
+                alias, target, hint
+            ),
+        ) {
+            failed += 1;
+        }
+    }
+    Ok(failed)
+}
+
+/// Checks the active version and the `go.env` file written for it.
+async fn check_active_version() -> Res<usize> {
+    let active_version = match utils::read_active_file().await {
+        Some(version) => version,
+        None => {
+            info!("No version is active. Skipping the go.env check.");
+            return Ok(0);
+        }
+    };
+
+    let installed_versions = utils::list_installed_versions().await?;
+    if !report(
+        installed_versions.contains(&active_version),
+        &format!("Active version {} is installed.", active_version),
+        &format!(
+            "Active version {} is not installed. Run 'gvm use <version>' with an installed version, or reinstall it.",
+            active_version
+        ),
+    ) {
+        return Ok(1);
+    }
+
+    let go_env_path = utils::get_environment_file_path().join("go.env");
+    let expected_goroot = utils::get_version_file_path().join(&active_version);
+    let goroot = fs::read_to_string(&go_env_path)
+        .ok()
+        .as_deref()
+        .and_then(parse_goroot);
+    let ok = goroot.as_deref().map(Path::new) == Some(expected_goroot.as_path());
+    if !report(
+        ok,
+        &format!(
+            "{} sets GOROOT to {}.",
+            go_env_path.display(),
+            expected_goroot.display()
+        ),
+        &format!(
+            "{} is missing or does not set GOROOT to {}. Run 'gvm use {}' to rewrite it.",
+            go_env_path.display(),
+            expected_goroot.display(),
+            active_version
+        ),
+    ) {
+        return Ok(1);
+    }
+
+    Ok(0)
+}
+
+/// Checks that the shell profile sources the block written by `gvm init`.
+fn check_profile() -> usize {
+    let init_script = utils::get_gvm_base_file_path().join("init-shell");
+    let mut failed = 0;
+    if !report(
+        init_script.is_file(),
+        &format!("Init script {} exists.", init_script.display()),
+        &format!(
+            "Init script {} is missing. Run 'gvm init' to create it.",
+            init_script.display()
+        ),
+    ) {
+        failed += 1;
+    }
+
+    let shell_config_path = match utils::get_shell_config_file_path() {
+        Ok(path) => path,
+        Err(e) => {
+            warning!("{}. Wire up the shell profile by hand.", e);
+            return failed + 1;
+        }
+    };
+    let content = fs::read_to_string(&shell_config_path).unwrap_or_default();
+    let initialized =
+        content.contains(config::INIT_START_MARKER) && content.contains(config::INIT_END_MARKER);
+    if !report(
+        initialized,
+        &format!(
+            "{} contains the gvm init block.",
+            shell_config_path.display()
+        ),
+        &format!(
+            "{} does not contain the gvm init block. Run 'gvm init' to add it.",
+            shell_config_path.display()
+        ),
+    ) {
+        failed += 1;
+    }
+
+    failed
+}
+
+/// Diagnoses the GVM installation.
+///
+/// This function performs the following checks and prints a report with a
+/// remediation hint for every failed one:
+/// 1. The directory structure created by `gvm init` exists.
+/// 2. Every alias, in particular `default`, points at an installed version.
+/// 3. The active version is installed and `go.env` sets its GOROOT.
+/// 4. The init script exists and the shell profile contains the init block.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` if all checks passed, or an error
+/// naming the number of failed checks, so CI can gate on the exit code.
+pub async fn doctor() -> Res<()> {
+    info!("Checking directory structure ...");
+    let mut failed = check_directories();
+
+    info!("Checking aliases ...");
+    failed += check_aliases().await?;
+
+    info!("Checking active version ...");
+    failed += check_active_version().await?;
+
+    info!("Checking shell profile ...");
+    failed += check_profile();
+
+    if failed > 0 {
+        error!("{} check(s) failed. See the hints above.", failed);
+    }
+
+    success!("All checks passed.");
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use serde_json::{Map, Value};
+
+use crate::{
+    error,
+    utils::{self, ShellSyntax},
+    Res,
+};
+
+/// Output formats of `gvm env`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum EnvFormat {
+    /// `export KEY=value` lines for bash, zsh and other POSIX shells.
+    Bash,
+    /// `set -gx KEY value` lines for fish.
+    Fish,
+    /// `KEY=value` lines for `.env` files, without PATH.
+    Dotenv,
+    /// A JSON object of the variables, with the PATH entries in `PATH_PREPEND`.
+    Json,
+}
+
+impl From<ShellSyntax> for EnvFormat {
+    fn from(shell: ShellSyntax) -> Self {
+        match shell {
+            ShellSyntax::Bash => EnvFormat::Bash,
+            ShellSyntax::Fish => EnvFormat::Fish,
+        }
+    }
+}
+
+/// Renders the Go environment of a version as a dotenv file.
+///
+/// PATH is left out, since dotenv files can only set variables and not extend
+/// them. Values are quoted with `utils::dotenv_quote`.
+fn render_dotenv(version: &str) -> String {
+    let mut content = String::new();
+    for (env_key, env_path) in utils::go_env_vars(version) {
+        content.push_str(&format!(
+            "{}={}\n",
+            env_key,
+            utils::dotenv_quote(&env_path.to_string_lossy())
+        ));
+    }
+    content
+}
+
+/// Renders the Go environment of a version as a JSON object.
+///
+/// Every variable from `utils::go_env_vars` becomes a string member, and the
+/// directories to prepend to PATH are listed in `PATH_PREPEND`.
+fn render_json(version: &str) -> Res<String> {
+    let mut env_map = Map::new();
+    for (env_key, env_path) in utils::go_env_vars(version) {
+        env_map.insert(
+            env_key.to_string(),
+            Value::String(env_path.to_string_lossy().to_string()),
+        );
+    }
+    env_map.insert(
+        "PATH_PREPEND".to_string(),
+        Value::Array(
+            utils::go_path_entries(version)
+                .iter()
+                .map(|p| Value::String(p.to_string_lossy().to_string()))
+                .collect(),
+        ),
+    );
+    Ok(format!("{}\n", serde_json::to_string_pretty(&env_map)?))
+}
+
+/// Prints the Go environment for the active or a specified version.
+///
+/// By default the environment is printed as shell statements so it can be
+/// evaluated directly, e.g. `eval "$(gvm env)"`. The other formats print the
+/// same values for fish, for `.env` files read by tools like docker compose,
+/// or as JSON for tools that don't want to parse shell syntax.
+///
+/// # Parameters
+///
+/// * `version`: An optional version to print the environment for. If `None`,
+///   the active version is used.
+/// * `format`: The format to print the environment in.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` if the environment was printed, or an
+/// error if no version could be resolved.
+pub async fn env(version: Option<String>, format: EnvFormat) -> Res<()> {
+    let real_version = match version {
+        Some(v) => utils::parse_version_arg(v)?,
+        None => match utils::get_active_version().await {
+            Some(v) => v,
+            None => error!("No active version found. Use 'gvm list' to see available versions."),
+        },
+    };
+
+    let installed_versions: Vec<String> = utils::list_installed_versions().await?;
+    if !installed_versions.contains(&real_version) {
+        error!(
+            "Version {} is not installed. Please install it first.",
+            real_version
+        );
+    }
+
+    let output = match format {
+        EnvFormat::Bash => utils::render_env_script(&real_version, ShellSyntax::Bash),
+        EnvFormat::Fish => utils::render_env_script(&real_version, ShellSyntax::Fish),
+        EnvFormat::Dotenv => render_dotenv(&real_version),
+        EnvFormat::Json => render_json(&real_version)?,
+    };
+    print!("{}", output);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::gvm_root;
+
+    #[test]
+    fn dotenv_lists_the_variables_without_path() {
+        // the test root contains a space, so every value is quoted
+        let root = gvm_root().display().to_string();
+
+        assert_eq!(
+            render_dotenv("go1.22.0"),
+            format!(
+                "GOROOT='{0}/version/go1.22.0'\nGOCACHE='{0}/cache/go1.22.0/go-build'\nGOPATH='{0}/package/go1.22.0'\nGOENV='{0}/environment/go.env'\n",
+                root
+            )
+        );
+    }
+
+    #[test]
+    fn json_maps_the_variables_to_their_values() {
+        let root = gvm_root();
+        let env: Value = serde_json::from_str(&render_json("go1.22.0").unwrap()).unwrap();
+
+        assert_eq!(
+            env,
+            serde_json::json!({
+                "GOROOT": root.join("version/go1.22.0"),
+                "GOCACHE": root.join("cache/go1.22.0/go-build"),
+                "GOPATH": root.join("package/go1.22.0"),
+                "GOENV": root.join("environment/go.env"),
+                "PATH_PREPEND": [
+                    root.join("version/go1.22.0/bin"),
+                    root.join("package/go1.22.0/bin"),
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn shell_formats_export_every_variable_and_path() {
+        let bash = utils::render_env_script("go1.22.0", ShellSyntax::Bash);
+        let fish = utils::render_env_script("go1.22.0", ShellSyntax::Fish);
+
+        for key in ["GOROOT", "GOCACHE", "GOPATH", "GOENV"] {
+            assert!(bash.contains(&format!("export {}=", key)), "{}", bash);
+            assert!(fish.contains(&format!("set -gx {} ", key)), "{}", fish);
+        }
+        assert!(bash.contains("PATH"), "{}", bash);
+        assert!(fish.contains("set -gx PATH "), "{}", fish);
+    }
+}
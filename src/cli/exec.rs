@@ -0,0 +1,104 @@
+use std::{env, os::unix::process::ExitStatusExt};
+
+use tokio::process::Command;
+
+use crate::{debug, error, utils, Res};
+
+/// Runs a command under a specific version without activating it.
+///
+/// The child process gets GOROOT, GOCACHE, GOPATH and GOENV of the version and
+/// its `bin` directories prepended to PATH. Neither the active file nor any
+/// alias is touched, so the global active version stays as it is.
+///
+/// # Parameters
+///
+/// * `version`: The version to run the command under.
+/// * `command`: The program to run followed by its arguments.
+///
+/// # Returns
+///
+/// Returns `Res<i32>` with the exit code of the child. If the child was killed
+/// by a signal, `128 + signal` is returned like in a shell. Returns an error if
+/// the version is not installed or the command cannot be started.
+pub async fn exec(version: String, command: Vec<String>) -> Res<i32> {
+    let real_version = utils::parse_version_arg(version)?;
+    let installed_versions = utils::list_installed_versions().await?;
+    if !installed_versions.contains(&real_version) {
+        error!(
+            "Version {} is not installed. Please install it first.",
+            real_version
+        );
+    }
+
+    let (program, args) = match command.split_first() {
+        Some(split) => split,
+        None => error!("No command given. Usage: gvm exec <version> -- <command>..."),
+    };
+
+    let mut path_entries = utils::go_path_entries(&real_version);
+    if let Some(path) = env::var_os("PATH") {
+        path_entries.extend(env::split_paths(&path));
+    }
+    let path = env::join_paths(path_entries)?;
+    for (key, value) in utils::go_env_vars(&real_version) {
+        debug!("{}={}", key, value.display());
+    }
+    debug!("PATH={}", path.to_string_lossy());
+
+    let status = match Command::new(program)
+        .args(args)
+        .envs(utils::go_env_vars(&real_version))
+        .env("PATH", path)
+        .status()
+        .await
+    {
+        Ok(status) => status,
+        Err(e) => error!("Failed to run '{}': {}", program, e),
+    };
+
+    Ok(status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .unwrap_or(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::{assert_rejected, fake_version, MALICIOUS_NAMES};
+
+    fn sh(script: &str) -> Vec<String> {
+        vec!["sh".to_string(), "-c".to_string(), script.to_string()]
+    }
+
+    #[tokio::test]
+    async fn exec_runs_the_command_under_the_version() {
+        let version_dir = fake_version("go1.17.2");
+
+        let go_version = vec!["go".to_string(), "version".to_string()];
+        assert_eq!(exec("1.17.2".to_string(), go_version).await.unwrap(), 0);
+        let script = format!(
+            r#"[ "$GOROOT" = "{}" ] && [ "$(command -v go)" = "$GOROOT/bin/go" ]"#,
+            version_dir.display()
+        );
+        assert_eq!(exec("1.17.2".to_string(), sh(&script)).await.unwrap(), 0);
+        assert_eq!(exec("1.17.2".to_string(), sh("exit 3")).await.unwrap(), 3);
+        assert_eq!(
+            exec("1.17.2".to_string(), sh("kill -9 $$")).await.unwrap(),
+            137
+        );
+    }
+
+    #[tokio::test]
+    async fn exec_requires_an_installed_version() {
+        let err = exec("1.17.99".to_string(), sh("true")).await.unwrap_err();
+        assert!(err.to_string().contains("not installed"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn exec_rejects_malicious_versions() {
+        for name in MALICIOUS_NAMES {
+            assert_rejected(name, exec(name.to_string(), vec!["true".to_string()]).await);
+        }
+    }
+}
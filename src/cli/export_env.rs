@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+use crate::{error, success, utils, Res};
+
+/// Writes a standalone environment script for an installed version.
+///
+/// Unlike activation, this does not touch the active file, the default alias
+/// or the managed `go.env`, so parallel jobs can each source the environment
+/// of a different version.
+///
+/// # Parameters
+///
+/// * `version`: The installed version to export the environment for.
+/// * `shell`: The shell syntax of the script.
+/// * `output`: The file to write. If `None`, the script is printed to stdout.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` if the script was written, or an error
+/// if the version is not installed or the file cannot be written.
+pub async fn export_env(
+    version: String,
+    shell: utils::ShellSyntax,
+    output: Option<PathBuf>,
+) -> Res<()> {
+    let real_version = utils::parse_version_arg(version)?;
+
+    let installed_versions: Vec<String> = utils::list_installed_versions().await?;
+    if !installed_versions.contains(&real_version) {
+        error!(
+            "Version {} is not installed. Please install it first.",
+            real_version
+        );
+    }
+
+    let script = utils::render_env_script(&real_version, shell);
+
+    match output {
+        Some(output) => match async_fs::write(&output, script).await {
+            Ok(_) => success!(
+                "Environment for version {} written to {}.",
+                real_version,
+                output.display()
+            ),
+            Err(e) => error!("Error writing {}: {}", output.display(), e),
+        },
+        None => print!("{}", script),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+
+use crate::{error, info, success, utils, Res};
+
+/// Snapshot of installed versions, aliases and the active version.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct Manifest {
+    pub active: Option<String>,
+    pub versions: Vec<String>,
+    /// The version every alias resolves to.
+    pub aliases: BTreeMap<String, String>,
+    /// The alias each alias of an alias points to. Manifests written before it
+    /// was recorded don't have it, and all their aliases point to versions.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub alias_parents: BTreeMap<String, String>,
+}
+
+/// Writes a manifest of the current toolchain set to a file.
+///
+/// The manifest contains every installed version, all user aliases (the
+/// `default` alias is implied by the active version) and the active version.
+/// For an alias of an alias, the alias it points to is recorded as well, so
+/// `gvm restore` recreates the chain instead of pinning the version.
+/// It can be committed to a repository and replayed with `gvm restore`.
+///
+/// # Parameters
+///
+/// * `file`: The path of the manifest file to write.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` if the manifest was written, or an
+/// error if the GVM directories cannot be read.
+pub async fn freeze(file: PathBuf) -> Res<()> {
+    let mut versions = utils::list_installed_versions().await?;
+    versions.sort_by(|a, b| utils::cmp_versions(a, b));
+
+    let mut aliases = BTreeMap::new();
+    let mut alias_parents = BTreeMap::new();
+    for alias_name in utils::list_aliases().await? {
+        if alias_name == "default" {
+            continue;
+        }
+        if let Some(target) = utils::get_alias_target(&alias_name).await {
+            if let Some(parent) = utils::get_parent_alias(&alias_name).await {
+                alias_parents.insert(alias_name.clone(), parent);
+            }
+            aliases.insert(alias_name, target);
+        }
+    }
+
+    let manifest = Manifest {
+        active: utils::get_active_version().await,
+        versions,
+        aliases,
+        alias_parents,
+    };
+
+    info!("Writing manifest to {} ...", file.display());
+    let data = serde_json::to_string_pretty(&manifest)?;
+    match async_fs::write(&file, data).await {
+        Ok(_) => success!(
+            "Froze {} versions and {} aliases.",
+            manifest.versions.len(),
+            manifest.aliases.len()
+        ),
+        Err(e) => error!("Error writing manifest {}: {}", file.display(), e),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::{fake_alias, fake_version};
+
+    #[test]
+    fn manifests_without_alias_parents_still_load() {
+        let manifest: Manifest = serde_json::from_str(
+            r#"{"active":null,"versions":["go1.22.0"],"aliases":{"stable":"go1.22.0"}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.aliases["stable"], "go1.22.0");
+        assert!(manifest.alias_parents.is_empty());
+    }
+
+    #[tokio::test]
+    async fn freeze_records_the_alias_an_alias_points_to() {
+        fake_version("go1.85.1");
+        fake_alias("t285-base", "go1.85.1", false);
+        fake_alias("t285-child", "t285-base", true);
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("gvm.json");
+
+        freeze(file.clone()).await.unwrap();
+
+        let manifest: Manifest =
+            serde_json::from_str(&std::fs::read_to_string(file).unwrap()).unwrap();
+        assert_eq!(manifest.aliases["t285-base"], "go1.85.1");
+        assert_eq!(manifest.aliases["t285-child"], "go1.85.1");
+        assert_eq!(manifest.alias_parents["t285-child"], "t285-base");
+        assert!(!manifest.alias_parents.contains_key("t285-base"));
+    }
+}
@@ -0,0 +1,77 @@
+use crate::Res;
+
+/// Shells `gvm hook` can generate an integration for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum HookShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+const BASH_HOOK: &str = r#"_gvm_hook() {
+  if [ "$PWD" = "${_GVM_HOOK_PWD:-}" ]; then
+    return 0
+  fi
+  _GVM_HOOK_PWD="$PWD"
+  local gvm_env
+  gvm_env="$(command gvm use --print 2>/dev/null)" || return 0
+  if [ "$gvm_env" != "${_GVM_HOOK_ENV:-}" ]; then
+    _GVM_HOOK_ENV="$gvm_env"
+    eval "$gvm_env"
+  fi
+}
+if [[ ";${PROMPT_COMMAND:-};" != *";_gvm_hook;"* ]]; then
+  PROMPT_COMMAND="_gvm_hook${PROMPT_COMMAND:+;$PROMPT_COMMAND}"
+fi
+"#;
+
+const ZSH_HOOK: &str = r#"_gvm_hook() {
+  local gvm_env
+  gvm_env="$(command gvm use --print 2>/dev/null)" || return 0
+  if [ "$gvm_env" != "${_GVM_HOOK_ENV:-}" ]; then
+    _GVM_HOOK_ENV="$gvm_env"
+    eval "$gvm_env"
+  fi
+}
+autoload -Uz add-zsh-hook
+add-zsh-hook chpwd _gvm_hook
+_gvm_hook
+"#;
+
+const FISH_HOOK: &str = r#"function _gvm_hook --on-variable PWD
+    set -l gvm_env (command gvm use --print --shell fish 2>/dev/null | string collect)
+    or return 0
+    if test "$gvm_env" != "$_GVM_HOOK_ENV"
+        set -g _GVM_HOOK_ENV $gvm_env
+        echo $gvm_env | source
+    end
+end
+_gvm_hook
+"#;
+
+/// Prints a shell integration that switches versions on directory change.
+///
+/// The printed code defines a function that runs `gvm use --print` whenever
+/// the working directory changes and evaluates its output, so the version
+/// pinned by `.go-version` or `go.mod` is applied to the current shell. Bash
+/// runs it from `PROMPT_COMMAND`, zsh from a `chpwd` hook and fish on changes
+/// of `PWD`. It is meant to be evaluated from the shell profile, e.g.
+/// `eval "$(gvm hook bash)"`.
+///
+/// # Parameters
+///
+/// * `shell`: The shell to print the integration for.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is always `Ok(())`.
+pub async fn hook(shell: HookShell) -> Res<()> {
+    let script = match shell {
+        HookShell::Bash => BASH_HOOK,
+        HookShell::Zsh => ZSH_HOOK,
+        HookShell::Fish => FISH_HOOK,
+    };
+    print!("{}", script);
+
+    Ok(())
+}
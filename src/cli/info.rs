@@ -0,0 +1,201 @@
+use std::path::Path;
+
+use indicatif::HumanBytes;
+use tokio::process::Command;
+
+use crate::{error, utils, Res};
+
+/// Runs `go version` with the toolchain of an installed version.
+///
+/// # Returns
+///
+/// The output of `go version` without the trailing newline, or a short
+/// explanation if the binary is missing or fails.
+async fn go_version_output(version: &str) -> String {
+    let go = utils::get_version_file_path()
+        .join(version)
+        .join("bin")
+        .join("go");
+    if !go.is_file() {
+        return format!("unavailable ({} not found)", go.display());
+    }
+
+    match Command::new(&go)
+        .arg("version")
+        .envs(utils::go_env_vars(version))
+        .output()
+        .await
+    {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        }
+        Ok(output) => format!(
+            "unavailable ({})",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => format!("unavailable ({})", e),
+    }
+}
+
+/// Prints details about a version.
+///
+/// For an installed version this is its GOROOT, whether it is active, the
+/// aliases pointing to it, the size of its directory and the output of its
+/// `go version`. For a version that is only in the release cache, the
+/// download URL and checksum of its archive are printed instead.
+///
+/// # Parameters
+///
+/// * `version`: The version to describe.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` if the details were printed, or an
+/// error if the version is neither installed nor in the release cache.
+pub async fn info(version: String) -> Res<()> {
+    let real_version = utils::parse_version_arg(version)?;
+    let cache_file = utils::get_release_cache_file_path();
+
+    for (key, value) in version_details(&real_version, &cache_file).await? {
+        println!("{}: {}", key, value);
+    }
+    Ok(())
+}
+
+/// Collects the details `info` prints about a version, as key-value pairs.
+///
+/// # Parameters
+///
+/// * `real_version`: The version to describe, including the "go" prefix.
+/// * `cache_file`: The release cache to look up a version that is not
+///   installed.
+///
+/// # Returns
+///
+/// Returns `Res<Vec<(&str, String)>>` with the details in print order, or an
+/// error if the version is neither installed nor in the release cache.
+async fn version_details(
+    real_version: &str,
+    cache_file: &Path,
+) -> Res<Vec<(&'static str, String)>> {
+    let real_version = real_version.to_string();
+    let installed_versions = utils::list_installed_versions().await?;
+    if installed_versions.contains(&real_version) {
+        let goroot = utils::get_version_file_path().join(&real_version);
+        let active = utils::get_active_version().await.as_ref() == Some(&real_version);
+
+        let mut aliases = Vec::new();
+        for alias in utils::list_aliases().await.unwrap_or_default() {
+            if utils::get_alias_target(&alias).await.as_deref() == Some(real_version.as_str()) {
+                aliases.push(alias);
+            }
+        }
+        aliases.sort();
+
+        let size = match utils::dir_size(&goroot) {
+            Ok(size) => HumanBytes(size).to_string(),
+            Err(e) => format!("unknown ({})", e),
+        };
+
+        let aliases = if aliases.is_empty() {
+            "none".to_string()
+        } else {
+            aliases.join(", ")
+        };
+        let go_version = go_version_output(&real_version).await;
+        return Ok(vec![
+            ("version", real_version),
+            ("installed", "yes".to_string()),
+            ("goroot", goroot.display().to_string()),
+            ("active", if active { "yes" } else { "no" }.to_string()),
+            ("aliases", aliases),
+            ("size", size),
+            ("go version", go_version),
+        ]);
+    }
+
+    let release =
+        match utils::list_cached_versions(cache_file, Some(real_version.clone()), false).await {
+            Ok(releases) => releases.into_iter().find(|r| r.version == real_version),
+            Err(_) => None,
+        };
+    let release = match release {
+        Some(release) => release,
+        None => error!(
+            "Version {} is not installed and not in the release cache. Run 'gvm update' to refresh the cache.",
+            real_version
+        ),
+    };
+
+    Ok(vec![
+        ("version", real_version),
+        ("installed", "no".to_string()),
+        ("platform", format!("{}-{}", release.os, release.arch)),
+        ("url", release.url),
+        (
+            "sha256",
+            release.sha256.unwrap_or_else(|| "unknown".to_string()),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::{assert_rejected, fake_alias, fake_version, MALICIOUS_NAMES};
+
+    fn detail<'a>(details: &'a [(&str, String)], key: &str) -> &'a str {
+        details.iter().find(|(k, _)| *k == key).unwrap().1.as_str()
+    }
+
+    #[tokio::test]
+    async fn installed_versions_show_their_toolchain() {
+        let goroot = fake_version("go1.32.1");
+        fake_alias("t320-b", "go1.32.1", false);
+        fake_alias("t320-a", "go1.32.1", false);
+        let cache = tempfile::tempdir().unwrap();
+
+        let details = version_details("go1.32.1", &cache.path().join("releases.json"))
+            .await
+            .unwrap();
+
+        assert_eq!(detail(&details, "installed"), "yes");
+        assert_eq!(detail(&details, "goroot"), goroot.display().to_string());
+        assert_eq!(detail(&details, "active"), "no");
+        assert_eq!(detail(&details, "aliases"), "t320-a, t320-b");
+        assert_eq!(detail(&details, "go version"), "go version go1.32.1");
+    }
+
+    #[tokio::test]
+    async fn cached_versions_show_their_download() {
+        let cache = tempfile::tempdir().unwrap();
+        let cache_file = cache.path().join("releases.json");
+        let release = utils::FilteredRelease {
+            version: "go1.32.9".to_string(),
+            url: "https://go.dev/dl/go1.32.9.tar.gz".to_string(),
+            sha256: None,
+            os: utils::get_target_os(),
+            arch: utils::get_target_arch(),
+        };
+        std::fs::write(&cache_file, serde_json::to_string(&[release]).unwrap()).unwrap();
+
+        let details = version_details("go1.32.9", &cache_file).await.unwrap();
+        assert_eq!(detail(&details, "installed"), "no");
+        assert_eq!(detail(&details, "url"), "https://go.dev/dl/go1.32.9.tar.gz");
+        assert_eq!(detail(&details, "sha256"), "unknown");
+
+        let err = version_details("go1.32.8", &cache_file).await.unwrap_err();
+        assert!(
+            err.to_string().contains("not in the release cache"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn info_rejects_malicious_versions() {
+        for name in MALICIOUS_NAMES {
+            assert_rejected(name, info(name.to_string()).await);
+        }
+    }
+}
@@ -1,32 +1,42 @@
-use std::{env, io};
+use std::{env, io, path::Path};
 
 use regex::Regex;
 
-use crate::{error, info, success, utils, Res};
+use super::{
+    install::{install, InstallOptions},
+    use_version::{resolve_installed_version, use_version},
+};
+use crate::{config, error, info, success, utils, Res};
 
 /// Returns the content of the initialization script for GVM (Go Version Manager).
 ///
-/// This function generates a bash script as a string that performs the following tasks:
+/// This function generates a bash or zsh script as a string that performs the following tasks:
 /// - Sets up the GVM_ROOT environment variable
-/// - Generates and sources bash completion for GVM
+/// - Generates completion for GVM for the given shell
 /// - Sources the Go environment file if it exists
 /// - Adds GOROOT/bin and GOPATH/bin to the PATH if they exist and are not already included
+/// - Puts the shim directory in front of PATH if `gvm shim install` created it, so
+///   the `go` shim wins over GOROOT/bin
 ///
 /// # Arguments
 ///
 /// * `gvm_root` - A string slice that holds the path to the GVM root directory.
+/// * `shell` - The shell the script is for; selects how completions are installed.
+/// * `path_position` - Whether GOROOT/bin and GOPATH/bin are prepended or appended to PATH.
 ///
 /// # Returns
 ///
-/// A `String` containing the bash script for GVM initialization.
-fn get_init_script_content(gvm_root: &str) -> String {
+/// A `String` containing the script for GVM initialization.
+fn get_init_script_content(
+    gvm_root: &str,
+    shell: utils::LoginShell,
+    path_position: utils::PathPosition,
+) -> String {
     format!(
         r#"
 # >>> gvm initialize >>>
-export GVM_ROOT="{}"
-if [ -s "$HOME/.cargo/bin/gvm" ] && [ ! -f "$HOME/.bash_completions/gvm" ]; then
-        gvm completions bash > "$HOME/.bash_completions/gvm"
-fi
+export GVM_ROOT={}
+{}
 
 if [ -s "$GVM_ROOT/environment/go.env" ]; then
         set -a && source "$GVM_ROOT/environment/go.env" && set +a
@@ -37,7 +47,7 @@ if [ -s "$GOROOT/bin" ]; then
                 *:$GOROOT/bin:*)
                         ;;
                 *)
-                        export PATH="$GOROOT/bin:$PATH"
+                        {}
                         ;;
         esac
 fi
@@ -47,16 +57,135 @@ if [ -s "$GOPATH/bin" ]; then
                 *:$GOPATH/bin:*)
                         ;;
                 *)
-                        export PATH="$GOPATH/bin:$PATH"
+                        {}
+                        ;;
+        esac
+fi
+
+if [ -d "$GVM_ROOT/shims" ]; then
+        case ":$PATH:" in
+                *:$GVM_ROOT/shims:*)
+                        ;;
+                *)
+                        export PATH="$GVM_ROOT/shims:$PATH"
                         ;;
         esac
 fi
 # <<< gvm initialize <<<
 "#,
-        gvm_root
+        utils::shell_quote(gvm_root),
+        get_completions_setup(shell),
+        get_path_export("$GOROOT/bin", path_position),
+        get_path_export("$GOPATH/bin", path_position),
+    )
+}
+
+/// Returns the content of the fish initialization script for GVM.
+///
+/// This is the fish counterpart of `get_init_script_content`. Since fish cannot
+/// source the `KEY=value` lines of `go.env`, they are read line by line and
+/// exported with `set -gx`. GOROOT/bin, GOPATH/bin and the shim directory are
+/// added with `fish_add_path`, which skips directories already in PATH.
+///
+/// # Arguments
+///
+/// * `gvm_root` - A string slice that holds the path to the GVM root directory.
+/// * `path_position` - Whether GOROOT/bin and GOPATH/bin are prepended or appended to PATH.
+///
+/// # Returns
+///
+/// A `String` containing the fish script for GVM initialization.
+fn get_fish_init_script_content(gvm_root: &str, path_position: utils::PathPosition) -> String {
+    let add_path = match path_position {
+        utils::PathPosition::Prepend => "fish_add_path --path",
+        utils::PathPosition::Append => "fish_add_path --path --append",
+    };
+
+    let gvm_root = utils::fish_quote(gvm_root);
+
+    format!(
+        r#"
+# >>> gvm initialize >>>
+set -gx GVM_ROOT {gvm_root}
+if test -s "$HOME/.cargo/bin/gvm"; and not test -f "$HOME/.config/fish/completions/gvm.fish"
+        mkdir -p "$HOME/.config/fish/completions"
+        gvm completions fish > "$HOME/.config/fish/completions/gvm.fish"
+end
+
+if test -s "$GVM_ROOT/environment/go.env"
+        for line in (string match --regex '^[A-Z_]+=.*' < "$GVM_ROOT/environment/go.env")
+                set -l kv (string split --max 1 = -- $line)
+                set -l value $kv[2]
+                # values are single-quoted for POSIX shells, see utils::shell_quote
+                if string match --quiet --regex "^'.*'\$" -- $value
+                        set value (string replace --all -- "'\\''" "'" (string replace --regex -- "^'(.*)'\$" '$1' $value))
+                end
+                set -gx $kv[1] $value
+        end
+end
+
+if test -d "$GOROOT/bin"
+        {add_path} "$GOROOT/bin"
+end
+
+if test -d "$GOPATH/bin"
+        {add_path} "$GOPATH/bin"
+end
+
+if test -d "$GVM_ROOT/shims"
+        fish_add_path --path "$GVM_ROOT/shims"
+end
+# <<< gvm initialize <<<
+"#
     )
 }
 
+/// Returns the lines that generate GVM completions once for a shell.
+///
+/// Bash completions are written to `$HOME/.bash_completions/gvm`. Zsh
+/// completions are written to `$HOME/.zsh/completions/_gvm`, which is added to
+/// `fpath`. If the profile (or a framework like oh-my-zsh) already ran
+/// `compinit`, `_gvm` is registered with `compdef` instead of initializing
+/// completion a second time; otherwise `compinit` is run once.
+///
+/// # Arguments
+///
+/// * `shell` - The shell to generate completions for.
+fn get_completions_setup(shell: utils::LoginShell) -> String {
+    match shell {
+        utils::LoginShell::Zsh => {
+            r#"if [ -s "$HOME/.cargo/bin/gvm" ] && [ ! -f "$HOME/.zsh/completions/_gvm" ]; then
+        mkdir -p "$HOME/.zsh/completions"
+        gvm completions zsh > "$HOME/.zsh/completions/_gvm"
+fi
+fpath=("$HOME/.zsh/completions" $fpath)
+if (( $+functions[compdef] )); then
+        autoload -Uz _gvm && compdef _gvm gvm
+else
+        autoload -Uz compinit && compinit -i
+fi"#
+            .to_string()
+        }
+        _ => r#"if [ -s "$HOME/.cargo/bin/gvm" ] && [ ! -f "$HOME/.bash_completions/gvm" ]; then
+        gvm completions bash > "$HOME/.bash_completions/gvm"
+fi"#
+        .to_string(),
+    }
+}
+
+/// Returns the `export PATH=...` line that adds a directory to PATH.
+///
+/// # Arguments
+///
+/// * `dir` - The directory to add, usually a shell variable reference like `$GOROOT/bin`.
+/// * `path_position` - Whether the directory is put in front of or behind the existing PATH.
+fn get_path_export(dir: &str, path_position: utils::PathPosition) -> String {
+    match path_position {
+        utils::PathPosition::Prepend => format!("export PATH=\"{}:$PATH\"", dir),
+        utils::PathPosition::Append => format!("export PATH=\"$PATH:{}\"", dir),
+    }
+}
+
 /// Creates the base directory structure for the GVM (Go Version Manager) application.
 ///
 /// This function attempts to create several directories that are essential for GVM's operation:
@@ -140,24 +269,27 @@ async fn create_base_directories() -> Res<()> {
 ///
 /// This function attempts to set up the Go environment using the provided version
 /// or the default version if none is specified. It handles the initialization
-/// process and reports the outcome.
+/// process and reports the outcome. When `GVM_DEFAULT_VERSION` is set, that
+/// version is installed and activated as the last step.
 ///
 /// # Arguments
 ///
-/// * `version` - An optional `String` specifying the Go version to initialize.
-///               If `None`, the default version will be used.
+/// * `path_position` - Whether GOROOT/bin and GOPATH/bin are prepended or appended to PATH.
+///   If `None`, `GVM_PATH_POSITION` is read and prepending is the default.
 ///
 /// # Returns
 ///
 /// Returns a `Res<()>`, which is a custom result type. On success, it returns
 /// `Ok(())`. On failure, it returns an error detailing what went wrong during
 /// the initialization process.
-pub async fn init() -> Res<()> {
-    // currently we only support bash
-    let shell = env::var("SHELL").expect("Failed to retrieve SHELL environment variable");
-    if !shell.contains("bash") {
-        error!("Go environment initialization is only supported for bash shells.");
-    }
+pub async fn init(path_position: Option<utils::PathPosition>) -> Res<()> {
+    let shell = match utils::get_login_shell() {
+        Ok(shell) => shell,
+        Err(e) => error!(
+            "{}. Go environment initialization is only supported for bash, zsh and fish shells.",
+            e
+        ),
+    };
 
     info!("Creating GVM path structure ...");
     match create_base_directories().await {
@@ -167,10 +299,16 @@ pub async fn init() -> Res<()> {
         }
     }
 
-    info!("Create init script for bash shell ...");
+    info!("Create init script for {} shell ...", shell);
     let gvm_base_dir = utils::get_gvm_base_file_path();
     let gvm_init_file_path = gvm_base_dir.join("init-shell");
-    let init_script_content = get_init_script_content(&gvm_base_dir.to_string_lossy());
+    let path_position = path_position.unwrap_or_else(utils::PathPosition::from_env);
+    let init_script_content = match shell {
+        utils::LoginShell::Fish => {
+            get_fish_init_script_content(&gvm_base_dir.to_string_lossy(), path_position)
+        }
+        _ => get_init_script_content(&gvm_base_dir.to_string_lossy(), shell, path_position),
+    };
     match async_fs::write(&gvm_init_file_path, init_script_content).await {
         Ok(_) => success!("Init script created successfully."),
         Err(e) => {
@@ -179,11 +317,19 @@ pub async fn init() -> Res<()> {
     }
 
     info!("Initialize GVM in profile ...");
-    let start_marker = "# >>> gvm initialize >>>";
-    let end_marker = "# <<< gvm initialize <<<";
+    let start_marker = config::INIT_START_MARKER;
+    let end_marker = config::INIT_END_MARKER;
 
     let shell_config_path = utils::get_shell_config_file_path()?;
-    let shell_config_content = async_fs::read_to_string(&shell_config_path).await?;
+    // fish users often have no config.fish yet
+    if let Some(parent) = shell_config_path.parent() {
+        async_fs::create_dir_all(parent).await?;
+    }
+    let shell_config_content = match async_fs::read_to_string(&shell_config_path).await {
+        Ok(content) => content,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
 
     // Build a regex that matches from the start marker to the end marker (non-greedy).
     let pattern = format!(
@@ -193,13 +339,29 @@ pub async fn init() -> Res<()> {
     );
     let re = Regex::new(&pattern)?;
 
-    if re.is_match(&shell_config_content) {
-        info!("Go environment already initialized. Reload your profile to load go environment.");
+    if let Some(existing_block) = re.find(&shell_config_content) {
+        let content = async_fs::read_to_string(&gvm_init_file_path).await?;
+        if existing_block.as_str() == content.trim() {
+            info!(
+                "Go environment already initialized. Reload your profile to load go environment."
+            );
+        } else {
+            info!("Updating Go environment...");
+            let new_shell_config_content = re
+                .replace(&shell_config_content, regex::NoExpand(content.trim()))
+                .to_string();
+            match async_fs::write(&shell_config_path, new_shell_config_content).await {
+                Ok(_) => success!("Go environment updated. Reload your profile."),
+                Err(e) => {
+                    error!("Error updating Go environment: {}", e);
+                }
+            }
+        }
     } else {
         info!("Initializing Go environment...");
         let mut new_shell_config_content = shell_config_content;
         let content = async_fs::read_to_string(&gvm_init_file_path).await?;
-        if !new_shell_config_content.ends_with('\n') {
+        if !new_shell_config_content.is_empty() && !new_shell_config_content.ends_with('\n') {
             new_shell_config_content.push('\n');
         }
         new_shell_config_content.push_str(&content);
@@ -212,5 +374,133 @@ pub async fn init() -> Res<()> {
         }
     }
 
-    Ok(())
+    bootstrap_default_version().await
+}
+
+/// Installs and activates the version named by `GVM_DEFAULT_VERSION`, if set.
+///
+/// This lets container images and provisioning scripts declare a Go version
+/// and get it during `gvm init`, see `bootstrap_version`.
+async fn bootstrap_default_version() -> Res<()> {
+    bootstrap_version(
+        env::var("GVM_DEFAULT_VERSION").ok(),
+        &utils::get_release_cache_file_path(),
+    )
+    .await
+}
+
+/// Activates a version, installing it first if needed.
+///
+/// The version is resolved like `gvm use` does, so `latest` or a minor version
+/// like "1.22" activate a matching installed version. Otherwise it is installed
+/// like `gvm install` does. If the release cache is not available yet, the
+/// bootstrap is skipped with a hint to run `gvm update`.
+///
+/// # Arguments
+///
+/// * `version` - The version to bootstrap; `None` or a blank value skips it.
+/// * `cache_file` - The release cache the version would be installed from.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if no version is given, the cache is missing, or the
+/// version was installed and activated. Returns an error if the version is
+/// invalid or installation fails.
+async fn bootstrap_version(version: Option<String>, cache_file: &Path) -> Res<()> {
+    let default_version = match version {
+        Some(v) if !v.trim().is_empty() => v.trim().to_string(),
+        _ => return Ok(()),
+    };
+    utils::validate_name("version", &default_version)?;
+
+    info!(
+        "Bootstrapping default version '{}' from GVM_DEFAULT_VERSION ...",
+        default_version
+    );
+
+    let installed_versions = utils::list_installed_versions().await?;
+    if let Some(installed) = resolve_installed_version(default_version.clone(), &installed_versions)
+        .ok()
+        .filter(|v| installed_versions.contains(v))
+    {
+        return use_version(Some(installed), false, None, false, false).await;
+    }
+
+    if !cache_file.exists() {
+        info!(
+            "Release cache not available. Run 'gvm update' and 'gvm install {} --use' to finish bootstrapping.",
+            default_version
+        );
+        return Ok(());
+    }
+
+    install(
+        default_version,
+        InstallOptions {
+            activate: Some(true),
+            ..InstallOptions::default()
+        },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::{
+        assert_rejected, clear_activation, fake_version, lock_activation, MALICIOUS_NAMES,
+    };
+
+    #[tokio::test]
+    async fn bootstrap_skips_without_a_version_or_cache() {
+        let missing_cache = utils::get_cache_dir().join("t216-missing.json");
+
+        bootstrap_version(None, &missing_cache).await.unwrap();
+        bootstrap_version(Some("  ".to_string()), &missing_cache)
+            .await
+            .unwrap();
+        bootstrap_version(Some("1.36".to_string()), &missing_cache)
+            .await
+            .unwrap();
+        assert!(utils::list_installed_versions()
+            .await
+            .unwrap()
+            .iter()
+            .all(|v| !v.starts_with("go1.36")));
+        // an empty value means unset, see above
+        for name in MALICIOUS_NAMES.iter().filter(|name| !name.is_empty()) {
+            assert_rejected(
+                name,
+                bootstrap_version(Some(name.to_string()), &missing_cache).await,
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn bootstrap_activates_an_installed_patch_release() {
+        let _lock = lock_activation().await;
+        clear_activation();
+        fake_version("go1.37.1");
+        fake_version("go1.37.2");
+        let missing_cache = utils::get_cache_dir().join("t216-missing.json");
+
+        bootstrap_version(Some(" 1.37 ".to_string()), &missing_cache)
+            .await
+            .unwrap();
+
+        assert_eq!(utils::read_active_file().await.as_deref(), Some("go1.37.2"));
+        clear_activation();
+    }
+
+    #[test]
+    fn zsh_completion_setup_runs_compinit_only_once() {
+        let setup = get_completions_setup(utils::LoginShell::Zsh);
+        let guard = setup.find("if (( $+functions[compdef] )); then").unwrap();
+        let compdef = setup.find("compdef _gvm gvm").unwrap();
+        let compinit = setup.find("compinit -i").unwrap();
+
+        assert!(setup.find("fpath=").unwrap() < guard);
+        assert!(guard < compdef && compdef < compinit);
+        assert_eq!(setup.matches("compinit -i").count(), 1);
+    }
 }
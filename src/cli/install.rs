@@ -1,16 +1,45 @@
 use crate::{
-    config, error, info, success,
-    utils::{self, activate_version, get_real_version},
-    Res,
+    error, info, success,
+    utils::{self, activate_version},
+    warning, Res,
 };
 use flate2::read::GzDecoder;
-use serde_json;
+use futures_lite::{io::AsyncWriteExt, stream::StreamExt};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use std::{
     error::Error,
     fs,
+    io::{self, IsTerminal, Read},
     path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, OnceLock,
+    },
 };
 use tar::Archive;
+use tokio::{sync::Semaphore, task::JoinSet};
+
+/// Options of `gvm install`.
+///
+/// `activate`, `checksum` and `from_file` only apply to `install`, which
+/// installs a single version.
+#[derive(Debug, Clone, Default)]
+pub struct InstallOptions {
+    /// When set, the installed version is activated. The value tells whether
+    /// the `default` alias is repointed to it as well.
+    pub activate: Option<bool>,
+    /// An explicit SHA-256 digest to verify the archive against.
+    pub checksum: Option<String>,
+    /// Whether a missing checksum is fetched from the sidecar.
+    pub verify_sidecar: bool,
+    /// Whether the release cache is updated first.
+    pub refresh: utils::CacheRefresh,
+    /// A local release archive to install instead of downloading.
+    pub from_file: Option<PathBuf>,
+    /// Whether an existing installation of the version is replaced.
+    pub force: bool,
+}
 
 /// Checks if a specific version of the software is already installed.
 ///
@@ -30,140 +59,1385 @@ fn version_already_installed(version: String) -> bool {
     version_path.exists()
 }
 
+/// Returns the path of the temporary archive file for a release package URL.
+///
+/// # Arguments
+///
+/// * `url` - The URL of the release package.
+///
+/// # Returns
+///
+/// * `Res<PathBuf>` - The path inside the archive directory named after the package,
+///   or an error if the URL has no package name.
+fn get_archive_file(url: &str) -> Res<PathBuf> {
+    let package_name = url
+        .split('/')
+        .next_back()
+        .filter(|name| !name.is_empty())
+        .ok_or("Invalid package URL; cannot extract package name.")?;
+    Ok(utils::get_archive_file_path().join(package_name))
+}
+
+/// Returns the path a verified release package is kept at for reuse.
+///
+/// # Arguments
+///
+/// * `url` - The URL of the release package.
+///
+/// # Returns
+///
+/// * `Res<PathBuf>` - The path inside the archive cache named after the package,
+///   or an error if the URL has no package name.
+fn get_cached_archive_file(url: &str) -> Res<PathBuf> {
+    let archive_file = get_archive_file(url)?;
+    let package_name = archive_file.file_name().unwrap_or_default();
+    Ok(utils::get_archive_cache_path().join(package_name))
+}
+
+/// Checks whether a cached release package can be installed without downloading it.
+///
+/// A cached package that does not match the expected digest is removed, so it
+/// is replaced by the next download.
+///
+/// # Arguments
+///
+/// * `cached_archive` - The path of the cached package.
+/// * `expected` - The expected SHA-256 digest as hex.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the cached package exists and matches the digest.
+fn reuse_cached_archive(cached_archive: &Path, expected: &str) -> bool {
+    if !cached_archive.is_file() {
+        return false;
+    }
+    info!("Verifying cached archive {} ...", cached_archive.display());
+    match utils::sha256_file(cached_archive) {
+        Ok(actual) if actual.eq_ignore_ascii_case(expected) => {
+            success!("Using cached archive {}.", cached_archive.display());
+            true
+        }
+        _ => {
+            warning!(
+                "Cached archive {} is corrupted. Downloading it again.",
+                cached_archive.display()
+            );
+            let _ = fs::remove_file(cached_archive);
+            false
+        }
+    }
+}
+
+/// Moves a verified release package into the archive cache.
+///
+/// Failing to cache the package is not an error; it is removed instead.
+///
+/// # Arguments
+///
+/// * `archive_file` - The path of the downloaded and verified package.
+/// * `cached_archive` - The path the package is kept at.
+fn store_cached_archive(archive_file: &Path, cached_archive: &Path) {
+    let result = cached_archive
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::rename(archive_file, cached_archive));
+    match result {
+        Ok(_) => success!("Archive kept at {}.", cached_archive.display()),
+        Err(e) => {
+            warning!("Failed to keep archive for reuse: {}", e);
+            let _ = fs::remove_file(archive_file);
+        }
+    }
+}
+
+/// Number of extraction directories handed out by `get_extract_dir` in this process.
+static EXTRACT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns a unique temporary directory to extract a release package into.
+///
+/// The directory lives next to the downloaded archives, on the same filesystem
+/// as the installation directory, so the extracted release can be moved into
+/// place with an atomic rename. Concurrent installations never share it: the
+/// name is made of the process id and a counter, `extract-<pid>-<n>`.
+fn get_extract_dir() -> PathBuf {
+    let n = EXTRACT_COUNTER.fetch_add(1, Ordering::Relaxed);
+    utils::get_archive_file_path().join(format!("extract-{}-{}", process::id(), n))
+}
+
+/// Removes the leftovers of an interrupted installation.
+///
+/// This deletes the partially downloaded archive and the temporary extraction
+/// directory, if they exist.
+///
+/// # Arguments
+///
+/// * `archive_file` - The path of the archive file being downloaded.
+/// * `extract_dir` - The temporary directory the package is extracted into.
+fn cleanup_partial_install(archive_file: &Path, extract_dir: &Path) {
+    if archive_file.exists() {
+        let _ = fs::remove_file(archive_file);
+    }
+
+    if extract_dir.exists() {
+        let _ = fs::remove_dir_all(extract_dir);
+    }
+}
+
+/// Returns the progress bars of the downloads running in this process.
+///
+/// Concurrent installs (see `install_many`) share it, so their bars are drawn
+/// below each other instead of over each other.
+fn progress_bars() -> &'static MultiProgress {
+    static PROGRESS_BARS: OnceLock<MultiProgress> = OnceLock::new();
+    PROGRESS_BARS.get_or_init(MultiProgress::new)
+}
+
+/// Creates the progress bar shown while downloading a release package.
+///
+/// The bar is hidden when stdout is not a terminal so piped output stays clean.
+/// Without a known length, a spinner with the received byte count is shown.
+///
+/// # Arguments
+///
+/// * `total` - The expected size of the download in bytes, if known.
+/// * `version` - The version being downloaded, shown in front of the bar.
+fn download_progress_bar(total: Option<u64>, version: &str) -> ProgressBar {
+    if !io::stdout().is_terminal() {
+        return ProgressBar::hidden();
+    }
+
+    let progress = match total {
+        Some(total) => ProgressBar::new(total).with_style(
+            ProgressStyle::with_template(
+                "\t{prefix} {bar:40.cyan/blue} {bytes}/{total_bytes} ({bytes_per_sec}, {eta})",
+            )
+            .unwrap_or_else(|_| ProgressStyle::default_bar()),
+        ),
+        None => ProgressBar::new_spinner().with_style(
+            ProgressStyle::with_template("\t{prefix} {spinner} {bytes} ({bytes_per_sec})")
+                .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+        ),
+    };
+    progress_bars().add(progress.with_prefix(version.to_string()))
+}
+
+/// Streams a release package into the archive file in a single attempt.
+///
+/// # Arguments
+///
+/// * `url` - The URL of the release package.
+/// * `archive_file` - The path the package is written to. An existing file is truncated.
+/// * `version` - The version being downloaded, see `download_progress_bar`.
+///
+/// # Returns
+///
+/// * `Res<()>` - `Ok(())` once the whole package is written, otherwise the error
+///   of the request, the transfer or the file write.
+async fn download_attempt(url: &str, archive_file: &Path, version: &str) -> Res<()> {
+    let response = utils::http_client()?
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    // stream archive to temporary file
+    info!("Create temporary archive file: {}", archive_file.display());
+    let mut file = match async_fs::File::create(archive_file).await {
+        Ok(file) => file,
+        Err(err) => error!("Failed to create temporary archive file: {}", err),
+    };
+
+    let progress = download_progress_bar(response.content_length(), version);
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                progress.abandon();
+                return Err(err.into());
+            }
+        };
+        file.write_all(&chunk).await?;
+        progress.inc(chunk.len() as u64);
+    }
+    file.flush().await?;
+    progress.finish_and_clear();
+    progress_bars().remove(&progress);
+
+    Ok(())
+}
+
 /// Downloads a release package from the specified URL and saves it to a temporary file.
 ///
-/// This asynchronous function fetches a release package from the given URL, saves it to a
-/// temporary file, and returns the path to the saved file.
+/// This asynchronous function streams a release package from the given URL into a
+/// temporary file while showing a progress bar, and returns the path to the saved file.
+/// Transient failures are retried (see `utils::with_retries`). The partially written
+/// file of a failed attempt is deleted, so every attempt starts clean. A file left
+/// behind by an install that was killed is removed before downloading.
 ///
 /// # Arguments
 ///
 /// * `url` - A String containing the URL of the release package to download.
+/// * `version` - The version being downloaded, shown in front of the progress bar.
 ///
 /// # Returns
 ///
 /// * `Result<PathBuf, Box<dyn Error + Send + Sync>>` - Returns a Result which, if successful,
 ///   contains a PathBuf pointing to the location of the saved temporary file. If an error occurs
 ///   during the download or file writing process, it returns a boxed Error.
-async fn download_release(url: String) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
-    let package_url = url.clone();
-
-    info!("Download package from source: {}", url);
-    let response = reqwest::get(url).await?;
-    if !response.status().is_success() {
-        error!(
-            "Error: Failed to download package. HTTP Status: {}",
-            response.status()
+async fn download_release(
+    url: String,
+    version: &str,
+) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+    let archive_file = get_archive_file(&url)?;
+    if archive_file.exists() {
+        info!(
+            "Removing leftover archive {} of an interrupted install ...",
+            archive_file.display()
         );
+        fs::remove_file(&archive_file)?;
     }
 
-    let content = response.bytes().await?;
+    info!("Download package from source: {}", url);
+    let (url, file) = (&url, &archive_file);
+    utils::with_retries("Download", move || async move {
+        let result = download_attempt(url, file, version).await;
+        if result.is_err() && file.exists() {
+            let _ = fs::remove_file(file);
+        }
+        result
+    })
+    .await?;
 
-    // write archive to temporary file
-    let package_name = package_url
-        .split("/")
-        .last()
-        .ok_or("Invalid package URL; cannot extract package name.")?;
-    let archive_path = utils::get_archive_file_path();
-    let archive_file = archive_path.join(&package_name);
+    info!("Temporary archive file created: {}", archive_file.display());
 
-    info!("Create temporary archive file: {}", archive_file.display());
-    match async_fs::write(&archive_file, &content).await {
-        Ok(_) => info!("Temporary archive file created: {}", archive_file.display()),
-        Err(err) => error!("Failed to create temporary archive file: {}", err),
+    Ok(archive_file)
+}
+
+/// Verifies an archive against an expected SHA-256 digest.
+///
+/// # Arguments
+///
+/// * `archive_file` - The path of the downloaded archive.
+/// * `expected` - The expected SHA-256 digest as hex.
+///
+/// # Returns
+///
+/// * `Res<()>` - `Ok(())` if the digests match, otherwise an error.
+fn verify_checksum(archive_file: &Path, expected: &str) -> Res<()> {
+    info!("Verifying checksum of {} ...", archive_file.display());
+    let actual = utils::sha256_file(archive_file)?;
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            archive_file.display(),
+            expected,
+            actual
+        )
+        .into());
     }
+    success!("Checksum of {} verified.", archive_file.display());
+    Ok(())
+}
 
-    Ok(archive_file)
+/// Fetches the SHA-256 digest published next to a release archive.
+///
+/// go.dev serves a `<archive>.sha256` file for every download. If the sidecar
+/// cannot be fetched or does not contain a valid digest, a warning is printed
+/// and `None` is returned so the installation continues unverified.
+///
+/// # Arguments
+///
+/// * `url` - The URL of the release archive.
+///
+/// # Returns
+///
+/// * `Option<String>` - The published digest, if available.
+async fn fetch_sidecar_checksum(url: &str) -> Option<String> {
+    let sidecar_url = format!("{}.sha256", url);
+    info!("Fetch checksum from source: {}", sidecar_url);
+
+    let client = match utils::http_client() {
+        Ok(client) => client,
+        Err(e) => {
+            warning!(
+                "Failed to fetch checksum file: {}. Skipping verification.",
+                e
+            );
+            return None;
+        }
+    };
+    let body = match client.get(&sidecar_url).send().await {
+        Ok(response) if response.status().is_success() => response.text().await.ok(),
+        Ok(response) => {
+            warning!(
+                "Checksum file not available (HTTP Status: {}). Skipping verification.",
+                response.status()
+            );
+            return None;
+        }
+        Err(e) => {
+            warning!(
+                "Failed to fetch checksum file: {}. Skipping verification.",
+                e
+            );
+            return None;
+        }
+    };
+
+    let checksum = body
+        .as_deref()
+        .and_then(|b| b.split_whitespace().next())
+        .filter(|c| utils::is_valid_sha256(c))
+        .map(|c| c.to_lowercase());
+    if checksum.is_none() {
+        warning!("Checksum file is malformed. Skipping verification.");
+    }
+    checksum
 }
 
-/// Extracts a downloaded package and sets up the release directory.
+/// Extracts a package and sets up the release directory.
 ///
-/// This function takes a downloaded archive file, extracts its contents to the installation
-/// directory, renames the extracted directory to match the release version, and cleans up
-/// temporary files.
+/// The archive is extracted into `extract_dir` first, and its `go` directory is
+/// only renamed to the release directory once extraction succeeded. A failed or
+/// concurrent installation therefore never leaves a partial release behind. The
+/// temporary directory is removed in any case, the archive itself is left in place.
 ///
 /// # Arguments
 ///
-/// * `archive_file` - A `PathBuf` representing the path to the downloaded archive file.
-/// * `release` - A `util::FilteredRelease` containing information about the release being installed.
+/// * `archive_file` - The path to the archive file.
+/// * `version` - The name of the installation directory of the release, e.g. "go1.22.0".
+/// * `extract_dir` - The temporary directory to extract the archive into.
+/// * `replace` - Whether an existing release directory is replaced. It is only
+///   removed once the new release has been extracted successfully.
+/// * `cancelled` - Checked before every archive entry and before the release is
+///   moved into place; once set, the extraction stops with an error.
 ///
 /// # Returns
 ///
 /// * `Res<()>` - A Result type. Returns `Ok(())` if the extraction and setup process is successful,
-///   or an error if any step fails.
-fn extract_package(archive_file: PathBuf, release: utils::FilteredRelease) -> Res<()> {
-    // get install path
-    let install_path = utils::get_version_file_path();
+///   or an error if any step fails or the extraction was cancelled.
+fn extract_package(
+    archive_file: &Path,
+    version: &str,
+    extract_dir: &Path,
+    replace: bool,
+    cancelled: &AtomicBool,
+) -> Res<()> {
+    let result = unpack_package(archive_file, version, extract_dir, replace, cancelled);
+    if extract_dir.exists() {
+        let _ = fs::remove_dir_all(extract_dir);
+    }
+    result
+}
 
-    // extract package to installation directory
-    let package_file = fs::File::open(&archive_file)?;
+/// Unpacks a package into `extract_dir` and moves its `go` directory into place.
+///
+/// The archive is only accepted if it contains `go/bin/go`, so a malformed
+/// archive never ends up as a version directory. See `extract_package`, which
+/// also removes `extract_dir` afterwards.
+fn unpack_package(
+    archive_file: &Path,
+    version: &str,
+    extract_dir: &Path,
+    replace: bool,
+    cancelled: &AtomicBool,
+) -> Res<()> {
+    // extract package to temporary directory
+    let package_file = fs::File::open(archive_file)?;
     let decompressor = GzDecoder::new(package_file);
     let mut package_archive = Archive::new(decompressor);
 
-    info!("Extracting package to: {}", install_path.display());
-    match package_archive.unpack(&install_path) {
-        Ok(_) => success!("Package extracted successfully."),
+    info!(
+        "Extracting package {} to: {}",
+        archive_file.display(),
+        extract_dir.display()
+    );
+    fs::create_dir_all(extract_dir)?;
+    let entries = match package_archive.entries() {
+        Ok(entries) => entries,
         Err(e) => error!("Error: Failed to extract package: {}", e),
+    };
+    for entry in entries {
+        if cancelled.load(Ordering::Relaxed) {
+            error!("Extraction of {} cancelled.", version);
+        }
+        if let Err(e) = entry.and_then(|mut entry| entry.unpack_in(extract_dir)) {
+            error!("Error: Failed to extract package: {}", e);
+        }
     }
+    success!("Package {} extracted successfully.", version);
 
     // create release
-    let version_path = Path::new(&install_path).join(&release.version);
-    let release_dir = Path::new(&install_path).join("go");
+    let version_path = utils::get_version_file_path().join(version);
+    let release_dir = extract_dir.join("go");
+    let go_bin = release_dir.join("bin");
+    if !go_bin.join("go").is_file() && !go_bin.join("go.exe").is_file() {
+        error!(
+            "Error: The archive {} did not contain a valid Go toolchain (go/bin/go is missing).",
+            archive_file.display()
+        );
+    }
 
+    if cancelled.load(Ordering::Relaxed) {
+        error!("Extraction of {} cancelled.", version);
+    }
     info!("Create release directory: {}", version_path.display());
+    if version_path.exists() {
+        if !replace {
+            error!("Error: Version {} was installed in the meantime.", version);
+        }
+        info!("Removing existing release directory ...");
+        if let Err(e) = fs::remove_dir_all(&version_path) {
+            error!("Error: Failed to remove existing release directory: {}", e);
+        }
+    }
     match fs::rename(&release_dir, &version_path) {
-        Ok(_) => success!("Release {} installed successfully.", release.version),
+        Ok(_) => success!(
+            "Release {} installed successfully to {}.",
+            version,
+            version_path.display()
+        ),
         Err(e) => error!("Error: Failed to rename release directory: {}", e),
     }
 
-    // clean up temporary files
-    info!("Clean up temporary files ...");
-    match fs::remove_file(&archive_file) {
-        Ok(_) => success!("Temporary files cleaned up successfully."),
-        Err(e) => error!("Error: Failed to remove temporary archive file: {}", e),
+    Ok(())
+}
+
+/// Checks that a file starts with the gzip magic bytes.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file to check.
+///
+/// # Returns
+///
+/// * `io::Result<bool>` - `true` if the file looks like a gzip archive.
+fn is_gzip_file(path: &Path) -> io::Result<bool> {
+    let mut magic = [0u8; 2];
+    let mut file = fs::File::open(path)?;
+    match file.read_exact(&mut magic) {
+        Ok(_) => Ok(magic == [0x1f, 0x8b]),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Installs a version from a pre-staged release archive.
+///
+/// The release cache is not consulted and nothing is downloaded, which makes
+/// this usable in air-gapped environments. The archive must be an official
+/// `.tar.gz` release containing `go/bin/go`, and is left in place after the
+/// installation.
+///
+/// # Arguments
+///
+/// * `version` - The version to install the archive as. Only used for the
+///   name of the installation directory.
+/// * `archive_file` - The path of the release archive.
+/// * `checksum` - An optional SHA-256 digest to verify the archive against.
+/// * `force` - Whether an existing installation of the version is replaced.
+///
+/// # Returns
+///
+/// * `Res<String>` - The installed version, or an error if the archive is
+///   missing, not a gzip file, fails verification or cannot be extracted.
+async fn install_from_file(
+    version: String,
+    archive_file: PathBuf,
+    checksum: Option<String>,
+    force: bool,
+) -> Res<String> {
+    if utils::is_latest_keyword(&version) {
+        error!("'{}' cannot be resolved without the release cache. Pass an explicit version with --from-file.", version);
+    }
+    let real_version = utils::parse_version_arg(version)?;
+
+    info!(
+        "Installing version {} from {} ...",
+        real_version,
+        archive_file.display()
+    );
+
+    if version_already_installed(real_version.clone()) {
+        if !force {
+            error!(
+                "Version {} is already installed. Pass --force to reinstall it.",
+                real_version
+            );
+        }
+        warning!(
+            "Version {} is already installed. Reinstalling it.",
+            real_version
+        );
+    }
+
+    if !archive_file.is_file() {
+        error!("Archive {} does not exist.", archive_file.display());
+    }
+    if !is_gzip_file(&archive_file)? {
+        error!(
+            "Archive {} is not a gzip compressed tarball.",
+            archive_file.display()
+        );
+    }
+
+    if checksum.is_none() {
+        warning!(
+            "No checksum given for {}. Pass --checksum to verify it.",
+            archive_file.display()
+        );
+    }
+
+    let extract_version = real_version.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        if let Some(expected) = checksum {
+            verify_checksum(&archive_file, &expected)?;
+        }
+        let cancelled = AtomicBool::new(false);
+        extract_package(
+            &archive_file,
+            &extract_version,
+            &get_extract_dir(),
+            force,
+            &cancelled,
+        )
+    })
+    .await?;
+
+    if let Err(err) = result {
+        error!("Error: Failed to install version {}: {}", real_version, err);
+    }
+
+    success!("Installing version {} complete.", real_version);
+    Ok(real_version)
+}
+
+/// Installs a version from the release cache or from a local archive.
+///
+/// # Arguments
+///
+/// * `version` - The version to install, e.g. "1.21.5", "1.22" or "latest".
+/// * `options` - How the version is installed, see `InstallOptions`.
+///
+/// # Returns
+///
+/// * `Res<()>` - `Ok(())` if the version was installed (and activated), or an
+///   error if any step fails.
+pub async fn install(version: String, options: InstallOptions) -> Res<()> {
+    let InstallOptions {
+        activate,
+        checksum,
+        verify_sidecar,
+        refresh,
+        from_file,
+        force,
+    } = options;
+    utils::validate_name("version", &version)?;
+    if let Some(ref checksum) = checksum {
+        if !utils::is_valid_sha256(checksum) {
+            error!(
+                "Invalid checksum '{}'. Expected 64 hexadecimal characters.",
+                checksum
+            );
+        }
+    }
+
+    if let Some(archive_file) = from_file {
+        let real_version = install_from_file(version, archive_file, checksum, force).await?;
+        if let Some(activate_default) = activate {
+            return activate_version(real_version, activate_default).await;
+        }
+        return Ok(());
+    }
+
+    super::update::refresh_release_cache(refresh).await?;
+
+    let real_version = install_release(version, checksum, verify_sidecar, force).await?;
+
+    if let Some(activate_default) = activate {
+        return activate_version(real_version, activate_default).await;
     }
 
     Ok(())
 }
 
-pub async fn install(version: String, use_version: bool) -> Res<()> {
-    let mut cache_dir: PathBuf = utils::get_cache_dir();
-    cache_dir.push(config::RELEASE_CACHE_FILE);
-    let data = async_fs::read_to_string(&cache_dir).await?;
-    let available_versions: Vec<utils::FilteredRelease> = serde_json::from_str(&data)?;
+/// Installs several versions concurrently.
+///
+/// The release cache is refreshed once up front and every version is resolved
+/// against it, so versions resolving to the same release (e.g. "1.22" and
+/// "1.22.5") install it once. Then at most `jobs` versions are downloaded and
+/// extracted at the same time, each with a progress bar labelled with its
+/// version. A failed version does not stop the others; the failures are
+/// summarized at the end.
+///
+/// # Arguments
+///
+/// * `versions` - The versions to install, e.g. "1.21.5" or "latest".
+/// * `jobs` - How many versions are installed at the same time, at least one.
+/// * `options` - How the versions are installed. Only `verify_sidecar`,
+///   `refresh` and `force` apply.
+///
+/// # Returns
+///
+/// * `Res<()>` - `Ok(())` if every version was installed, or an error naming
+///   the versions that failed.
+pub async fn install_many(versions: Vec<String>, jobs: usize, options: InstallOptions) -> Res<()> {
+    let InstallOptions {
+        verify_sidecar,
+        refresh,
+        force,
+        ..
+    } = options;
+    for version in &versions {
+        utils::validate_name("version", version)?;
+    }
+    super::update::refresh_release_cache(refresh).await?;
+
+    let mut failed = Vec::new();
+    let mut resolved = Vec::new();
+    for version in versions {
+        match resolve_release(version.clone()).await {
+            Ok(release) => resolved.push((version, release)),
+            Err(err) => {
+                warning!("{}: {}", version, err);
+                failed.push(version);
+            }
+        }
+    }
+    let releases = dedupe_releases(resolved);
 
-    let version_filter = get_real_version(version);
+    let total = releases.len() + failed.len();
+    let permits = Arc::new(Semaphore::new(jobs.max(1)));
+    let mut tasks = JoinSet::new();
+    for release in releases {
+        let permits = Arc::clone(&permits);
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let version = release.version.clone();
+            let result = install_resolved(release, None, verify_sidecar, force).await;
+            (version, result.map_err(|e| e.to_string()))
+        });
+    }
 
-    let releases: Vec<utils::FilteredRelease> = available_versions
-        .into_iter()
-        .filter(|release| release.version == version_filter)
-        .collect();
+    while let Some(joined) = tasks.join_next().await {
+        let (version, result) = joined?;
+        if let Err(err) = result {
+            warning!("{}: {}", version, err);
+            failed.push(version);
+        }
+    }
 
-    if releases.len() == 0 || releases.len() > 1 {
+    if !failed.is_empty() {
+        failed.sort_by(|a, b| utils::cmp_versions(a, b));
         error!(
-            "Version not found or multiple versions found in cache for version {}.",
-            version_filter
+            "{} of {} versions failed to install: {}",
+            failed.len(),
+            total,
+            failed.join(", ")
         );
     }
 
-    let release = releases.get(0).unwrap();
+    success!("Installed {} versions.", total);
+    Ok(())
+}
+
+/// Drops releases that an earlier requested version already resolved to.
+///
+/// Releases are compared by their install directory, and a warning names the
+/// requested version that is skipped.
+///
+/// # Arguments
+///
+/// * `resolved` - The requested versions with the releases they resolved to,
+///   in the order they were requested.
+///
+/// # Returns
+///
+/// * `Vec<utils::FilteredRelease>` - Every release once, in requested order.
+fn dedupe_releases(resolved: Vec<(String, utils::FilteredRelease)>) -> Vec<utils::FilteredRelease> {
+    let mut releases: Vec<utils::FilteredRelease> = Vec::new();
+    for (version, release) in resolved {
+        let install_dir_name = utils::get_install_dir_name(&release.version);
+        if releases
+            .iter()
+            .any(|r| utils::get_install_dir_name(&r.version) == install_dir_name)
+        {
+            warning!(
+                "{} resolves to {}, which is already being installed.",
+                version,
+                release.version
+            );
+            continue;
+        }
+        releases.push(release);
+    }
+    releases
+}
+
+/// Prints what `gvm install` would do, without downloading or writing anything.
+///
+/// Every version is resolved against the release cache as a real install
+/// would, but the cache is not refreshed first, so the preview reflects the
+/// cache as it is.
+///
+/// # Arguments
+///
+/// * `versions` - The versions to preview, e.g. "1.22" or "latest".
+/// * `options` - How the versions would be installed. An explicit checksum
+///   wins over the cached one; `refresh` and `from_file` are ignored.
+///
+/// # Returns
+///
+/// * `Res<()>` - `Ok(())` if every version can be installed, or an error for
+///   the first one that cannot.
+pub async fn install_dry_run(versions: Vec<String>, options: InstallOptions) -> Res<()> {
+    let InstallOptions {
+        activate,
+        checksum,
+        verify_sidecar,
+        force,
+        ..
+    } = options;
+    for version in versions {
+        utils::validate_name("version", &version)?;
+        let release = resolve_release(version).await?;
+        let install_dir_name = utils::get_install_dir_name(&release.version);
+        let install_dir = utils::get_version_file_path().join(&install_dir_name);
+        if version_already_installed(install_dir_name) && !force {
+            error!(
+                "Version {} is already installed. Pass --force to reinstall it.",
+                release.version
+            );
+        }
+
+        let checksum = match (checksum.clone(), release.sha256.clone()) {
+            (Some(checksum), _) => checksum,
+            (None, Some(cached)) => cached,
+            (None, None) if verify_sidecar => format!("from {}.sha256", release.url),
+            (None, None) => "unknown, the archive would not be verified".to_string(),
+        };
+        let source = match get_cached_archive_file(&release.url)? {
+            cached if utils::is_archive_cache_enabled() && cached.is_file() => {
+                format!("{} (cached)", cached.display())
+            }
+            _ => release.url.clone(),
+        };
+        let activation = match activate {
+            Some(true) => "yes, and repoint the default alias",
+            Some(false) => "yes, without repointing the default alias",
+            None => "no",
+        };
+
+        println!("Would install {}:", release.version);
+        println!("  archive: {}", source);
+        println!("  sha256: {}", checksum);
+        println!("  directory: {}", install_dir.display());
+        if install_dir.exists() {
+            println!("  replaces: the existing installation");
+        }
+        println!("  activate: {}", activation);
+    }
+
+    Ok(())
+}
+
+/// Looks up a release in the release cache.
+///
+/// # Arguments
+///
+/// * `version` - The version to look up, a minor version like "1.22" for its
+///   newest patch release, or the "latest" keyword.
+///
+/// # Returns
+///
+/// * `Res<utils::FilteredRelease>` - The cached release, or an error if the
+///   cache cannot be read or has no matching release for the target platform.
+async fn resolve_release(version: String) -> Res<utils::FilteredRelease> {
+    let cache_file = utils::get_release_cache_file_path();
+    let data = match async_fs::read_to_string(&cache_file).await {
+        Ok(data) => data,
+        Err(e) => error!(
+            "Error reading release cache {}: {}. Run 'gvm update' first.",
+            cache_file.display(),
+            e
+        ),
+    };
+    let (available_versions, foreign_versions): (Vec<_>, Vec<_>) =
+        utils::parse_release_cache(&data)?
+            .into_iter()
+            .partition(utils::is_target_release);
+
+    let version_filter = if utils::is_latest_keyword(&version) {
+        let latest =
+            utils::latest_stable_version(available_versions.iter().map(|r| r.version.as_str()));
+        match latest {
+            Some(latest) => {
+                info!("Resolved '{}' to version {}.", version, latest);
+                latest
+            }
+            None => error!("No stable release found in cache. Run 'gvm update' first."),
+        }
+    } else {
+        let real_version = utils::parse_version_arg(version)?;
+        match utils::latest_patch_version(
+            &real_version,
+            available_versions.iter().map(|r| r.version.as_str()),
+        ) {
+            Some(patch) if patch != real_version => {
+                info!("Resolved '{}' to version {}.", real_version, patch);
+                patch
+            }
+            _ => real_version,
+        }
+    };
+
+    let release = match available_versions
+        .into_iter()
+        .find(|release| release.version == version_filter)
+    {
+        Some(release) => release,
+        None if foreign_versions.iter().any(|r| r.version == version_filter) => error!(
+            "Cached release {} is not built for {}/{}. Run 'gvm update' with the same --assume-os/--assume-arch first.",
+            version_filter,
+            utils::get_target_os(),
+            utils::get_target_arch()
+        ),
+        None => error!("Version {} not found in cache.", version_filter),
+    };
+
+    Ok(release)
+}
+
+/// Downloads and installs a release from the release cache.
+///
+/// # Arguments
+///
+/// * `version` - The version to install, or the "latest" keyword.
+/// * `checksum` - An explicit SHA-256 digest, which wins over the cached one.
+/// * `verify_sidecar` - Whether a missing checksum is fetched from the sidecar.
+/// * `force` - Whether an existing installation of the version is replaced.
+///   The active version stays active, since it is activated by directory name.
+///
+/// # Returns
+///
+/// * `Res<String>` - The installed version, or an error if it is not in the
+///   cache, already installed without `force`, or the download or extraction
+///   fails.
+async fn install_release(
+    version: String,
+    checksum: Option<String>,
+    verify_sidecar: bool,
+    force: bool,
+) -> Res<String> {
+    let release = resolve_release(version).await?;
+    install_resolved(release, checksum, verify_sidecar, force).await
+}
+
+/// Downloads and installs a release resolved by `resolve_release`.
+///
+/// # Arguments
+///
+/// * `release` - The release to install.
+/// * `checksum` - An explicit SHA-256 digest, which wins over the cached one.
+/// * `verify_sidecar` - Whether a missing checksum is fetched from the sidecar.
+/// * `force` - Whether an existing installation of the version is replaced.
+///
+/// # Returns
+///
+/// * `Res<String>` - The installed version, or an error if it is already
+///   installed without `force`, or the download or extraction fails.
+async fn install_resolved(
+    release: utils::FilteredRelease,
+    checksum: Option<String>,
+    verify_sidecar: bool,
+    force: bool,
+) -> Res<String> {
     info!("Installing version {} ...", release.version);
 
-    if version_already_installed(release.version.clone()) {
-        error!("Version {} is already installed.", release.version);
+    if version_already_installed(utils::get_install_dir_name(&release.version)) {
+        if !force {
+            error!(
+                "Version {} is already installed. Pass --force to reinstall it.",
+                release.version
+            );
+        }
+        warning!(
+            "Version {} is already installed. Reinstalling it.",
+            release.version
+        );
     }
 
-    let archive_file = download_release(release.url.clone()).await?;
+    // An explicit checksum always wins, then the one recorded in the release
+    // cache, then the sidecar published next to the archive.
+    let checksum = match (checksum, release.sha256.clone()) {
+        (Some(checksum), _) => Some(checksum),
+        (None, Some(cached)) => Some(cached),
+        (None, None) if verify_sidecar => fetch_sidecar_checksum(&release.url).await,
+        (None, None) => {
+            warning!(
+                "No checksum recorded for {}. Run 'gvm update' to refresh the release cache.",
+                release.version
+            );
+            None
+        }
+    };
+
+    let archive_file = get_archive_file(&release.url)?;
+    let cached_archive = get_cached_archive_file(&release.url)?;
+    let extract_dir = get_extract_dir();
+    // the extraction runs on a blocking thread, which keeps running when the
+    // future below is dropped on Ctrl-C; it holds the `extraction` permit
+    // until it is done, and stops early once `cancelled` is set
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let extraction = Arc::new(Semaphore::new(1));
+    let download_and_extract = async {
+        // only archives with a known digest are reused, since only those can be
+        // checked for corruption
+        let cached = match checksum.clone() {
+            Some(expected) if utils::is_archive_cache_enabled() => {
+                let cached_archive = cached_archive.clone();
+                tokio::task::spawn_blocking(move || {
+                    reuse_cached_archive(&cached_archive, &expected)
+                })
+                .await?
+            }
+            _ => false,
+        };
+        let archive_file = if cached {
+            cached_archive.clone()
+        } else {
+            download_release(release.url.clone(), &release.version).await?
+        };
+        let install_dir_name = utils::get_install_dir_name(&release.version);
+        let extract_dir = extract_dir.clone();
+        let cached_archive = cached_archive.clone();
+        let cancelled = Arc::clone(&cancelled);
+        let permit = Arc::clone(&extraction).acquire_owned().await?;
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            if cached {
+                return extract_package(
+                    &archive_file,
+                    &install_dir_name,
+                    &extract_dir,
+                    force,
+                    &cancelled,
+                );
+            }
+            if let Some(ref expected) = checksum {
+                // never extract a corrupted or tampered download
+                if let Err(err) = verify_checksum(&archive_file, expected) {
+                    let _ = fs::remove_file(&archive_file);
+                    return Err(err);
+                }
+            }
+            extract_package(
+                &archive_file,
+                &install_dir_name,
+                &extract_dir,
+                force,
+                &cancelled,
+            )?;
+
+            if checksum.is_some() {
+                store_cached_archive(&archive_file, &cached_archive);
+                return Ok(());
+            }
 
-    match extract_package(archive_file, release.clone()) {
-        Ok(_) => success!("Installing version {} complete.", release.version),
-        Err(err) => {
-            error!("Error: Failed to extract package: {}", err);
+            // clean up temporary files
+            info!("Clean up temporary files of {} ...", install_dir_name);
+            match fs::remove_file(&archive_file) {
+                Ok(_) => success!("Temporary files cleaned up successfully."),
+                Err(e) => error!("Error: Failed to remove temporary archive file: {}", e),
+            }
+            Ok(())
+        })
+        .await?
+    };
+
+    tokio::select! {
+        result = download_and_extract => match result {
+            Ok(_) => success!("Installing version {} complete.", release.version),
+            Err(err) => {
+                error!("Error: Failed to install version {}: {}", release.version, err);
+            }
+        },
+        _ = tokio::signal::ctrl_c() => {
+            cancelled.store(true, Ordering::Relaxed);
+            let _ = extraction.acquire().await;
+            cleanup_partial_install(&archive_file, &extract_dir);
+            error!("install cancelled, cleaned up.");
         }
     }
 
-    if use_version {
-        return activate_version(release.version.clone()).await;
+    Ok(release.version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::update;
+    use crate::utils::test_support::{assert_rejected, MALICIOUS_NAMES};
+
+    /// Writes a gzipped tarball holding an empty file for each of `entries`.
+    fn write_package(path: &Path, entries: &[&str]) {
+        let encoder = flate2::write::GzEncoder::new(
+            fs::File::create(path).unwrap(),
+            flate2::Compression::fast(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        for entry in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(0);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, entry, io::empty())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
     }
 
-    Ok(())
+    #[test]
+    fn extract_dirs_are_unique() {
+        let dirs: Vec<PathBuf> = (0..100).map(|_| get_extract_dir()).collect();
+        let mut unique = dirs.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), dirs.len());
+    }
+
+    #[test]
+    fn failed_extraction_leaves_no_partial_directories() {
+        let packages = tempfile::tempdir().unwrap();
+        let archive = packages.path().join("go1.78.1.linux-amd64.tar.gz");
+        write_package(&archive, &["go/README.md", "go/src/fmt/print.go"]);
+        let extract_dir = get_extract_dir();
+
+        let err = extract_package(
+            &archive,
+            "go1.78.1",
+            &extract_dir,
+            false,
+            &AtomicBool::new(false),
+        )
+        .unwrap_err();
+
+        assert!(err.to_string().contains("go/bin/go is missing"), "{}", err);
+        assert!(!extract_dir.exists());
+        assert!(!utils::get_version_file_path().join("go1.78.1").exists());
+    }
+
+    #[test]
+    fn extraction_moves_the_toolchain_into_place() {
+        let packages = tempfile::tempdir().unwrap();
+        let archive = packages.path().join("go1.78.2.linux-amd64.tar.gz");
+        write_package(&archive, &["go/bin/go", "go/README.md"]);
+        let extract_dir = get_extract_dir();
+
+        extract_package(
+            &archive,
+            "go1.78.2",
+            &extract_dir,
+            false,
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+
+        let version_dir = utils::get_version_file_path().join("go1.78.2");
+        assert!(version_dir.join("bin").join("go").is_file());
+        assert!(!extract_dir.exists());
+        fs::remove_dir_all(version_dir).unwrap();
+    }
+
+    #[test]
+    fn cancelled_extraction_stops_and_cleans_up() {
+        let packages = tempfile::tempdir().unwrap();
+        let archive = packages.path().join("go1.78.3.linux-amd64.tar.gz");
+        write_package(&archive, &["go/bin/go", "go/README.md"]);
+        let extract_dir = get_extract_dir();
+
+        let err = extract_package(
+            &archive,
+            "go1.78.3",
+            &extract_dir,
+            false,
+            &AtomicBool::new(true),
+        )
+        .unwrap_err();
+
+        assert!(err.to_string().contains("cancelled"), "{}", err);
+        assert!(!extract_dir.exists());
+        assert!(!utils::get_version_file_path().join("go1.78.3").exists());
+    }
+
+    #[tokio::test]
+    async fn install_from_file_extracts_a_local_archive() {
+        let packages = tempfile::tempdir().unwrap();
+        let archive = packages.path().join("go.tar.gz");
+        write_package(&archive, &["go/bin/go", "go/README.md"]);
+        let not_gzip = packages.path().join("go.tar");
+        fs::write(&not_gzip, "not a tarball").unwrap();
+        let version_dir = utils::get_version_file_path().join("go1.78.4");
+
+        let err = install_from_file("1.78.4".to_string(), not_gzip, None, false)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not a gzip"), "{}", err);
+        let err = install_from_file(
+            "1.78.4".to_string(),
+            archive.clone(),
+            Some("00".repeat(32)),
+            false,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"), "{}", err);
+        assert!(!version_dir.exists());
+
+        let installed = install_from_file("1.78.4".to_string(), archive.clone(), None, false)
+            .await
+            .unwrap();
+        assert_eq!(installed, "go1.78.4");
+        assert!(version_dir.join("bin").join("go").is_file());
+        assert!(archive.is_file());
+        fs::remove_dir_all(version_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn force_replaces_an_existing_installation() {
+        let packages = tempfile::tempdir().unwrap();
+        let archive = packages.path().join("go.tar.gz");
+        write_package(&archive, &["go/bin/go"]);
+        let version_dir = utils::get_version_file_path().join("go1.78.5");
+        fs::create_dir_all(&version_dir).unwrap();
+        fs::write(version_dir.join("partial"), "").unwrap();
+
+        let err = install_from_file("1.78.5".to_string(), archive.clone(), None, false)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("already installed"), "{}", err);
+        assert!(version_dir.join("partial").is_file());
+
+        install_from_file("1.78.5".to_string(), archive, None, true)
+            .await
+            .unwrap();
+        assert!(!version_dir.join("partial").exists());
+        assert!(version_dir.join("bin").join("go").is_file());
+        fs::remove_dir_all(version_dir).unwrap();
+    }
+
+    /// Serves `body` to a single HTTP request and returns the URL of `path`.
+    async fn serve_once(path: &str, body: Vec<u8>) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/{}", listener.local_addr().unwrap(), path);
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(head.as_bytes()).await.unwrap();
+            stream.write_all(&body).await.unwrap();
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn download_replaces_a_stray_archive() {
+        let package = "go1.30.4.t304-amd64.tar.gz";
+        let url = serve_once(package, b"fresh archive".to_vec()).await;
+        let stray = get_archive_file(&url).unwrap();
+        fs::write(&stray, "partial download of an interrupted install").unwrap();
+
+        let archive_file = download_release(url, "go1.30.4").await.unwrap();
+
+        assert_eq!(archive_file, stray);
+        assert_eq!(fs::read(&archive_file).unwrap(), b"fresh archive");
+        fs::remove_file(archive_file).unwrap();
+    }
+
+    #[tokio::test]
+    async fn reinstalling_reuses_the_cached_archive() {
+        let packages = tempfile::tempdir().unwrap();
+        let archive = packages.path().join("go.tar.gz");
+        write_package(&archive, &["go/bin/go"]);
+        // the server answers a single request, so a second download would fail
+        let url = serve_once("go1.31.3.t313-amd64.tar.gz", fs::read(&archive).unwrap()).await;
+        let release = utils::FilteredRelease {
+            version: "go1.31.3".to_string(),
+            sha256: Some(utils::sha256_file(&archive).unwrap()),
+            url: url.clone(),
+            os: "linux".to_string(),
+            arch: "amd64".to_string(),
+        };
+        let version_dir = utils::get_version_file_path().join("go1.31.3");
+        let cached_archive = get_cached_archive_file(&url).unwrap();
+
+        install_resolved(release.clone(), None, false, false)
+            .await
+            .unwrap();
+        assert!(cached_archive.is_file());
+        fs::remove_dir_all(&version_dir).unwrap();
+
+        install_resolved(release, None, false, false).await.unwrap();
+        assert!(version_dir.join("bin").join("go").is_file());
+        fs::remove_dir_all(&version_dir).unwrap();
+        fs::remove_file(cached_archive).unwrap();
+    }
+
+    #[test]
+    fn cleanup_partial_install_removes_the_leftovers() {
+        let archive_file = utils::get_archive_file_path().join("go1.30.5.t304-amd64.tar.gz");
+        let extract_dir = get_extract_dir();
+        fs::create_dir_all(extract_dir.join("go")).unwrap();
+        fs::write(&archive_file, "partial").unwrap();
+
+        cleanup_partial_install(&archive_file, &extract_dir);
+
+        assert!(!archive_file.exists());
+        assert!(!extract_dir.exists());
+    }
+
+    #[test]
+    fn verify_checksum_compares_the_archive_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("go.tar.gz");
+        fs::write(&archive, "hello\n").unwrap();
+        let digest = "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
+
+        verify_checksum(&archive, digest).unwrap();
+        verify_checksum(&archive, &digest.to_uppercase()).unwrap();
+
+        let wrong = digest.replace('5', "6");
+        let err = verify_checksum(&archive, &wrong).unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"), "{}", err);
+        assert!(err.to_string().contains(digest), "{}", err);
+    }
+
+    fn release(version: &str) -> utils::FilteredRelease {
+        utils::FilteredRelease {
+            version: version.to_string(),
+            url: format!("https://go.dev/dl/{}.linux-amd64.tar.gz", version),
+            sha256: None,
+            os: "linux".to_string(),
+            arch: "amd64".to_string(),
+        }
+    }
+
+    #[test]
+    fn dedupe_releases_installs_each_release_once() {
+        let resolved = vec![
+            ("1.22".to_string(), release("go1.22.5")),
+            ("1.21.3".to_string(), release("go1.21.3")),
+            ("go1.22.5".to_string(), release("go1.22.5")),
+            ("latest".to_string(), release("go1.22.5")),
+        ];
+
+        let versions: Vec<String> = dedupe_releases(resolved)
+            .into_iter()
+            .map(|r| r.version)
+            .collect();
+
+        assert_eq!(versions, ["go1.22.5", "go1.21.3"]);
+    }
+
+    /// Writes the release cache shared by the tests once and returns its releases.
+    async fn cached_releases() -> &'static [utils::FilteredRelease] {
+        static RELEASES: tokio::sync::OnceCell<Vec<utils::FilteredRelease>> =
+            tokio::sync::OnceCell::const_new();
+        RELEASES
+            .get_or_init(|| async {
+                let target = |version: &str| utils::FilteredRelease {
+                    version: version.to_string(),
+                    url: format!(
+                        "https://go.dev/dl/{}.{}-{}.tar.gz",
+                        version,
+                        utils::get_target_os(),
+                        utils::get_target_arch()
+                    ),
+                    sha256: Some("ab".repeat(32)),
+                    os: utils::get_target_os(),
+                    arch: utils::get_target_arch(),
+                };
+                let mut foreign = release("go1.88.9");
+                foreign.os = "plan9".to_string();
+                let releases = vec![
+                    target("go1.89rc1"),
+                    target("go1.88.2"),
+                    target("go1.88.0"),
+                    foreign,
+                ];
+                update::write_release_cache(utils::get_release_cache_file_path(), &releases)
+                    .await
+                    .unwrap();
+                releases
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn resolve_release_reads_the_cache_update_writes() {
+        let releases = cached_releases().await;
+
+        for (version, expected) in [
+            ("1.88", &releases[1]),
+            ("go1.88.0", &releases[2]),
+            ("latest", &releases[1]),
+            ("latest-stable", &releases[1]),
+        ] {
+            let resolved = resolve_release(version.to_string()).await.unwrap();
+            assert_eq!(resolved.version, expected.version);
+            assert_eq!(resolved.url, expected.url);
+            assert_eq!(resolved.sha256, expected.sha256);
+        }
+        let err = resolve_release("1.88.9".to_string()).await.unwrap_err();
+        assert!(err.to_string().contains("1.88.9"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn dry_run_leaves_nothing_behind() {
+        let release = &cached_releases().await[1];
+        let options = InstallOptions {
+            activate: Some(true),
+            ..InstallOptions::default()
+        };
+
+        install_dry_run(vec!["1.88".to_string()], options)
+            .await
+            .unwrap();
+
+        let install_dir =
+            utils::get_version_file_path().join(utils::get_install_dir_name(&release.version));
+        assert!(!install_dir.exists());
+        assert!(!get_archive_file(&release.url).unwrap().exists());
+        assert!(!get_cached_archive_file(&release.url).unwrap().exists());
+    }
+
+    #[tokio::test]
+    async fn install_rejects_malicious_versions() {
+        for name in MALICIOUS_NAMES {
+            assert_rejected(
+                name,
+                install(name.to_string(), InstallOptions::default()).await,
+            );
+            assert_rejected(
+                name,
+                install_many(vec![name.to_string()], 1, InstallOptions::default()).await,
+            );
+            assert_rejected(
+                name,
+                install_dry_run(vec![name.to_string()], InstallOptions::default()).await,
+            );
+        }
+    }
 }
@@ -1,23 +1,58 @@
-use crate::{utils, Res};
+use std::cmp::Ordering;
 
-/// Lists installed Go versions, optionally filtered by version and stability.
-///
-/// This function retrieves all installed Go versions, applies filters based on the provided
-/// parameters, sorts the results, and prints them to the console.
+use serde::Serialize;
+
+use crate::{utils, warning, Res};
+
+/// An installed version as printed by `gvm list --json`.
+#[derive(Serialize, Debug)]
+struct ListEntry {
+    version: String,
+    active: bool,
+    installed: bool,
+    /// The newest patch release of the version's minor line, if newer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latest: Option<String>,
+}
+
+/// Finds a newer patch release of an installed version.
 ///
 /// # Parameters
 ///
-/// * `version`: An optional String that specifies a version filter. If provided, only versions
-///              matching this filter will be listed. The filter can end with '*' for prefix matching.
-///
-/// * `stable`: A boolean flag. When set to true, only stable versions will be listed.
+/// * `version`: The installed version, e.g. "go1.22.1".
+/// * `available`: The versions in the release cache.
 ///
 /// # Returns
 ///
-/// Returns `Res<()>`, which is `Ok(())` if the operation succeeds, or an error if it fails.
-pub async fn list(version: Option<String>, stable: bool) -> Res<()> {
-    let mut releases: Vec<String> = utils::list_installed_versions().await?;
+/// The newest stable release of the same minor line (e.g. "go1.22.6") if it
+/// is newer than `version`, otherwise `None`. Toolchains installed for a
+/// foreign platform are never annotated.
+fn newer_patch(version: &str, available: &[String]) -> Option<String> {
+    let (parts, suffix) = utils::parse_version_parts(version);
+    if parts.len() < 2 || suffix.starts_with('.') {
+        return None;
+    }
+    let minor = format!("go{}.{}", parts[0], parts[1]);
+    utils::latest_patch_version(&minor, available.iter().map(String::as_str))
+        .filter(|latest| utils::cmp_versions(latest, version) == Ordering::Greater)
+}
 
+/// Applies the version and stability filters of `list` and sorts the result.
+///
+/// # Parameters
+///
+/// * `releases`: The installed versions.
+/// * `version`: A version to match exactly, or a prefix ending in '*'.
+/// * `stable`: When set to true, pre-releases are dropped.
+///
+/// # Returns
+///
+/// The matching versions, oldest first.
+fn select_versions(
+    mut releases: Vec<String>,
+    version: Option<String>,
+    stable: bool,
+) -> Vec<String> {
     let version_filter = version.map(|f| {
         if f.starts_with("go") {
             f
@@ -27,7 +62,7 @@ pub async fn list(version: Option<String>, stable: bool) -> Res<()> {
     });
 
     releases.retain(|r: &String| {
-        if stable && !utils::is_stable_version(&r) {
+        if stable && !utils::is_stable_version(r) {
             return false;
         }
         if let Some(ref filter) = version_filter {
@@ -42,16 +77,170 @@ pub async fn list(version: Option<String>, stable: bool) -> Res<()> {
         }
     });
 
-    releases.sort_by(|a, b| utils::cmp_versions(&a, &b));
+    releases.sort_by(|a, b| utils::cmp_versions(a, b));
+    releases
+}
+
+/// Renders the versions printed by `gvm list --names-only`, one per line.
+fn render_names(releases: &[String]) -> String {
+    releases
+        .iter()
+        .map(|release| format!("{}\n", release))
+        .collect()
+}
+
+/// Lists installed Go versions, optionally filtered by version and stability.
+///
+/// This function retrieves all installed Go versions, applies filters based on the provided
+/// parameters, sorts the results, and prints them to the console.
+///
+/// # Parameters
+///
+/// * `version`: An optional String that specifies a version filter. If provided, only versions
+///   matching this filter will be listed. The filter can end with '*' for prefix matching.
+///
+/// * `stable`: A boolean flag. When set to true, only stable versions will be listed.
+///
+/// * `json`: When set to true, the versions are printed as a JSON array in the
+///   same order instead of colored text.
+///
+/// * `outdated`: When set to true, versions with a newer patch release in the
+///   release cache are annotated with it. Without a cache, a hint is printed
+///   and the versions are listed as usual.
+///
+/// * `names_only`: When set to true, only the version names are printed, one
+///   per line, without color or the active marker, for use in scripts.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` if the operation succeeds, or an error if it fails.
+pub async fn list(
+    version: Option<String>,
+    stable: bool,
+    json: bool,
+    outdated: bool,
+    names_only: bool,
+) -> Res<()> {
+    let releases = select_versions(utils::list_installed_versions().await?, version, stable);
+
+    if names_only {
+        print!("{}", render_names(&releases));
+        return Ok(());
+    }
+
+    let active_version = utils::get_active_version().await;
+    let is_active = |release: &String| active_version.as_ref() == Some(release);
+
+    let mut available: Vec<String> = Vec::new();
+    if outdated {
+        let cache_file = utils::get_release_cache_file_path();
+        match utils::list_cached_versions(&cache_file, None, true).await {
+            Ok(cached) => available = cached.into_iter().map(|r| r.version).collect(),
+            Err(_) => warning!(
+                "No release cache found at {}. Run 'gvm update' to see available updates.",
+                cache_file.display()
+            ),
+        }
+    }
+
+    if json {
+        let entries: Vec<ListEntry> = releases
+            .into_iter()
+            .map(|release| ListEntry {
+                active: is_active(&release),
+                installed: true,
+                latest: newer_patch(&release, &available),
+                version: release,
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
 
     for release in releases {
-        if utils::is_version_active(&release).await {
-            use colored::Colorize;
-            println!("{} {}", release.green().bold(), "*".yellow());
+        use colored::Colorize;
+        let latest = newer_patch(&release, &available)
+            .map(|latest| format!(" (latest: {})", latest).cyan().to_string())
+            .unwrap_or_default();
+        if is_active(&release) {
+            println!("{} {}{}", release.green().bold(), "*".yellow(), latest);
         } else {
-            println!("{}", release);
+            println!("{}{}", release, latest);
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn names_only_prints_bare_versions() {
+        let installed: Vec<String> = ["go1.22.1", "go1.21.5", "go1.23rc1", "go1.22.0"]
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+
+        let all = select_versions(installed.clone(), None, false);
+        assert_eq!(
+            render_names(&all),
+            "go1.21.5\ngo1.22.0\ngo1.22.1\ngo1.23rc1\n"
+        );
+        let stable = select_versions(installed.clone(), Some("1.22.*".to_string()), true);
+        assert_eq!(render_names(&stable), "go1.22.0\ngo1.22.1\n");
+        assert_eq!(
+            render_names(&select_versions(installed, Some("1.20".to_string()), false)),
+            ""
+        );
+    }
+
+    #[test]
+    fn newer_patch_annotates_outdated_versions_only() {
+        let available: Vec<String> = ["go1.21.13", "go1.22.1", "go1.22.6", "go1.23rc1"]
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+
+        assert_eq!(
+            newer_patch("go1.22.1", &available).as_deref(),
+            Some("go1.22.6")
+        );
+        assert_eq!(
+            newer_patch("go1.21.0", &available).as_deref(),
+            Some("go1.21.13")
+        );
+        assert_eq!(newer_patch("go1.22.6", &available), None);
+        assert_eq!(newer_patch("go1.23rc1", &available), None);
+        assert_eq!(newer_patch("go1.24.0", &available), None);
+        assert_eq!(newer_patch("go1.22.1.darwin-arm64", &available), None);
+    }
+
+    #[test]
+    fn json_entries_have_the_documented_shape() {
+        let entries = [
+            ListEntry {
+                version: "go1.22.1".to_string(),
+                active: true,
+                installed: true,
+                latest: Some("go1.22.6".to_string()),
+            },
+            ListEntry {
+                version: "go1.23.0".to_string(),
+                active: false,
+                installed: true,
+                latest: None,
+            },
+        ];
+
+        assert_eq!(
+            serde_json::to_value(entries).unwrap(),
+            json!([
+                {"version": "go1.22.1", "active": true, "installed": true, "latest": "go1.22.6"},
+                {"version": "go1.23.0", "active": false, "installed": true}
+            ])
+        );
+    }
+}
@@ -1,12 +1,134 @@
-use std::path::PathBuf;
+use serde::Serialize;
 
-use crate::{config, utils, Res};
+use super::update::refresh_release_cache;
+use crate::{info, utils, Res};
+
+/// How `gvm list-remote` prints the releases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListRemoteFormat {
+    /// One version per line, installed ones highlighted.
+    Text,
+    /// A JSON array of the releases.
+    Json,
+    /// One line per minor version with its newest release.
+    Minor,
+}
+
+/// A cached release as printed by `gvm list-remote --json`.
+#[derive(Serialize, Debug)]
+struct ListRemoteEntry {
+    version: String,
+    url: String,
+    active: bool,
+    installed: bool,
+}
+
+/// Collapses releases into one entry per minor version.
+///
+/// Releases are grouped on the first two numeric parts of their version, so
+/// "go1.22.0", "go1.22.6" and "go1.22rc1" share the line "go1.22".
+///
+/// # Parameters
+///
+/// * `releases`: The releases to group, sorted ascending.
+///
+/// # Returns
+///
+/// Pairs of the minor version and the newest release of that line, oldest
+/// line first.
+fn group_by_minor(releases: Vec<utils::FilteredRelease>) -> Vec<(String, utils::FilteredRelease)> {
+    let mut groups: Vec<(String, utils::FilteredRelease)> = Vec::new();
+    for release in releases {
+        let (parts, _) = utils::parse_version_parts(&release.version);
+        let minor = match parts.as_slice() {
+            [major, minor, ..] => format!("go{}.{}", major, minor),
+            _ => release.version.clone(),
+        };
+        match groups.last_mut() {
+            // sorted ascending, so the last release of a line is its newest
+            Some((last, newest)) if *last == minor => *newest = release,
+            _ => groups.push((minor, release)),
+        }
+    }
+    groups
+}
+
+/// Returns how the release cache is refreshed before listing a platform.
+///
+/// The cache of a foreign platform (`--os`/`--arch`) is only ever written on
+/// demand, so instead of a hint it is fetched when missing or stale. With
+/// `--json` the fetch runs quietly, see `verbosity` in `main`.
+///
+/// # Parameters
+///
+/// * `refresh`: The policy requested on the command line.
+/// * `foreign`: Whether releases are listed for a foreign platform.
+fn platform_refresh(refresh: utils::CacheRefresh, foreign: bool) -> utils::CacheRefresh {
+    match refresh {
+        utils::CacheRefresh::Hint if foreign => utils::CacheRefresh::IfStale,
+        refresh => refresh,
+    }
+}
+
+/// Keeps only the `newest` last or the `oldest` first of an ascending list.
+///
+/// `newest` wins if both are given; with neither, the list is left alone.
+fn select_ends<T>(items: &mut Vec<T>, newest: Option<usize>, oldest: Option<usize>) {
+    if let Some(n) = newest {
+        items.drain(..items.len().saturating_sub(n));
+    } else if let Some(n) = oldest {
+        items.truncate(n);
+    }
+}
+
+/// Keeps only the installed releases, or only the others.
+///
+/// With `installed` unset, the list is left alone.
+fn select_installed(
+    releases: &mut Vec<utils::FilteredRelease>,
+    installed_releases: &[String],
+    installed: Option<bool>,
+) {
+    if let Some(installed) = installed {
+        releases.retain(|release| {
+            installed_releases.contains(&utils::get_install_dir_name(&release.version)) == installed
+        });
+    }
+}
+
+/// Builds the entries printed by `gvm list-remote --json`.
+///
+/// # Parameters
+///
+/// * `releases`: The releases to print.
+/// * `installed_releases`: The names of the installed version directories.
+/// * `active_version`: The active version, if any.
+///
+/// # Returns
+///
+/// One entry per release, in the same order.
+fn remote_entries(
+    releases: Vec<utils::FilteredRelease>,
+    installed_releases: &[String],
+    active_version: Option<&str>,
+) -> Vec<ListRemoteEntry> {
+    releases
+        .into_iter()
+        .map(|release| ListRemoteEntry {
+            active: active_version == Some(release.version.as_str()),
+            installed: installed_releases.contains(&utils::get_install_dir_name(&release.version)),
+            version: release.version,
+            url: release.url,
+        })
+        .collect()
+}
 
 /// Lists remote Go versions based on the cached releases.
 ///
 /// This function retrieves the list of Go versions from the local cache,
 /// applies filtering based on the provided parameters, and prints the
-/// resulting list of versions to the console.
+/// resulting list of versions to the console, oldest first so the newest
+/// version ends up last.
 ///
 /// # Parameters
 ///
@@ -15,22 +137,89 @@ use crate::{config, utils, Res};
 ///   an exact version or use a wildcard (e.g., "1.21.*").
 ///
 /// * `stable`: A boolean flag. When set to `true`, only stable versions
+///   will be listed. `gvm list-remote` passes `true` unless `--all` is given.
+///
+/// * `newest`: When set, only the given number of newest matching versions
+///   will be listed.
+///
+/// * `oldest`: When set, only the given number of oldest matching versions
 ///   will be listed.
 ///
+/// * `refresh`: Whether the release cache is updated before listing, always
+///   or only when it is stale. Otherwise a stale cache yields a hint. The
+///   cache of a foreign platform (`--os`/`--arch`) is always fetched when it
+///   is missing or stale.
+///
+/// * `format`: How the releases are printed. `Json` prints a JSON array in the
+///   same order instead of colored text. `Minor` prints one line per minor
+///   version with its newest release, e.g. "go1.22 (latest go1.22.6)";
+///   `newest` and `oldest` then count minor versions.
+///
+/// * `installed`: When set, only releases that are installed (`Some(true)`)
+///   or not installed (`Some(false)`) are listed. The filter is applied before
+///   `newest` and `oldest`.
+///
 /// # Returns
 ///
 /// Returns `Res<()>`, which is `Ok(())` if the operation succeeds, or
 /// an error if there's a problem reading the cache or processing the data.
-pub async fn list_remote(version: Option<String>, stable: bool) -> Res<()> {
-    let mut cache_file: PathBuf = utils::get_cache_dir();
-    cache_file.push(config::RELEASE_CACHE_FILE);
+pub async fn list_remote(
+    version: Option<String>,
+    stable: bool,
+    newest: Option<usize>,
+    oldest: Option<usize>,
+    refresh: utils::CacheRefresh,
+    format: ListRemoteFormat,
+    installed: Option<bool>,
+) -> Res<()> {
+    let json = format == ListRemoteFormat::Json;
+    refresh_release_cache(platform_refresh(refresh, utils::is_foreign_platform())).await?;
+    if utils::is_foreign_platform() && !json {
+        info!(
+            "Releases for {}/{}:",
+            utils::get_target_os(),
+            utils::get_target_arch()
+        );
+    }
+
+    let cache_file = utils::get_release_cache_file_path();
 
-    let releases: Vec<utils::FilteredRelease> =
+    let mut releases: Vec<utils::FilteredRelease> =
         utils::list_cached_versions(cache_file, version, stable).await?;
     let installed_releases: Vec<String> = utils::list_installed_versions().await?;
+    select_installed(&mut releases, &installed_releases, installed);
+
+    if format == ListRemoteFormat::Minor {
+        let mut groups = group_by_minor(releases);
+        select_ends(&mut groups, newest, oldest);
+        for (minor, release) in groups {
+            if installed_releases.contains(&utils::get_install_dir_name(&release.version)) {
+                use colored::Colorize;
+                println!(
+                    "{} (latest {} {})",
+                    minor,
+                    release.version.green().bold(),
+                    "*".yellow()
+                );
+            } else {
+                println!("{} (latest {})", minor, release.version);
+            }
+        }
+        return Ok(());
+    }
+
+    // Releases are sorted ascending, so the newest ones are at the end.
+    select_ends(&mut releases, newest, oldest);
+
+    if json {
+        let active_version = utils::get_active_version().await;
+        let entries = remote_entries(releases, &installed_releases, active_version.as_deref());
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
 
     for release in releases {
-        if installed_releases.contains(&release.version) {
+        if installed_releases.contains(&utils::get_install_dir_name(&release.version)) {
             use colored::Colorize;
             println!("{} {}", release.version.green().bold(), "*".yellow());
         } else {
@@ -39,3 +228,172 @@ pub async fn list_remote(version: Option<String>, stable: bool) -> Res<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::update;
+    use serde_json::json;
+
+    fn release(version: &str) -> utils::FilteredRelease {
+        utils::FilteredRelease {
+            version: version.to_string(),
+            url: format!("https://go.dev/dl/{}.linux-amd64.tar.gz", version),
+            sha256: None,
+            os: "linux".to_string(),
+            arch: "amd64".to_string(),
+        }
+    }
+
+    #[test]
+    fn json_entries_have_the_documented_shape() {
+        let entries = remote_entries(
+            vec![release("go1.21.0"), release("go1.22.0")],
+            &["go1.21.0".to_string(), "go1.22.0".to_string()],
+            Some("go1.22.0"),
+        );
+
+        assert_eq!(
+            serde_json::to_value(&entries).unwrap(),
+            json!([
+                {
+                    "version": "go1.21.0",
+                    "url": "https://go.dev/dl/go1.21.0.linux-amd64.tar.gz",
+                    "active": false,
+                    "installed": true
+                },
+                {
+                    "version": "go1.22.0",
+                    "url": "https://go.dev/dl/go1.22.0.linux-amd64.tar.gz",
+                    "active": true,
+                    "installed": true
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn json_entries_mark_missing_versions_as_not_installed() {
+        let entries = remote_entries(vec![release("go1.23.0")], &[], None);
+
+        assert_eq!(
+            serde_json::to_value(&entries).unwrap(),
+            json!([{
+                "version": "go1.23.0",
+                "url": "https://go.dev/dl/go1.23.0.linux-amd64.tar.gz",
+                "active": false,
+                "installed": false
+            }])
+        );
+    }
+
+    #[test]
+    fn foreign_platform_caches_are_fetched_when_stale() {
+        use utils::CacheRefresh::{Always, Hint, IfStale};
+
+        assert_eq!(platform_refresh(Hint, false), Hint);
+        assert_eq!(platform_refresh(Hint, true), IfStale);
+        assert_eq!(platform_refresh(IfStale, true), IfStale);
+        assert_eq!(platform_refresh(Always, true), Always);
+        assert_eq!(platform_refresh(Always, false), Always);
+    }
+
+    #[test]
+    fn select_ends_takes_from_either_end() {
+        let select = |newest, oldest| {
+            let mut versions = vec!["go1.20", "go1.21", "go1.22", "go1.23", "go1.24"];
+            select_ends(&mut versions, newest, oldest);
+            versions
+        };
+
+        assert_eq!(select(Some(2), None), ["go1.23", "go1.24"]);
+        assert_eq!(select(None, Some(2)), ["go1.20", "go1.21"]);
+        assert_eq!(select(Some(10), None).len(), 5);
+        assert_eq!(select(None, Some(10)).len(), 5);
+        assert!(select(Some(0), None).is_empty());
+        assert_eq!(select(None, None).len(), 5);
+    }
+
+    #[test]
+    fn the_newest_limit_applies_to_json_entries() {
+        let mut releases: Vec<_> = ["go1.21.0", "go1.22.0", "go1.22.1", "go1.23.0"]
+            .into_iter()
+            .map(release)
+            .collect();
+        select_ends(&mut releases, Some(3), None);
+
+        let entries = remote_entries(releases, &[], None);
+        let versions: Vec<&str> = entries.iter().map(|e| e.version.as_str()).collect();
+        assert_eq!(versions, ["go1.22.0", "go1.22.1", "go1.23.0"]);
+    }
+
+    #[test]
+    fn installed_toggles_split_the_cache() {
+        let installed_releases = ["go1.21.0".to_string(), "go1.22.1".to_string()];
+        let select = |installed| {
+            let mut releases: Vec<_> = ["go1.21.0", "go1.22.0", "go1.22.1", "go1.23.0"]
+                .into_iter()
+                .map(release)
+                .collect();
+            select_installed(&mut releases, &installed_releases, installed);
+            releases.into_iter().map(|r| r.version).collect::<Vec<_>>()
+        };
+
+        assert_eq!(select(Some(true)), ["go1.21.0", "go1.22.1"]);
+        assert_eq!(select(Some(false)), ["go1.22.0", "go1.23.0"]);
+        assert_eq!(select(None).len(), 4);
+    }
+
+    #[tokio::test]
+    async fn minor_lines_respect_the_stable_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_file = dir.path().join("releases.json");
+        let releases: Vec<_> = ["go1.22.5", "go1.22.6", "go1.23rc1", "go1.23rc2"]
+            .into_iter()
+            .map(|version| utils::FilteredRelease {
+                os: utils::get_target_os(),
+                arch: utils::get_target_arch(),
+                ..release(version)
+            })
+            .collect();
+        update::write_release_cache(&cache_file, &releases)
+            .await
+            .unwrap();
+        let lines = |stable| {
+            let cache_file = cache_file.clone();
+            async move {
+                let releases = utils::list_cached_versions(cache_file, None, stable)
+                    .await
+                    .unwrap();
+                group_by_minor(releases)
+                    .into_iter()
+                    .map(|(minor, newest)| (minor, newest.version))
+                    .collect::<Vec<_>>()
+            }
+        };
+        let line = |minor: &str, newest: &str| (minor.to_string(), newest.to_string());
+
+        assert_eq!(
+            lines(false).await,
+            [line("go1.22", "go1.22.6"), line("go1.23", "go1.23rc2")]
+        );
+        assert_eq!(lines(true).await, [line("go1.22", "go1.22.6")]);
+    }
+
+    #[test]
+    fn group_by_minor_keeps_the_newest_release_per_line() {
+        let groups = group_by_minor(vec![
+            release("go1.21.0"),
+            release("go1.21.13"),
+            release("go1.22rc1"),
+            release("go1.22.0"),
+            release("go1.22.6"),
+        ]);
+
+        let lines: Vec<(&str, &str)> = groups
+            .iter()
+            .map(|(minor, newest)| (minor.as_str(), newest.version.as_str()))
+            .collect();
+        assert_eq!(lines, [("go1.21", "go1.21.13"), ("go1.22", "go1.22.6")]);
+    }
+}
@@ -0,0 +1,173 @@
+use std::{env, path::Path};
+
+use crate::{config, error, info, success, utils, Res};
+
+/// Reads the version pinned in a `.go-version` file.
+///
+/// The content is checked like a version given on the command line, so a
+/// file naming something else, e.g. a path, is rejected.
+///
+/// # Parameters
+///
+/// * `version_file`: The `.go-version` file to read.
+///
+/// # Returns
+///
+/// Returns `Res<String>`, the pinned version with the "go" prefix, or an error
+/// if the file cannot be read, is empty, or does not name a Go version.
+async fn read_pinned_version(version_file: &Path) -> Res<String> {
+    let content = async_fs::read_to_string(version_file).await?;
+    let pinned = content.trim();
+    if pinned.is_empty() {
+        error!("{} is empty.", version_file.display());
+    }
+    match utils::parse_version_arg(pinned.to_string()) {
+        Ok(real_version) => Ok(real_version),
+        Err(e) => error!("{}: {}", version_file.display(), e),
+    }
+}
+
+/// Writes a version to the `.go-version` file in a directory.
+///
+/// The "go" prefix is left out, like in the files other Go tools write.
+///
+/// # Parameters
+///
+/// * `dir`: The directory to pin the version in.
+/// * `real_version`: The version to pin, including the "go" prefix.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` if the file was written, or an error
+/// if writing it fails.
+async fn pin_version(dir: &Path, real_version: &str) -> Res<()> {
+    let version_file = dir.join(config::GO_VERSION_FILE);
+    let content = format!("{}\n", real_version.trim_start_matches("go"));
+    match async_fs::write(&version_file, content).await {
+        Ok(_) => success!("Pinned {} in {}.", real_version, version_file.display()),
+        Err(e) => error!("Error writing {}: {}", version_file.display(), e),
+    }
+    Ok(())
+}
+
+/// Pins a version for the current directory or activates the pinned version.
+///
+/// With a version, it is written to a `.go-version` file in the current
+/// directory. Without one, the nearest `.go-version` is looked up by walking
+/// up from the current directory to the filesystem root, and the version it
+/// names is activated. The `default` alias is left unchanged, so new shells
+/// outside the project keep using the global version.
+///
+/// # Parameters
+///
+/// * `version`: The version to pin, or `None` to activate the pinned version.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` if the version was pinned or activated,
+/// or an error if the file cannot be read or written.
+pub async fn local(version: Option<String>) -> Res<()> {
+    let current_dir = env::current_dir()?;
+
+    if let Some(version) = version {
+        let real_version = utils::parse_version_arg(version)?;
+        let installed_versions = utils::list_installed_versions().await?;
+        if !installed_versions.contains(&real_version) {
+            info!(
+                "Version {} is not installed yet. Run 'gvm install {}' before activating it.",
+                real_version, real_version
+            );
+        }
+
+        return pin_version(&current_dir, &real_version).await;
+    }
+
+    let version_file = match utils::find_file_upwards(&current_dir, config::GO_VERSION_FILE) {
+        Some(version_file) => version_file,
+        None => error!(
+            "No {} found in {} or any parent directory. Pin a version with 'gvm local <version>'.",
+            config::GO_VERSION_FILE,
+            current_dir.display()
+        ),
+    };
+
+    let real_version = read_pinned_version(&version_file).await?;
+    info!(
+        "Using version {} from {}.",
+        real_version,
+        version_file.display()
+    );
+
+    let installed_versions = utils::list_installed_versions().await?;
+    if !installed_versions.contains(&real_version) {
+        error!(
+            "Version {} is not installed. Please install it first.",
+            real_version
+        );
+    }
+
+    utils::activate_version(real_version, false).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::{assert_rejected, MALICIOUS_NAMES};
+
+    #[tokio::test]
+    async fn pinned_versions_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let version_file = dir.path().join(config::GO_VERSION_FILE);
+
+        pin_version(dir.path(), "go1.22.3").await.unwrap();
+
+        assert_eq!(std::fs::read_to_string(&version_file).unwrap(), "1.22.3\n");
+        assert_eq!(
+            read_pinned_version(&version_file).await.unwrap(),
+            "go1.22.3"
+        );
+    }
+
+    #[tokio::test]
+    async fn pinned_versions_are_normalized() {
+        let dir = tempfile::tempdir().unwrap();
+        let version_file = dir.path().join(config::GO_VERSION_FILE);
+        for (content, expected) in [
+            ("1.22.0\n", "go1.22.0"),
+            ("  go1.23rc1  \n", "go1.23rc1"),
+            ("1.21", "go1.21"),
+        ] {
+            std::fs::write(&version_file, content).unwrap();
+            assert_eq!(read_pinned_version(&version_file).await.unwrap(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn pinned_versions_are_validated() {
+        let dir = tempfile::tempdir().unwrap();
+        let version_file = dir.path().join(config::GO_VERSION_FILE);
+        for content in ["", "\n", "../../bin", "golang1.22", "1.22; rm -rf ~"] {
+            std::fs::write(&version_file, content).unwrap();
+            assert!(
+                read_pinned_version(&version_file).await.is_err(),
+                "{:?} was accepted",
+                content
+            );
+        }
+        for name in MALICIOUS_NAMES {
+            std::fs::write(&version_file, name).unwrap();
+            assert!(
+                read_pinned_version(&version_file).await.is_err(),
+                "{:?}",
+                name
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn local_rejects_malicious_versions() {
+        for name in MALICIOUS_NAMES {
+            assert_rejected(name, local(Some(name.to_string())).await);
+        }
+    }
+}
@@ -1,19 +1,61 @@
 mod alias;
+mod cache;
+mod complete;
+mod configure;
+mod current;
+mod diff;
+mod doctor;
+mod env;
+mod exec;
+mod export_env;
+mod freeze;
+mod hook;
+mod info;
 mod init;
 mod install;
 mod list;
 mod list_remote;
+mod local;
+mod prune;
+mod rehash;
 mod remove;
 mod remove_alias;
+mod rename_alias;
+mod restore;
+mod self_update;
+mod shim;
 mod update;
 mod use_version;
+mod which;
 
-pub use alias::alias;
+pub use alias::{alias, AliasOptions};
+pub use cache::{cache, CacheAction};
+pub use complete::{
+    add_dynamic_completions, complete_aliases, complete_versions, install_completions,
+};
+pub use configure::{configure, ConfigAction};
+pub use current::current;
+pub use diff::diff;
+pub use doctor::doctor;
+pub use env::{env, EnvFormat};
+pub use exec::exec;
+pub use export_env::export_env;
+pub use freeze::freeze;
+pub use hook::{hook, HookShell};
+pub use info::info;
 pub use init::init;
-pub use install::install;
+pub use install::{install, install_dry_run, install_many, InstallOptions};
 pub use list::list;
-pub use list_remote::list_remote;
+pub use list_remote::{list_remote, ListRemoteFormat};
+pub use local::local;
+pub use prune::prune;
+pub use rehash::rehash;
 pub use remove::remove;
 pub use remove_alias::remove_alias;
-pub use update::update;
+pub use rename_alias::rename_alias;
+pub use restore::restore;
+pub use self_update::self_update;
+pub use shim::{shim, ShimAction};
+pub use update::{check_update, update};
 pub use use_version::use_version;
+pub use which::which;
@@ -0,0 +1,287 @@
+use std::collections::{BTreeMap, HashSet};
+
+use super::remove::remove_version;
+use crate::{info, success, utils, Res};
+
+/// Returns the versions to remove under a retention policy.
+///
+/// Versions in `protected` are always kept. Without `keep`, every other version
+/// is removed. With `keep`, versions are grouped by major.minor and the `keep`
+/// newest versions of each group are retained as well.
+///
+/// # Parameters
+///
+/// * `installed`: The installed versions.
+/// * `protected`: Versions that must not be removed (active and aliased ones).
+/// * `keep`: The number of newest versions to retain per minor version.
+///
+/// # Returns
+///
+/// The versions to remove, sorted from oldest to newest.
+fn select_prunable(
+    installed: Vec<String>,
+    protected: &HashSet<String>,
+    keep: Option<usize>,
+) -> Vec<String> {
+    let mut groups: BTreeMap<Vec<u32>, Vec<String>> = BTreeMap::new();
+    for version in installed {
+        let (parts, _) = utils::parse_version_parts(&version);
+        let minor = parts.into_iter().take(2).collect();
+        groups.entry(minor).or_default().push(version);
+    }
+
+    let mut prunable = Vec::new();
+    for (_, mut versions) in groups {
+        versions.sort_by(|a, b| utils::cmp_versions(b, a));
+        let retained = keep.unwrap_or(0);
+        prunable.extend(
+            versions
+                .into_iter()
+                .skip(retained)
+                .filter(|v| !protected.contains(v)),
+        );
+    }
+
+    prunable.sort_by(|a, b| utils::cmp_versions(a, b));
+    prunable
+}
+
+/// Returns the newest stable versions among the installed ones.
+///
+/// # Parameters
+///
+/// * `installed`: The installed versions.
+/// * `count`: The number of versions to return.
+fn newest_stable(installed: &[String], count: usize) -> Vec<String> {
+    let mut stable: Vec<&String> = installed
+        .iter()
+        .filter(|v| utils::is_stable_version(v))
+        .collect();
+    stable.sort_by(|a, b| utils::cmp_versions(b, a));
+    stable.into_iter().take(count).cloned().collect()
+}
+
+/// Returns the versions to remove under both retention policies.
+///
+/// A version is kept if it is protected, among the `keep` newest of its minor
+/// version or among the `keep_newest` newest stable versions overall, so the
+/// two limits add up rather than override each other.
+///
+/// # Parameters
+///
+/// * `installed`: The installed versions.
+/// * `protected`: Versions that must not be removed (active and aliased ones).
+/// * `keep`: The number of newest versions to retain per minor version.
+/// * `keep_newest`: The number of newest stable versions to retain overall.
+///
+/// # Returns
+///
+/// The versions to remove, sorted from oldest to newest.
+fn plan_prune(
+    installed: Vec<String>,
+    mut protected: HashSet<String>,
+    keep: Option<usize>,
+    keep_newest: Option<usize>,
+) -> Vec<String> {
+    if let Some(count) = keep_newest {
+        protected.extend(newest_stable(&installed, count));
+    }
+    select_prunable(installed, &protected, keep)
+}
+
+/// Returns the active version and every version an alias points to.
+async fn protected_versions() -> Res<HashSet<String>> {
+    let mut protected = HashSet::new();
+    if let Some(active) = utils::get_active_version().await {
+        protected.insert(active);
+    }
+    for alias_name in utils::list_aliases().await? {
+        if let Some(target) = utils::get_alias_target(&alias_name).await {
+            protected.insert(target);
+        }
+    }
+    Ok(protected)
+}
+
+/// Removes installed versions that are no longer needed.
+///
+/// The active version and every version an alias points to are always kept,
+/// even with `--keep 0` or `--keep-newest 0`. Without `keep` and `keep_newest`,
+/// all other versions are removed. With `keep`, the `keep` newest versions of
+/// each major.minor line are retained too. With `keep_newest`, the
+/// `keep_newest` newest stable versions overall are retained too; given both,
+/// a version is kept if either retains it, see `plan_prune`.
+///
+/// # Parameters
+///
+/// * `keep`: The number of newest versions to retain per minor version.
+/// * `keep_newest`: The number of newest stable versions to retain overall.
+/// * `dry_run`: When `true`, the versions are only listed, not removed.
+/// * `yes`: When `true`, the versions are removed without asking for
+///   confirmation, see `utils::confirm`.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` if pruning completed, or an error if
+/// the GVM directories cannot be read.
+pub async fn prune(
+    keep: Option<usize>,
+    keep_newest: Option<usize>,
+    dry_run: bool,
+    yes: bool,
+) -> Res<()> {
+    let protected = protected_versions().await?;
+    let installed = utils::list_installed_versions().await?;
+    let prunable = plan_prune(installed, protected, keep, keep_newest);
+    if prunable.is_empty() {
+        success!("Nothing to prune.");
+        return Ok(());
+    }
+
+    if !dry_run && !utils::confirm(&format!("Remove {}?", prunable.join(", ")), yes)? {
+        info!("Nothing pruned.");
+        return Ok(());
+    }
+
+    for version in &prunable {
+        if dry_run {
+            info!("Would remove version {}.", version);
+            continue;
+        }
+
+        // removed like `gvm remove`, which also forgets it as previous version
+        remove_version(version.clone(), false, false).await?;
+    }
+
+    if dry_run {
+        success!("{} versions would be removed.", prunable.len());
+    } else {
+        success!("Pruned {} versions.", prunable.len());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::{clear_activation, fake_alias, fake_version, lock_activation};
+
+    fn versions(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn installed() -> Vec<String> {
+        versions(&[
+            "go1.22.1",
+            "go1.21.3",
+            "go1.20.5",
+            "go1.22rc1",
+            "go1.21.1",
+            "go1.22.0",
+            "go1.21.2",
+        ])
+    }
+
+    #[test]
+    fn keep_retains_the_newest_versions_of_each_minor_version() {
+        let none = HashSet::new();
+
+        assert_eq!(
+            select_prunable(installed(), &none, Some(1)),
+            ["go1.21.1", "go1.21.2", "go1.22rc1", "go1.22.0"]
+        );
+        assert_eq!(
+            select_prunable(installed(), &none, Some(2)),
+            ["go1.21.1", "go1.22rc1"]
+        );
+        assert!(select_prunable(installed(), &none, Some(3)).is_empty());
+    }
+
+    #[test]
+    fn without_keep_everything_unprotected_is_removed_oldest_first() {
+        let none = HashSet::new();
+
+        assert_eq!(
+            select_prunable(installed(), &none, None),
+            [
+                "go1.20.5",
+                "go1.21.1",
+                "go1.21.2",
+                "go1.21.3",
+                "go1.22rc1",
+                "go1.22.0",
+                "go1.22.1"
+            ]
+        );
+        assert_eq!(
+            select_prunable(installed(), &none, Some(0)),
+            select_prunable(installed(), &none, None)
+        );
+    }
+
+    #[test]
+    fn keep_newest_retains_the_newest_stable_versions_overall() {
+        let none = HashSet::new();
+
+        assert_eq!(
+            plan_prune(installed(), none.clone(), None, Some(2)),
+            ["go1.20.5", "go1.21.1", "go1.21.2", "go1.21.3", "go1.22rc1"]
+        );
+        // both limits retain their versions
+        assert_eq!(
+            plan_prune(installed(), none, Some(2), Some(1)),
+            ["go1.21.1", "go1.22rc1"]
+        );
+    }
+
+    #[test]
+    fn zero_limits_keep_the_active_and_aliased_versions() {
+        let protected: HashSet<String> = versions(&["go1.21.1", "go1.22rc1"]).into_iter().collect();
+
+        for (keep, keep_newest) in [(Some(0), None), (None, Some(0)), (Some(0), Some(0))] {
+            assert_eq!(
+                plan_prune(installed(), protected.clone(), keep, keep_newest),
+                ["go1.20.5", "go1.21.2", "go1.21.3", "go1.22.0", "go1.22.1"]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn the_active_version_and_alias_targets_are_protected() {
+        let _lock = lock_activation().await;
+        clear_activation();
+        fake_version("go1.38.1");
+        fake_version("go1.38.2");
+        fake_version("go1.38.3");
+        utils::write_file_atomic(utils::get_version_file_path().join("active"), "go1.38.1")
+            .await
+            .unwrap();
+        fake_alias("t267-kept", "go1.38.2", false);
+
+        let protected = protected_versions().await.unwrap();
+
+        assert!(protected.contains("go1.38.1"));
+        assert!(protected.contains("go1.38.2"));
+        assert!(!protected.contains("go1.38.3"));
+        clear_activation();
+    }
+
+    #[test]
+    fn protected_versions_are_always_kept() {
+        let protected: HashSet<String> = versions(&["go1.20.5", "go1.21.1"]).into_iter().collect();
+
+        for keep in [None, Some(0), Some(1)] {
+            let prunable = select_prunable(installed(), &protected, keep);
+            assert!(
+                prunable.iter().all(|v| !protected.contains(v)),
+                "{:?}",
+                keep
+            );
+        }
+        assert_eq!(
+            select_prunable(installed(), &protected, Some(1)),
+            ["go1.21.2", "go1.22rc1", "go1.22.0"]
+        );
+    }
+}
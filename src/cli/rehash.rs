@@ -0,0 +1,41 @@
+use crate::{error, info, success, utils, Res};
+
+/// Rebuilds the environment of the active version.
+///
+/// This function regenerates `go.env` for the active version, makes sure its
+/// GOPATH/bin directory exists and recreates the `default` alias if it is
+/// missing or dangling. Tools installed with `go install` become visible after
+/// reloading the profile.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` if the environment was rebuilt, or an
+/// error if no version is active.
+pub async fn rehash() -> Res<()> {
+    let active_version = match utils::get_active_version().await {
+        Some(v) => v,
+        None => error!("No active version found. Use 'gvm use <version>' first."),
+    };
+
+    let gopath_bin = utils::get_gopath(&active_version).join("bin");
+    async_fs::create_dir_all(&gopath_bin).await?;
+
+    utils::init_go_environment(Some(active_version.clone())).await?;
+
+    let release_dir = utils::get_version_file_path().join(&active_version);
+    let default_alias = utils::get_alias_file_path().join("default");
+    if !default_alias.exists() {
+        info!(
+            "Recreating default alias for version '{}' ...",
+            active_version
+        );
+        utils::replace_symlink(&release_dir, &default_alias).await?;
+    }
+
+    success!(
+        "Rehashed version '{}'. Please reload profile.",
+        active_version
+    );
+
+    Ok(())
+}
@@ -1,26 +1,87 @@
 use std::fs;
 
-use crate::{error, info, success, utils, Res};
+use crate::{error, info, success, utils, warning, Res};
+
+/// Deactivates a version that is about to be removed.
+///
+/// Removes the `active` file and `go.env`, which would otherwise point new
+/// shells at the removed GOROOT.
+///
+/// # Returns
+///
+/// * `Res<()>`: `Ok(())` if the version was deactivated, or an error if one
+///   of the files exists but cannot be removed.
+async fn deactivate_version() -> Res<()> {
+    let files = [
+        utils::get_version_file_path().join("active"),
+        utils::get_environment_file_path().join("go.env"),
+    ];
+
+    for file in files {
+        match fs::remove_file(&file) {
+            Ok(_) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => error!("Failed to remove {}: {}", file.display(), err),
+        }
+    }
+    Ok(())
+}
+
+/// Forgets a version that is about to be removed as the previous version.
+///
+/// Removes the `previous` file if it records the version, so `gvm use -` does
+/// not switch back to it.
+///
+/// # Parameters
+///
+/// * `real_version`: The normalized version to forget.
+///
+/// # Returns
+///
+/// * `Res<()>`: `Ok(())` if the version is no longer recorded, or an error if
+///   the file cannot be removed.
+async fn forget_previous_version(real_version: &str) -> Res<()> {
+    if utils::read_previous_file().await.as_deref() != Some(real_version) {
+        return Ok(());
+    }
+
+    let previous = utils::get_version_file_path().join("previous");
+    match async_fs::remove_file(&previous).await {
+        Ok(_) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => error!("Failed to remove {}: {}", previous.display(), err),
+    }
+}
 
 /// Removes a specified version of the software from the system.
 ///
 /// This function performs the following steps:
 /// 1. Checks if the specified version is installed.
-/// 2. Ensures the version is not currently active.
-/// 3. Removes the default alias for the version.
-/// 4. Removes the version directory.
+/// 2. Collects the aliases resolving to the version, including aliases of
+///    aliases, before any of them is removed.
+/// 3. Ensures the version is not currently active, or deactivates it if `force` is set,
+///    see `deactivate_version`.
+/// 4. Drops the `previous` file if it records the version.
+/// 5. Removes the default alias if it points to the version.
+/// 6. Removes the other aliases resolving to the version, or warns about them.
+/// 7. Removes the version directory.
 ///
 /// # Parameters
 ///
-/// * `version`: A String representing the version to be removed.
+/// * `real_version`: The normalized version to be removed.
+/// * `force`: When `true`, an active version is deactivated instead of refused.
+/// * `keep_aliases`: When `true`, aliases other than `default` that resolve to
+///   the version are kept and only reported.
 ///
 /// # Returns
 ///
 /// * `Res<()>`: A Result type. Returns Ok(()) if the removal is successful,
 ///   or an error if any step of the removal process fails.
-pub async fn remove(version: String) -> Res<()> {
-    let real_version = utils::get_real_version(version);
-
+pub(super) async fn remove_version(
+    real_version: String,
+    force: bool,
+    keep_aliases: bool,
+) -> Res<()> {
     info!("Checking if version {} is installed...", real_version);
     let installed_versions: Vec<String> = utils::list_installed_versions().await?;
     if !installed_versions.contains(&real_version) {
@@ -30,32 +91,302 @@ pub async fn remove(version: String) -> Res<()> {
         );
     }
 
+    // resolve every alias up front: once `default` or an alias in the middle of
+    // a chain is gone, the aliases following it no longer resolve
+    let mut pointing_aliases = Vec::new();
+    for alias in utils::list_aliases().await? {
+        if utils::get_alias_target(&alias).await.as_deref() == Some(real_version.as_str()) {
+            pointing_aliases.push(alias);
+        }
+    }
+    pointing_aliases.sort();
+
     info!("Checking if version {} is active...", real_version);
     if utils::is_version_active(&real_version).await {
-        error!(
-            "Version {} is currently active. Please deactivate it first.",
-            real_version
-        );
+        if !force {
+            error!(
+                "Version {} is currently active. Please deactivate it first or pass --force.",
+                real_version
+            );
+        }
+
+        info!("Deactivating version {}...", real_version);
+        if let Err(err) = deactivate_version().await {
+            error!("Failed to deactivate version {}: {}", real_version, err);
+        }
+        success!("Version {} deactivated.", real_version);
+    }
+    forget_previous_version(&real_version).await?;
+
+    if pointing_aliases.iter().any(|alias| alias == "default") {
+        info!("Removing default alias for version '{}'...", real_version);
+        let alias_dir = utils::get_alias_file_path();
+        let alias_path = alias_dir.join("default");
+        match utils::remove_existing_symlink(alias_path).await {
+            Ok(_) => success!("Default alias removed for version {}.", real_version),
+            Err(err) => error!(
+                "Failed to remove default alias for version {}: {}",
+                real_version, err
+            ),
+        }
     }
 
-    info!("Removing default alias for version '{}'...", real_version);
     let alias_dir = utils::get_alias_file_path();
-    let alias_path = alias_dir.join("default");
-    match utils::remove_existing_symlink(alias_path).await {
-        Ok(_) => success!("Default alias removed for version {}.", real_version),
-        Err(err) => error!(
-            "Failed to remove default alias for version {}: {}",
-            real_version, err
-        ),
+    for alias in pointing_aliases {
+        if alias == "default" {
+            continue;
+        }
+
+        if keep_aliases {
+            warning!(
+                "Alias '{}' points to version {} and will be broken.",
+                alias,
+                real_version
+            );
+            continue;
+        }
+
+        match utils::remove_existing_symlink(alias_dir.join(&alias)).await {
+            Ok(_) => success!("Alias '{}' removed for version {}.", alias, real_version),
+            Err(err) => error!(
+                "Failed to remove alias '{}' for version {}: {}",
+                alias, real_version, err
+            ),
+        }
     }
 
     info!("Removing version {}...", real_version);
     let version_dir = utils::get_version_file_path();
     let version_path = version_dir.join(&real_version);
-    match fs::remove_dir_all(version_path) {
+    match async_fs::remove_dir_all(version_path).await {
         Ok(_) => success!("Version {} removed.", real_version),
         Err(err) => error!("Failed to remove version {}: {}", real_version, err),
     }
 
     Ok(())
 }
+
+/// Removes one or more installed versions.
+///
+/// Every version goes through the same checks as a single removal. The active
+/// version is never removed unless `force` is set, in which case it is
+/// deactivated first (the `active` marker, `go.env` and a `default` alias
+/// pointing to it are removed). A `previous` marker naming a removed version
+/// is removed as well. Aliases resolving to a removed version, including aliases of
+/// such aliases, are removed as well unless `keep_aliases` is set.
+///
+/// # Parameters
+///
+/// * `versions`: The versions to be removed.
+/// * `all`: When `true`, every installed version is removed instead. The active
+///   version is skipped unless `force` is set.
+/// * `force`: When `true`, the active version is deactivated and removed too.
+/// * `keep_aliases`: When `true`, aliases pointing to a removed version are
+///   left in place instead of being removed with it.
+/// * `yes`: When `true`, the versions are removed without asking for
+///   confirmation, see `utils::confirm`.
+///
+/// # Returns
+///
+/// * `Res<()>`: A Result type. Returns Ok(()) if all removals are successful,
+///   or an error as soon as one fails.
+pub async fn remove(
+    versions: Vec<String>,
+    all: bool,
+    force: bool,
+    keep_aliases: bool,
+    yes: bool,
+) -> Res<()> {
+    let versions: Vec<String> = if all {
+        let active_version = utils::get_active_version().await;
+        let mut installed = utils::list_installed_versions().await?;
+        installed.sort_by(|a, b| utils::cmp_versions(a, b));
+        installed
+            .into_iter()
+            .filter(|v| {
+                let skip = !force && active_version.as_ref() == Some(v);
+                if skip {
+                    info!("Keeping active version {}.", v);
+                }
+                !skip
+            })
+            .collect()
+    } else {
+        for version in &versions {
+            utils::validate_name("version", version)?;
+        }
+        versions
+            .into_iter()
+            .map(utils::parse_version_arg)
+            .collect::<Res<_>>()?
+    };
+
+    if versions.is_empty() {
+        success!("Nothing to remove.");
+        return Ok(());
+    }
+
+    if !utils::confirm(&format!("Remove {}?", versions.join(", ")), yes)? {
+        info!("Nothing removed.");
+        return Ok(());
+    }
+
+    for version in versions {
+        remove_version(version, force, keep_aliases).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::{
+        alias_exists, assert_rejected, fake_alias, fake_version, lock_activation, MALICIOUS_NAMES,
+    };
+
+    #[tokio::test]
+    async fn remove_rejects_malicious_versions() {
+        for name in MALICIOUS_NAMES {
+            assert_rejected(
+                name,
+                remove(vec![name.to_string()], false, true, false, true).await,
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn remove_removes_aliases_of_aliases() {
+        let removed = fake_version("go1.89.1");
+        fake_version("go1.89.2");
+        // created in an order that differs from both name and chain order
+        fake_alias("t289-c", "t289-b", true);
+        fake_alias("t289-a", "go1.89.1", false);
+        fake_alias("t289-b", "t289-a", true);
+        fake_alias("t289-keep", "go1.89.2", false);
+
+        remove(vec!["1.89.1".to_string()], false, false, false, true)
+            .await
+            .unwrap();
+
+        assert!(!removed.exists());
+        assert!(!alias_exists("t289-a"));
+        assert!(!alias_exists("t289-b"));
+        assert!(!alias_exists("t289-c"));
+        assert!(alias_exists("t289-keep"));
+        assert_eq!(
+            utils::get_alias_target("t289-keep").await.as_deref(),
+            Some("go1.89.2")
+        );
+    }
+
+    #[tokio::test]
+    async fn remove_removes_aliases_pointing_to_default() {
+        let _lock = lock_activation().await;
+        fake_version("go1.89.3");
+        fake_alias("default", "go1.89.3", false);
+        fake_alias("t289-work", "default", true);
+
+        remove(vec!["go1.89.3".to_string()], false, false, false, true)
+            .await
+            .unwrap();
+
+        assert!(!alias_exists("default"));
+        assert!(!alias_exists("t289-work"));
+    }
+
+    #[tokio::test]
+    async fn remove_refuses_the_active_version_without_force() {
+        let _lock = lock_activation().await;
+        let version = fake_version("go1.73.1");
+        utils::write_file_atomic(utils::get_version_file_path().join("active"), "go1.73.1")
+            .await
+            .unwrap();
+
+        let err = remove(vec!["go1.73.1".to_string()], false, false, false, true)
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("currently active"), "{}", err);
+        assert!(version.exists());
+        assert_eq!(utils::read_active_file().await.as_deref(), Some("go1.73.1"));
+        fs::remove_file(utils::get_version_file_path().join("active")).unwrap();
+    }
+
+    #[tokio::test]
+    async fn remove_force_clears_the_activation_of_the_version() {
+        let _lock = lock_activation().await;
+        let version = fake_version("go1.73.2");
+        fake_version("go1.73.3");
+        let version_dir = utils::get_version_file_path();
+        let go_env = utils::get_environment_file_path().join("go.env");
+        utils::write_file_atomic(version_dir.join("active"), "go1.73.2")
+            .await
+            .unwrap();
+        utils::write_file_atomic(version_dir.join("previous"), "go1.73.3")
+            .await
+            .unwrap();
+        utils::write_file_atomic(&go_env, "GOROOT=/nonexistent/go1.73.2\n")
+            .await
+            .unwrap();
+        fake_alias("default", "go1.73.2", false);
+
+        remove(vec!["go1.73.2".to_string()], false, true, false, true)
+            .await
+            .unwrap();
+
+        assert!(!version.exists());
+        assert_eq!(utils::read_active_file().await, None);
+        assert!(!go_env.exists());
+        assert!(!alias_exists("default"));
+        // another version recorded as previous stays available for `gvm use -`
+        assert_eq!(
+            utils::read_previous_file().await.as_deref(),
+            Some("go1.73.3")
+        );
+
+        // a previous marker naming the removed version is dropped
+        fake_version("go1.73.4");
+        utils::write_file_atomic(version_dir.join("active"), "go1.73.4")
+            .await
+            .unwrap();
+        utils::write_file_atomic(version_dir.join("previous"), "go1.73.4")
+            .await
+            .unwrap();
+        remove(vec!["go1.73.4".to_string()], false, true, false, true)
+            .await
+            .unwrap();
+        assert_eq!(utils::read_previous_file().await, None);
+        let _ = fs::remove_file(version_dir.join("previous"));
+    }
+
+    #[tokio::test]
+    async fn removing_the_previous_version_forgets_it() {
+        let _lock = lock_activation().await;
+        let version = fake_version("go1.73.5");
+        let previous = utils::get_version_file_path().join("previous");
+        utils::write_file_atomic(&previous, "go1.73.5")
+            .await
+            .unwrap();
+
+        remove(vec!["go1.73.5".to_string()], false, false, false, true)
+            .await
+            .unwrap();
+
+        assert!(!version.exists());
+        assert!(!previous.exists());
+    }
+
+    #[tokio::test]
+    async fn remove_keeps_aliases_when_asked() {
+        fake_version("go1.89.4");
+        fake_alias("t289-kept", "go1.89.4", false);
+
+        remove(vec!["go1.89.4".to_string()], false, false, true, true)
+            .await
+            .unwrap();
+
+        assert!(alias_exists("t289-kept"));
+        assert!(utils::resolve_alias("t289-kept").await.is_err());
+    }
+}
@@ -2,21 +2,24 @@ use crate::{error, info, success, utils, Res};
 
 /// Removes a specified alias from the system.
 ///
-/// This function attempts to remove the given alias, but will not remove the 'default' alias.
-/// If the alias doesn't exist, it will inform the user and return without error.
+/// This function attempts to remove the given alias, but will not remove the 'default' alias
+/// or an alias other aliases point to. If the alias doesn't exist, it will inform the user
+/// and return without error.
 ///
 /// # Arguments
 ///
 /// * `alias` - A String representing the name of the alias to be removed.
+/// * `yes` - Whether the alias is removed without asking for confirmation.
 ///
 /// # Returns
 ///
 /// * `Res<()>` - A Result type. Returns Ok(()) if the operation is successful,
-///               or an error if there's a problem during the removal process.
-pub async fn remove_alias(alias: String) -> Res<()> {
+///   or an error if there's a problem during the removal process.
+pub async fn remove_alias(alias: String, yes: bool) -> Res<()> {
     if alias == "default" {
         error!("Removing 'default' as alias is not allowed. Please choose a different alias.");
     }
+    utils::validate_alias_name(&alias)?;
 
     let available_aliases = utils::list_aliases().await?;
     if !available_aliases.contains(&alias) {
@@ -24,6 +27,26 @@ pub async fn remove_alias(alias: String) -> Res<()> {
         return Ok(());
     }
 
+    let mut dependents = Vec::new();
+    for other in &available_aliases {
+        if utils::get_parent_alias(other).await.as_deref() == Some(alias.as_str()) {
+            dependents.push(other.clone());
+        }
+    }
+    if !dependents.is_empty() {
+        dependents.sort();
+        error!(
+            "Alias {} is used by alias(es) {}. Remove or repoint them first.",
+            alias,
+            dependents.join(", ")
+        );
+    }
+
+    if !utils::confirm(&format!("Remove alias {}?", alias), yes)? {
+        info!("Alias {} kept.", alias);
+        return Ok(());
+    }
+
     info!("Removing alias {}...", alias);
     let alias_dir = utils::get_alias_file_path();
     let alias_path = alias_dir.join(&alias);
@@ -33,3 +56,26 @@ pub async fn remove_alias(alias: String) -> Res<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::{assert_rejected, MALICIOUS_NAMES};
+
+    #[tokio::test]
+    async fn reserved_names_cannot_be_removed() {
+        for name in crate::config::RESERVED_ALIAS_NAMES {
+            let err = remove_alias(name.to_string(), true).await.unwrap_err();
+            if *name != "default" {
+                assert!(err.to_string().contains("Reserved names are"), "{}", err);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn remove_alias_rejects_malicious_names() {
+        for name in MALICIOUS_NAMES {
+            assert_rejected(name, remove_alias(name.to_string(), true).await);
+        }
+    }
+}
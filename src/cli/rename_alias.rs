@@ -0,0 +1,155 @@
+use crate::{error, info, success, utils, Res};
+
+/// Renames an alias, keeping its target.
+///
+/// The symlink itself is renamed, so the alias is never missing in between
+/// and an existing alias with the new name is replaced in the same step.
+/// Aliases pointing to the renamed alias are repointed to its new name.
+///
+/// # Arguments
+///
+/// * `old` - The name of the alias to rename.
+/// * `new` - The new name of the alias.
+/// * `force` - Whether an existing alias named `new` is replaced.
+///
+/// # Returns
+///
+/// * `Res<()>` - `Ok(())` if the alias was renamed, or an error if either name
+///   is `default` or reserved, `old` does not exist, or `new` exists without
+///   `force`.
+pub async fn rename_alias(old: String, new: String, force: bool) -> Res<()> {
+    if old == "default" || new == "default" {
+        error!("Renaming to or from the 'default' alias is not allowed. Use 'gvm use' instead.");
+    }
+    utils::validate_alias_name(&old)?;
+    utils::validate_alias_name(&new)?;
+    if old == new {
+        error!("Alias {} already has that name.", old);
+    }
+
+    let alias_dir = utils::get_alias_file_path();
+    let old_path = alias_dir.join(&old);
+    let new_path = alias_dir.join(&new);
+
+    let target = match async_fs::read_link(&old_path).await {
+        Ok(target) => target,
+        Err(_) => error!("Alias {} does not exist.", old),
+    };
+    // with --force, `new` is replaced, so `old` must not reach it through
+    // other aliases, or the renamed alias would point to itself
+    let mut parent = utils::get_parent_alias(&old).await;
+    let mut visited = vec![old.clone()];
+    while let Some(alias) = parent {
+        if alias == new {
+            error!(
+                "Alias {} points to alias {}; renaming it would make it point to itself.",
+                old, new
+            );
+        }
+        if visited.contains(&alias) {
+            break;
+        }
+        parent = utils::get_parent_alias(&alias).await;
+        visited.push(alias);
+    }
+    if async_fs::symlink_metadata(&new_path).await.is_ok() && !force {
+        error!(
+            "Alias {} already exists. Please choose a different name or pass --force to replace it.",
+            new
+        );
+    }
+
+    let mut dependents = Vec::new();
+    for other in utils::list_aliases().await? {
+        if other != new && utils::get_parent_alias(&other).await.as_deref() == Some(old.as_str()) {
+            dependents.push(other);
+        }
+    }
+
+    info!("Renaming alias {} to {}...", old, new);
+    if let Err(e) = async_fs::rename(&old_path, &new_path).await {
+        error!("Failed to rename alias {} to {}: {}", old, new, e);
+    }
+    for dependent in dependents {
+        info!("Repointing alias {} to {}...", dependent, new);
+        utils::replace_symlink(&new_path, alias_dir.join(&dependent)).await?;
+    }
+
+    success!("Alias {} renamed to {} ({}).", old, new, target.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::{
+        alias_exists, assert_rejected, fake_alias, fake_version, MALICIOUS_NAMES,
+    };
+
+    #[tokio::test]
+    async fn renaming_keeps_the_target_and_repoints_dependents() {
+        let version = fake_version("go1.32.1");
+        fake_alias("t328-old", "go1.32.1", false);
+        fake_alias("t328-dependent", "t328-old", true);
+
+        rename_alias("t328-old".to_string(), "t328-new".to_string(), false)
+            .await
+            .unwrap();
+
+        assert!(!alias_exists("t328-old"));
+        let alias_dir = utils::get_alias_file_path();
+        assert_eq!(
+            async_fs::read_link(alias_dir.join("t328-new"))
+                .await
+                .unwrap(),
+            version
+        );
+        assert_eq!(
+            utils::get_parent_alias("t328-dependent").await.as_deref(),
+            Some("t328-new")
+        );
+    }
+
+    #[tokio::test]
+    async fn renaming_onto_an_existing_alias_requires_force() {
+        fake_version("go1.32.2");
+        fake_version("go1.32.3");
+        fake_alias("t328-from", "go1.32.2", false);
+        fake_alias("t328-taken", "go1.32.3", false);
+
+        let err = rename_alias("t328-from".to_string(), "t328-taken".to_string(), false)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("already exists"), "{}", err);
+        assert!(alias_exists("t328-from"));
+
+        rename_alias("t328-from".to_string(), "t328-taken".to_string(), true)
+            .await
+            .unwrap();
+        assert!(!alias_exists("t328-from"));
+        assert_eq!(
+            utils::get_alias_target("t328-taken").await.as_deref(),
+            Some("go1.32.2")
+        );
+        for (old, new) in [("default", "t328-taken"), ("t328-taken", "default")] {
+            let err = rename_alias(old.to_string(), new.to_string(), true)
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("'default'"), "{}", err);
+        }
+    }
+
+    #[tokio::test]
+    async fn rename_alias_rejects_malicious_names() {
+        for name in MALICIOUS_NAMES {
+            assert_rejected(
+                name,
+                rename_alias(name.to_string(), "stable".to_string(), true).await,
+            );
+            assert_rejected(
+                name,
+                rename_alias("stable".to_string(), name.to_string(), true).await,
+            );
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::path::PathBuf;
+
+use super::{
+    alias::{alias, AliasOptions},
+    freeze::Manifest,
+    install::{install, InstallOptions},
+    use_version::use_version,
+};
+use crate::{error, info, success, utils, warning, Res};
+
+/// Returns the aliases of a manifest in the order they can be created in.
+///
+/// An alias of an alias comes after the alias it points to, so chains are
+/// recreated from their start.
+///
+/// # Parameters
+///
+/// * `manifest`: The manifest to order the aliases of.
+///
+/// # Returns
+///
+/// The alias names, ordered by the length of their chain within the manifest
+/// and then by name.
+fn alias_creation_order(manifest: &Manifest) -> Vec<&String> {
+    let depth = |alias: &String| {
+        let mut depth = 0;
+        let mut current = alias;
+        while let Some(parent) = manifest.alias_parents.get(current) {
+            // a cycle cannot be frozen, but a hand-edited manifest may have one
+            if !manifest.aliases.contains_key(parent) || depth > manifest.aliases.len() {
+                break;
+            }
+            depth += 1;
+            current = parent;
+        }
+        depth
+    };
+    let mut order: Vec<&String> = manifest.aliases.keys().collect();
+    order.sort_by_key(|alias| depth(alias));
+    order
+}
+
+/// Restores a toolchain set from a manifest written by `gvm freeze`.
+///
+/// This function performs the following steps:
+/// 1. Installs every version from the manifest that is not installed yet.
+/// 2. Activates the version recorded as active, which also sets `default`.
+/// 3. Recreates aliases that don't exist yet. An alias of an alias points to
+///    the same alias again if that resolves, otherwise to its version.
+///
+/// # Parameters
+///
+/// * `file`: The path of the manifest file to read.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` if the toolchain set was restored, or
+/// an error if the manifest cannot be read or an installation fails.
+pub async fn restore(file: PathBuf) -> Res<()> {
+    let data = match async_fs::read_to_string(&file).await {
+        Ok(data) => data,
+        Err(e) => error!("Error reading manifest {}: {}", file.display(), e),
+    };
+    let manifest: Manifest = serde_json::from_str(&data)?;
+
+    let installed_versions = utils::list_installed_versions().await?;
+    let mut installed = Vec::new();
+    let mut present = Vec::new();
+    for version in &manifest.versions {
+        if installed_versions.contains(version) {
+            present.push(version.clone());
+        } else {
+            install(version.clone(), InstallOptions::default()).await?;
+            installed.push(version.clone());
+        }
+    }
+
+    if let Some(ref active) = manifest.active {
+        use_version(Some(active.clone()), false, None, false, false).await?;
+    }
+
+    let existing_aliases = utils::list_aliases().await?;
+    for alias_name in alias_creation_order(&manifest) {
+        if existing_aliases.contains(alias_name) {
+            info!("Alias {} already exists. Skipping.", alias_name);
+            continue;
+        }
+        let target = &manifest.aliases[alias_name];
+        let (target, options) = match manifest.alias_parents.get(alias_name) {
+            Some(parent) if utils::resolve_alias(parent).await.is_ok() => (
+                None,
+                AliasOptions {
+                    to_alias: Some(parent.clone()),
+                    ..AliasOptions::default()
+                },
+            ),
+            Some(parent) => {
+                warning!(
+                    "Alias {} pointed to alias {}, which does not resolve. Pointing it to version {} instead.",
+                    alias_name,
+                    parent,
+                    target
+                );
+                (Some(target.clone()), AliasOptions::default())
+            }
+            None => (Some(target.clone()), AliasOptions::default()),
+        };
+        alias(alias_name.clone(), target, options).await?;
+    }
+
+    success!(
+        "Restore complete: {} installed, {} already present.",
+        installed.len(),
+        present.len()
+    );
+    for version in installed {
+        success!("installed: {}", version);
+    }
+    for version in present {
+        info!("already present: {}", version);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::fake_version;
+    use std::collections::BTreeMap;
+
+    fn map(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn aliases_are_created_after_the_aliases_they_point_to() {
+        let manifest = Manifest {
+            aliases: map(&[
+                ("a-grandchild", "go1.85.2"),
+                ("b-child", "go1.85.2"),
+                ("c-base", "go1.85.2"),
+                ("d-work", "go1.85.2"),
+            ]),
+            alias_parents: map(&[
+                ("a-grandchild", "b-child"),
+                ("b-child", "c-base"),
+                ("d-work", "default"),
+            ]),
+            ..Manifest::default()
+        };
+
+        assert_eq!(
+            alias_creation_order(&manifest),
+            ["c-base", "d-work", "b-child", "a-grandchild"]
+        );
+    }
+
+    #[tokio::test]
+    async fn restore_recreates_aliases_of_aliases() {
+        fake_version("go1.85.3");
+        let manifest = Manifest {
+            active: None,
+            versions: vec!["go1.85.3".to_string()],
+            aliases: map(&[
+                ("t285-r-base", "go1.85.3"),
+                ("t285-r-child", "go1.85.3"),
+                ("t285-r-grand", "go1.85.3"),
+                ("t285-r-orphan", "go1.85.3"),
+            ]),
+            alias_parents: map(&[
+                ("t285-r-child", "t285-r-base"),
+                ("t285-r-grand", "t285-r-child"),
+                ("t285-r-orphan", "t285-r-missing"),
+            ]),
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("gvm.json");
+        std::fs::write(&file, serde_json::to_string(&manifest).unwrap()).unwrap();
+
+        restore(file).await.unwrap();
+
+        assert_eq!(utils::get_parent_alias("t285-r-base").await, None);
+        assert_eq!(
+            utils::get_parent_alias("t285-r-child").await.as_deref(),
+            Some("t285-r-base")
+        );
+        assert_eq!(
+            utils::get_parent_alias("t285-r-grand").await.as_deref(),
+            Some("t285-r-child")
+        );
+        assert_eq!(utils::get_parent_alias("t285-r-orphan").await, None);
+        assert_eq!(
+            utils::get_alias_target("t285-r-orphan").await.as_deref(),
+            Some("go1.85.3")
+        );
+    }
+}
@@ -0,0 +1,346 @@
+use std::{
+    env, fs,
+    io::{self, Read},
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    process,
+};
+
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tar::Archive;
+
+use crate::{config, error, info, success, utils, warning, Res};
+
+/// A GitHub release as returned by the releases API.
+#[derive(Deserialize, Debug)]
+struct GithubRelease {
+    tag_name: String,
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Returns the URL of the latest gvm release, `GVM_SELF_UPDATE_URL` if set.
+fn get_latest_release_url() -> String {
+    env::var("GVM_SELF_UPDATE_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+        .unwrap_or_else(|| {
+            format!(
+                "https://api.github.com/repos/{}/releases/latest",
+                config::SELF_UPDATE_REPO
+            )
+        })
+}
+
+/// Splits a gvm version like "2025.12.11" or "v2025.12.11" into its numbers.
+fn parse_gvm_version(version: &str) -> Vec<u32> {
+    version
+        .trim_start_matches('v')
+        .split('.')
+        .map_while(|part| part.parse().ok())
+        .collect()
+}
+
+/// Returns the names release assets use for an architecture.
+///
+/// # Parameters
+///
+/// * `arch`: The Rust architecture name (e.g. "x86_64").
+///
+/// # Returns
+///
+/// The Rust name, the Go name and other common spellings of the architecture.
+fn arch_asset_names(arch: &str) -> Vec<&str> {
+    let names: &[&str] = match arch {
+        "x86_64" => &["x86_64", "amd64", "x64"],
+        "x86" => &["x86", "i386", "i686", "386"],
+        "aarch64" => &["aarch64", "arm64"],
+        "arm" => &["arm", "armv6l", "armv7", "armv7l", "armhf"],
+        _ => &[],
+    };
+    let mut names = names.to_vec();
+    for name in [arch, utils::go_arch_name(arch)] {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Picks the release asset built for the host.
+///
+/// See `find_binary_asset_for`.
+fn find_binary_asset(assets: &[GithubAsset]) -> Option<&GithubAsset> {
+    find_binary_asset_for(assets, env::consts::OS, env::consts::ARCH)
+}
+
+/// Picks the release asset built for a platform.
+///
+/// Asset names are split at `-` and `.` into tokens, and an asset matches if
+/// one token is the OS as named by Rust (e.g. "macos") or Go (e.g. "darwin"),
+/// and one token is a name of the architecture (see `arch_asset_names`).
+/// Whole tokens are compared, so "x86" doesn't match "x86_64" and "arm"
+/// doesn't match "arm64". Checksum files are skipped.
+///
+/// # Parameters
+///
+/// * `assets`: The assets of the release.
+/// * `os`: The Rust OS name.
+/// * `arch`: The Rust architecture name.
+///
+/// # Returns
+///
+/// The first matching asset, or `None` if no asset is built for the platform.
+fn find_binary_asset_for<'a>(
+    assets: &'a [GithubAsset],
+    os: &str,
+    arch: &str,
+) -> Option<&'a GithubAsset> {
+    let oses = [os, utils::go_os_name(os)];
+    let arches = arch_asset_names(arch);
+    assets.iter().find(|asset| {
+        let name = asset.name.to_lowercase();
+        let tokens: Vec<&str> = name.split(['-', '.']).collect();
+        tokens.iter().any(|token| oses.contains(token))
+            && tokens.iter().any(|token| arches.contains(token))
+            && !name.ends_with(".sha256")
+    })
+}
+
+/// Downloads a release asset into memory.
+async fn fetch_asset(url: &str) -> Res<Vec<u8>> {
+    let client = utils::http_client()?;
+    utils::with_retries("Downloading gvm", move || async move {
+        let rsp = client.get(url).send().await?.error_for_status()?;
+        Ok(rsp.bytes().await?.to_vec())
+    })
+    .await
+}
+
+/// Returns the `gvm` binary from a downloaded asset.
+///
+/// Assets are either the bare binary or a `.tar.gz` archive containing a file
+/// named `gvm`.
+fn extract_binary(asset_name: &str, data: Vec<u8>) -> Res<Vec<u8>> {
+    if !asset_name.ends_with(".tar.gz") && !asset_name.ends_with(".tgz") {
+        return Ok(data);
+    }
+    let mut archive = Archive::new(GzDecoder::new(data.as_slice()));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.path()?.file_name().is_some_and(|name| name == "gvm") {
+            let mut binary = Vec::new();
+            entry.read_to_end(&mut binary)?;
+            return Ok(binary);
+        }
+    }
+    error!("Archive {} does not contain a gvm binary.", asset_name);
+}
+
+/// Writes the new binary next to the running one and renames it over it.
+///
+/// The rename is atomic because both files are in the same directory, so the
+/// executable is never left half-written.
+fn replace_executable(executable: &Path, binary: &[u8]) -> io::Result<()> {
+    let staged = executable.with_file_name(format!(".gvm-update-{}", process::id()));
+    let result = fs::write(&staged, binary)
+        .and_then(|_| fs::set_permissions(&staged, fs::Permissions::from_mode(0o755)))
+        .and_then(|_| fs::rename(&staged, executable));
+    if result.is_err() {
+        let _ = fs::remove_file(&staged);
+    }
+    result
+}
+
+/// Updates gvm itself to the newest release.
+///
+/// This function performs the following steps:
+/// 1. Fetches the latest release of the gvm repository from GitHub (or
+///    `GVM_SELF_UPDATE_URL`) and compares its tag with the running version.
+/// 2. Downloads the binary for the host OS and architecture and the `.sha256`
+///    file published next to it.
+/// 3. Verifies the checksum and atomically replaces the running executable.
+///
+/// Not to be confused with `gvm update`, which refreshes the Go release cache.
+///
+/// # Parameters
+///
+/// * `check`: When `true`, only report whether a newer release exists.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` if gvm is up to date or was updated,
+/// or an error if the release cannot be fetched, has no binary or checksum
+/// for the host, fails verification, or the executable cannot be replaced.
+pub async fn self_update(check: bool) -> Res<()> {
+    let current_version = env!("CARGO_PKG_VERSION");
+    let url = get_latest_release_url();
+
+    info!("Checking for a newer gvm release ...");
+    let client = utils::http_client()?;
+    let url = &url;
+    let release: GithubRelease = utils::with_retries("Fetching gvm releases", move || async move {
+        let rsp = client.get(url).send().await?.error_for_status()?;
+        Ok(rsp.json().await?)
+    })
+    .await?;
+
+    let latest_version = release.tag_name.trim_start_matches('v');
+    if parse_gvm_version(latest_version) <= parse_gvm_version(current_version) {
+        success!("gvm {} is up to date.", current_version);
+        return Ok(());
+    }
+    if check {
+        info!(
+            "gvm {} is available (currently {}). Run 'gvm self-update' to install it.",
+            latest_version, current_version
+        );
+        return Ok(());
+    }
+
+    let asset = match find_binary_asset(&release.assets) {
+        Some(asset) => asset,
+        None => error!(
+            "Release {} has no binary for {}-{}.",
+            release.tag_name,
+            env::consts::OS,
+            env::consts::ARCH
+        ),
+    };
+    let checksum_name = format!("{}.sha256", asset.name);
+    let checksum_asset = match release.assets.iter().find(|a| a.name == checksum_name) {
+        Some(checksum_asset) => checksum_asset,
+        None => error!(
+            "Release {} has no checksum file {}. Refusing to install an unverified binary.",
+            release.tag_name, checksum_name
+        ),
+    };
+
+    info!("Downloading {} ...", asset.browser_download_url);
+    let data = fetch_asset(&asset.browser_download_url).await?;
+    let checksum_file = fetch_asset(&checksum_asset.browser_download_url).await?;
+    let expected = String::from_utf8_lossy(&checksum_file)
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    if !utils::is_valid_sha256(&expected) {
+        error!("{} does not contain a SHA-256 digest.", checksum_name);
+    }
+    let actual: String = Sha256::digest(&data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    if actual != expected {
+        error!(
+            "Checksum mismatch for {}: expected {}, got {}.",
+            asset.name, expected, actual
+        );
+    }
+    success!("Checksum verified.");
+
+    let binary = extract_binary(&asset.name, data)?;
+    let executable = env::current_exe()?;
+    info!("Replacing {} ...", executable.display());
+    if let Err(e) = replace_executable(&executable, &binary) {
+        warning!("Make sure you can write to {}.", executable.display());
+        error!("Failed to replace {}: {}", executable.display(), e);
+    }
+
+    success!(
+        "Updated gvm from {} to {}.",
+        current_version,
+        latest_version
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assets(names: &[&str]) -> Vec<GithubAsset> {
+        names
+            .iter()
+            .map(|name| GithubAsset {
+                name: name.to_string(),
+                browser_download_url: format!("https://example.com/{}", name),
+            })
+            .collect()
+    }
+
+    fn found(assets: &[GithubAsset], os: &str, arch: &str) -> Option<String> {
+        find_binary_asset_for(assets, os, arch).map(|asset| asset.name.clone())
+    }
+
+    #[test]
+    fn find_binary_asset_matches_whole_tokens() {
+        let assets = assets(&[
+            "gvm-2025.12.11-linux-x86_64.tar.gz.sha256",
+            "gvm-2025.12.11-linux-x86_64.tar.gz",
+            "gvm-2025.12.11-linux-arm64.tar.gz",
+            "gvm-2025.12.11-linux-armv6l.tar.gz",
+            "gvm-2025.12.11-darwin-arm64.tar.gz",
+        ]);
+
+        assert_eq!(
+            found(&assets, "linux", "x86_64").as_deref(),
+            Some("gvm-2025.12.11-linux-x86_64.tar.gz")
+        );
+        assert_eq!(
+            found(&assets, "linux", "aarch64").as_deref(),
+            Some("gvm-2025.12.11-linux-arm64.tar.gz")
+        );
+        assert_eq!(
+            found(&assets, "linux", "arm").as_deref(),
+            Some("gvm-2025.12.11-linux-armv6l.tar.gz")
+        );
+        assert_eq!(
+            found(&assets, "macos", "aarch64").as_deref(),
+            Some("gvm-2025.12.11-darwin-arm64.tar.gz")
+        );
+        // x86 must not pick the x86_64 build
+        assert_eq!(found(&assets, "linux", "x86"), None);
+        assert_eq!(found(&assets, "macos", "x86_64"), None);
+    }
+
+    #[test]
+    fn find_binary_asset_does_not_match_arm_in_arm64() {
+        let assets = assets(&["gvm-linux-arm64", "gvm-linux-aarch64.tar.gz"]);
+
+        assert_eq!(found(&assets, "linux", "arm"), None);
+        assert_eq!(
+            found(&assets, "linux", "aarch64").as_deref(),
+            Some("gvm-linux-arm64")
+        );
+    }
+
+    #[test]
+    fn find_binary_asset_understands_target_triples() {
+        let assets = assets(&[
+            "gvm-x86_64-unknown-linux-gnu.tar.gz",
+            "gvm-i686-unknown-linux-gnu.tar.gz",
+            "gvm-aarch64-apple-darwin.tar.gz",
+        ]);
+
+        assert_eq!(
+            found(&assets, "linux", "x86").as_deref(),
+            Some("gvm-i686-unknown-linux-gnu.tar.gz")
+        );
+        assert_eq!(
+            found(&assets, "linux", "x86_64").as_deref(),
+            Some("gvm-x86_64-unknown-linux-gnu.tar.gz")
+        );
+        assert_eq!(
+            found(&assets, "macos", "aarch64").as_deref(),
+            Some("gvm-aarch64-apple-darwin.tar.gz")
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use std::{env, fs, os::unix::fs::PermissionsExt, path::Path};
+
+use crate::{error, info, success, utils, Res};
+
+/// Actions of `gvm shim`.
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum ShimAction {
+    #[clap(about = "Write the go shim to ~/.gvm/shims")]
+    Install,
+
+    #[clap(about = "Remove the go shim")]
+    Uninstall,
+}
+
+/// Renders the `go` shim.
+///
+/// The shim looks for the nearest `.go-version` file from the working
+/// directory up to the filesystem root, falls back to `gvm current`, and runs
+/// `go` under that version with `gvm exec`. `gvm exec` puts GOROOT/bin in front
+/// of PATH, so the real `go` is found instead of the shim again. If the
+/// toolchain has no `go` binary, `GVM_SHIM_VERSION` stops the shim from calling
+/// itself forever.
+///
+/// # Parameters
+///
+/// * `gvm`: The path of the gvm executable, so the shim works even if gvm is
+///   not on PATH.
+///
+/// # Returns
+///
+/// The shim as a POSIX shell script.
+fn render_go_shim(gvm: &Path) -> String {
+    format!(
+        r#"#!/bin/sh
+# Generated by 'gvm shim install'. Runs go with the version pinned by the
+# nearest .go-version file, or with the active version.
+gvm={}
+if [ -n "$GVM_SHIM_VERSION" ]; then
+        echo "gvm: $GVM_SHIM_VERSION has no go binary, reinstall it with 'gvm install --force $GVM_SHIM_VERSION'" >&2
+        exit 127
+fi
+version=
+dir=$PWD
+while :; do
+        if [ -f "$dir/.go-version" ]; then
+                version=$(tr -d '[:space:]' < "$dir/.go-version")
+                break
+        fi
+        [ "$dir" = / ] && break
+        dir=$(dirname "$dir")
+done
+[ -n "$version" ] || version=$("$gvm" current) || exit 1
+GVM_SHIM_VERSION=$version exec "$gvm" exec "$version" -- go "$@"
+"#,
+        utils::shell_quote(&gvm.to_string_lossy())
+    )
+}
+
+/// Installs or removes the `go` shim.
+///
+/// With the shim directory in front of PATH, every `go` invocation dispatches
+/// to the version pinned for the current directory, without `gvm use` or the
+/// shell hook. The init script written by `gvm init` adds the shim directory
+/// to PATH once it exists, after GOROOT/bin and GOPATH/bin, so it is never
+/// added twice.
+///
+/// # Parameters
+///
+/// * `action`: Whether the shim is installed or removed.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` on success, or an error if the shim
+/// cannot be written or removed.
+pub async fn shim(action: ShimAction) -> Res<()> {
+    let shims_dir = utils::get_shims_path();
+    let go_shim = shims_dir.join("go");
+
+    match action {
+        ShimAction::Install => {
+            let gvm = env::current_exe()?;
+            info!("Writing {} ...", go_shim.display());
+            let result = fs::create_dir_all(&shims_dir)
+                .and_then(|_| fs::write(&go_shim, render_go_shim(&gvm)))
+                .and_then(|_| fs::set_permissions(&go_shim, fs::Permissions::from_mode(0o755)));
+            if let Err(e) = result {
+                error!("Failed to write {}: {}", go_shim.display(), e);
+            }
+            success!("Shim {} installed.", go_shim.display());
+
+            let on_path = env::var_os("PATH")
+                .is_some_and(|path| env::split_paths(&path).any(|dir| dir == shims_dir));
+            if !on_path {
+                info!(
+                    "Reload your profile to put {} in front of PATH. Run 'gvm init' first if it was set up by an older gvm.",
+                    shims_dir.display()
+                );
+            }
+        }
+        ShimAction::Uninstall => {
+            if !shims_dir.exists() {
+                success!("No shim installed.");
+                return Ok(());
+            }
+            if let Err(e) = fs::remove_dir_all(&shims_dir) {
+                error!("Failed to remove {}: {}", shims_dir.display(), e);
+            }
+            success!("Shims removed from {}.", shims_dir.display());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    /// Runs the rendered shim in `dir` with a fake gvm that prints its arguments.
+    fn run_shim(dir: &Path, shim_env: Option<&str>) -> std::process::Output {
+        let bin = tempfile::Builder::new()
+            .prefix("gvm bin ")
+            .tempdir()
+            .unwrap();
+        let gvm = bin.path().join("gvm");
+        fs::write(
+            &gvm,
+            "#!/bin/sh\n[ \"$1\" = current ] && echo go1.21.0 && exit 0\necho \"$GVM_SHIM_VERSION\"\nprintf '%s\\n' \"$@\"\n",
+        )
+        .unwrap();
+        fs::set_permissions(&gvm, fs::Permissions::from_mode(0o755)).unwrap();
+        let shim = bin.path().join("go");
+        fs::write(&shim, render_go_shim(&gvm)).unwrap();
+
+        let mut command = Command::new("sh");
+        command.arg(&shim).args(["build", "./..."]).current_dir(dir);
+        command.env_remove("GVM_SHIM_VERSION");
+        if let Some(version) = shim_env {
+            command.env("GVM_SHIM_VERSION", version);
+        }
+        command.output().unwrap()
+    }
+
+    #[test]
+    fn the_shim_runs_the_pinned_version() {
+        let project = tempfile::tempdir().unwrap();
+        fs::write(project.path().join(".go-version"), "1.22.3\n").unwrap();
+        let nested = project.path().join("cmd").join("tool");
+        fs::create_dir_all(&nested).unwrap();
+
+        let output = run_shim(&nested, None);
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "1.22.3\nexec\n1.22.3\n--\ngo\nbuild\n./...\n"
+        );
+    }
+
+    #[test]
+    fn the_shim_falls_back_to_the_current_version() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let output = run_shim(dir.path(), None);
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "go1.21.0\nexec\ngo1.21.0\n--\ngo\nbuild\n./...\n"
+        );
+    }
+
+    #[test]
+    fn the_shim_does_not_call_itself() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let output = run_shim(dir.path(), Some("go1.22.3"));
+
+        assert_eq!(output.status.code(), Some(127));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("go1.22.3 has no go binary"));
+    }
+}
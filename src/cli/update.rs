@@ -1,11 +1,20 @@
-use reqwest;
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{DeserializeSeed, Deserializer, SeqAccess, Visitor},
+    Deserialize, Serialize,
+};
 use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    env,
     error::Error,
-    path::{Path, PathBuf},
+    fmt,
+    io::{self, BufReader, Read},
+    path::Path,
+    time::Duration,
 };
+use tokio::sync::mpsc;
 
-use crate::{config, info, success, utils, Res};
+use crate::{config, debug, error, info, success, utils, warning, Res};
 
 #[derive(Serialize, Deserialize, Debug)]
 struct Release {
@@ -20,41 +29,296 @@ struct File {
     os: String,
     arch: String,
     kind: String,
+    #[serde(default)]
+    sha256: String,
+}
+
+/// Deserializes the release list, keeping only the files built for one platform.
+///
+/// Releases are visited one at a time, so files for other platforms, and
+/// releases without any file for the platform, are dropped as they are read
+/// instead of being collected first.
+#[derive(Clone, Copy)]
+struct PlatformReleases<'a> {
+    os: &'a str,
+    arch: &'a str,
+}
+
+impl<'de> DeserializeSeed<'de> for PlatformReleases<'_> {
+    type Value = Vec<Release>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for PlatformReleases<'_> {
+    type Value = Vec<Release>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of Go releases")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut releases = Vec::new();
+        while let Some(mut release) = seq.next_element::<Release>()? {
+            release
+                .files
+                .retain(|file| file.os == self.os && file.arch == self.arch);
+            if !release.files.is_empty() {
+                // the list had room for the files of every platform
+                release.files.shrink_to_fit();
+                releases.push(release);
+            }
+        }
+        Ok(releases)
+    }
+}
+
+/// Number of response chunks buffered between the download and the parser.
+const RELEASE_CHUNK_BUFFER: usize = 16;
+
+/// Blocking reader over response chunks received from a channel.
+///
+/// Lets `serde_json` parse the release list on a blocking thread while the
+/// body is still being downloaded, so the whole response is never held in
+/// memory at once.
+struct ChunkReader {
+    chunks: mpsc::Receiver<bytes::Bytes>,
+    current: bytes::Bytes,
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.current.is_empty() {
+            match self.chunks.blocking_recv() {
+                Some(chunk) => self.current = chunk,
+                // the sender is dropped once the body has been read
+                None => return Ok(0),
+            }
+        }
+        let len = buf.len().min(self.current.len());
+        buf[..len].copy_from_slice(&self.current.split_to(len));
+        Ok(len)
+    }
+}
+
+/// Parses a release list read from `reader`, see `PlatformReleases`.
+fn parse_releases<R: Read>(reader: R, os: &str, arch: &str) -> serde_json::Result<Vec<Release>> {
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+    let releases = PlatformReleases { os, arch }.deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(releases)
 }
 
 /// Fetches the list of Go releases from the official Go website.
 ///
-/// This asynchronous function sends a GET request to the Go downloads API,
-/// retrieves the JSON response containing information about all Go releases,
-/// and deserializes it into a vector of `Release` structs.
+/// This asynchronous function sends a GET request to the Go downloads API
+/// (or the mirror configured with `GVM_RELEASES_URL`/`GVM_DOWNLOAD_BASE`),
+/// retrieves the JSON response containing information about all Go releases
+/// (retrying transient failures, see `utils::with_retries`),
+/// and deserializes it into a vector of `Release` structs. Only files for
+/// `os` and `arch` are kept, see `PlatformReleases`. The body is parsed on a
+/// blocking thread while it is downloaded, see `ChunkReader`.
+///
+/// # Parameters
+///
+/// * `os`: The Go operating system name to keep files for.
+/// * `arch`: The Go architecture name to keep files for.
 ///
 /// # Returns
 ///
 /// Returns a `Result` which, on success, contains a `Vec<Release>` representing
-/// all available Go releases. On failure, it returns a boxed error that
-/// implements `Error + Send + Sync`.
+/// the Go releases available for the platform. On failure, it returns a boxed
+/// error that implements `Error + Send + Sync`.
 ///
 /// # Errors
 ///
 /// This function will return an error if:
-/// - The HTTP request fails
+/// - The configured mirror URL is malformed
+/// - The HTTP request fails, times out or returns an error status, after
+///   transient failures have been retried
 /// - The response cannot be deserialized into the expected format
-async fn fetch_releases() -> Result<Vec<Release>, Box<dyn Error + Send + Sync>> {
-    let url = "https://go.dev/dl/?mode=json&include=all";
-    let rsp = reqwest::get(url).await?;
-    let releases: Vec<Release> = rsp.json().await?;
-    Ok(releases)
+async fn fetch_releases(
+    os: &str,
+    arch: &str,
+) -> Result<Vec<Release>, Box<dyn Error + Send + Sync>> {
+    let url = utils::get_releases_url()?;
+    debug!("Releases URL: {}", url);
+    let client = utils::http_client()?;
+    let url = &url;
+    utils::with_retries("Fetching releases", move || async move {
+        let mut rsp = client
+            .get(url.clone())
+            .timeout(Duration::from_secs(config::RELEASES_REQUEST_TIMEOUT_SECS))
+            .send()
+            .await?
+            .error_for_status()?;
+        let (sender, chunks) = mpsc::channel(RELEASE_CHUNK_BUFFER);
+        let (os, arch) = (os.to_string(), arch.to_string());
+        let parser = tokio::task::spawn_blocking(move || {
+            let reader = ChunkReader {
+                chunks,
+                current: bytes::Bytes::new(),
+            };
+            parse_releases(reader, &os, &arch)
+        });
+
+        let download = async move {
+            let mut received = 0;
+            while let Some(chunk) = rsp.chunk().await? {
+                received += chunk.len();
+                if sender.send(chunk).await.is_err() {
+                    // the parser stopped early and reports why
+                    break;
+                }
+            }
+            Ok::<_, reqwest::Error>(received)
+        };
+        let (download, parsed) = tokio::join!(download, parser);
+        // a failed download also fails the parser, which only sees a
+        // truncated body, so its error is the one worth reporting
+        let received = download?;
+        let releases = parsed??;
+        debug!("Received {} bytes of release data.", received);
+        Ok(releases)
+    })
+    .await
+}
+
+/// Floor below which releases are dropped from the cache.
+#[derive(Debug, Clone)]
+enum Prune {
+    /// Keep only releases of the two most recent minor versions.
+    SupportedMinors,
+    /// Keep only releases whose base version is at least the given version.
+    MinVersion(String),
+}
+
+/// Drops releases below the floor described by `prune`.
+///
+/// Releases are compared on their numeric base, so pre-releases of the floor
+/// version itself (e.g. "go1.22rc1" for a floor of "go1.22") are kept.
+///
+/// # Parameters
+///
+/// * `releases`: The filtered releases.
+/// * `prune`: The floor to apply.
+///
+/// # Returns
+///
+/// The releases at or above the floor, in their original order.
+fn prune_releases(
+    releases: Vec<utils::FilteredRelease>,
+    prune: Prune,
+) -> Vec<utils::FilteredRelease> {
+    let floor = match prune {
+        Prune::MinVersion(version) => utils::parse_version_parts(&version).0,
+        Prune::SupportedMinors => {
+            let newest = releases
+                .iter()
+                .filter(|r| utils::is_stable_version(&r.version))
+                .map(|r| utils::parse_version_parts(&r.version).0)
+                .max()
+                .unwrap_or_default();
+            match newest.as_slice() {
+                [major, minor, ..] => vec![*major, minor.saturating_sub(1)],
+                _ => return releases,
+            }
+        }
+    };
+
+    releases
+        .into_iter()
+        .filter(|r| utils::parse_version_parts(&r.version).0 >= floor)
+        .collect()
+}
+
+/// Selects the `tar.gz` archive of every release built for a platform.
+///
+/// Entries are keyed on (version, arch, kind) so the result never holds
+/// duplicates.
+///
+/// # Parameters
+///
+/// * `releases`: The releases as published by go.dev.
+/// * `os`: The Go operating system name to select archives for.
+/// * `arch`: The Go architecture name to select archives for.
+/// * `download_base`: The base URL archive names are joined onto.
+///
+/// # Returns
+///
+/// One `FilteredRelease` per release that has a matching archive, or an error
+/// if an archive name does not form a valid URL.
+fn filter_releases(
+    releases: Vec<Release>,
+    os: &str,
+    arch: &str,
+    download_base: &reqwest::Url,
+) -> Res<Vec<utils::FilteredRelease>> {
+    let mut filtered_releases = Vec::new();
+    let mut seen = HashSet::new();
+
+    for release in releases {
+        for file in release.files {
+            if file.os == os && file.arch == arch && file.filename.ends_with("tar.gz") {
+                if !seen.insert((release.version.clone(), file.arch.clone(), file.kind)) {
+                    continue;
+                }
+                let url = download_base.join(&file.filename)?.to_string();
+                filtered_releases.push(utils::FilteredRelease {
+                    version: release.version.clone(),
+                    url,
+                    sha256: Some(file.sha256).filter(|c| utils::is_valid_sha256(c)),
+                    os: file.os,
+                    arch: file.arch,
+                });
+            }
+        }
+    }
+
+    Ok(filtered_releases)
+}
+
+/// Fetches the releases and selects the archives for the target platform.
+///
+/// # Returns
+///
+/// The releases built for the target os and arch, as returned by
+/// `filter_releases`, or an error if fetching or filtering fails.
+async fn fetch_target_releases() -> Res<Vec<utils::FilteredRelease>> {
+    let download_base = utils::get_download_base()?;
+
+    let target_os = utils::get_target_os();
+    let target_arch = utils::get_target_arch();
+
+    info!("Fetch releases from source ...");
+    let releases = fetch_releases(&target_os, &target_arch).await?;
+
+    info!("Filter releases for {} {} ...", target_os, target_arch);
+    filter_releases(releases, &target_os, &target_arch, &download_base)
 }
 
-/// Creates a cache file containing filtered Go releases for Linux AMD64.
+/// Returns the newest stable version among `versions`.
+fn newest_stable<'a>(versions: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    versions
+        .filter(|v| utils::is_stable_version(v))
+        .max_by(|a, b| utils::cmp_versions(a, b))
+}
+
+/// Creates a cache file containing filtered Go releases for the target platform.
 ///
-/// This asynchronous function fetches all Go releases, filters them for Linux AMD64,
-/// and writes the filtered data to a cache file in JSON format.
+/// This asynchronous function fetches all Go releases, filters them for the target
+/// os and arch (the host OS and architecture unless overridden with
+/// `--assume-os`/`--assume-arch`), and writes the filtered data to a cache file
+/// in JSON format. Entries are deduplicated on version, arch and kind, so every
+/// version appears at most once.
 ///
 /// # Parameters
 ///
 /// * `cache_file`: A path-like parameter specifying the location where the cache file
-///    should be created or updated. It can be any type that implements `AsRef<Path>`.
+///   should be created or updated. It can be any type that implements `AsRef<Path>`.
+/// * `prune`: When set, releases older than the given floor are dropped from the cache.
 ///
 /// # Returns
 ///
@@ -65,30 +329,37 @@ async fn fetch_releases() -> Result<Vec<Release>, Box<dyn Error + Send + Sync>>
 /// # Errors
 ///
 /// This function may return an error if:
+/// - The configured mirror URL is malformed
 /// - Fetching releases fails
 /// - Creating directories fails
 /// - Writing to the cache file fails
 /// - JSON serialization fails
-async fn create_release_cache<P: AsRef<Path>>(cache_file: P) -> Res<()> {
-    info!("Fetch releases from source ...");
-    let releases = fetch_releases().await?;
-    let mut filtered_releases = Vec::new();
+async fn create_release_cache<P: AsRef<Path>>(cache_file: P, prune: Option<Prune>) -> Res<()> {
+    let mut filtered_releases = fetch_target_releases().await?;
 
-    info!("Filter releases for Linux AMD64 ...");
-    for release in releases {
-        for file in release.files {
-            if file.os == "linux" && file.arch == "amd64" && file.filename.ends_with("tar.gz") {
-                let url = format!("https://go.dev/dl/{}", file.filename);
-                filtered_releases.push(utils::FilteredRelease {
-                    version: release.version.clone(),
-                    url,
-                });
-            }
-        }
+    if let Some(prune) = prune {
+        let total = filtered_releases.len();
+        filtered_releases = prune_releases(filtered_releases, prune);
+        info!(
+            "Pruned {} releases below the minimum version.",
+            total - filtered_releases.len()
+        );
     }
 
+    write_release_cache(cache_file, &filtered_releases).await?;
+    success!("Cached {} releases.", filtered_releases.len());
+    Ok(())
+}
+
+/// Writes `releases` to the release cache file, creating its directory if needed.
+///
+/// This is the format `install`, `list-remote` and the other cache readers parse.
+pub(super) async fn write_release_cache<P: AsRef<Path>>(
+    cache_file: P,
+    releases: &[utils::FilteredRelease],
+) -> Res<()> {
     // Serialize the filtered data.
-    let data = serde_json::to_string_pretty(&filtered_releases)?;
+    let data = serde_json::to_string_pretty(releases)?;
 
     // Ensure that the parent directories exist.
     info!("Ensure cache directory exists ...");
@@ -98,7 +369,6 @@ async fn create_release_cache<P: AsRef<Path>>(cache_file: P) -> Res<()> {
 
     // Write the filtered data to the cache file.
     async_fs::write(&cache_file, &data).await?;
-    success!("Cached {} releases.", filtered_releases.len());
     Ok(())
 }
 
@@ -108,6 +378,12 @@ async fn create_release_cache<P: AsRef<Path>>(cache_file: P) -> Res<()> {
 /// for the releases cache file, and then calls `create_release_cache` to fetch
 /// and store the latest Go release information.
 ///
+/// # Parameters
+///
+/// * `prune_cache`: When `true`, only the two most recent minor versions are kept
+///   in the cache. `GVM_MIN_VERSION` sets an explicit floor and implies pruning.
+/// * `include_all`: When `true`, no release is pruned regardless of the other settings.
+///
 /// # Returns
 ///
 /// Returns a `Res<()>`, which is likely an alias for `Result<(), CustomErrorType>`.
@@ -119,9 +395,509 @@ async fn create_release_cache<P: AsRef<Path>>(cache_file: P) -> Res<()> {
 /// This function may return an error if:
 /// - Retrieving the cache directory fails
 /// - Creating the release cache fails
-pub async fn update() -> Res<()> {
-    let mut cache_dir: PathBuf = utils::get_cache_dir();
-    cache_dir.push(config::RELEASE_CACHE_FILE);
+pub async fn update(prune_cache: bool, include_all: bool) -> Res<()> {
+    let cache_file = utils::get_release_cache_file_path();
+
+    let min_version = env::var("GVM_MIN_VERSION")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .map(|v| utils::get_real_version(v.trim().to_string()));
+    let prune = match (include_all, min_version) {
+        (true, _) => None,
+        (false, Some(min_version)) => Some(Prune::MinVersion(min_version)),
+        (false, None) if prune_cache => Some(Prune::SupportedMinors),
+        (false, None) => None,
+    };
 
-    Ok(create_release_cache(cache_dir).await?)
+    create_release_cache(cache_file, prune).await
+}
+
+/// Checks whether a newer stable Go release has been published.
+///
+/// The newest stable remote release for the target platform is compared
+/// against the newest stable release in the cache, or against the active
+/// version if there is no cache. The cache is never written.
+///
+/// # Returns
+///
+/// Returns `Res<i32>`: `0` if the local state is up to date, or
+/// `config::UPDATE_AVAILABLE_EXIT_CODE` if a newer release exists, so CI can
+/// branch on the exit code. Returns an error if fetching the releases fails
+/// or no stable release is published for the target platform.
+pub async fn check_update() -> Res<i32> {
+    let remote_releases = fetch_target_releases().await?;
+    let Some(remote) = newest_stable(remote_releases.iter().map(|r| r.version.as_str())) else {
+        error!("No stable release is published for the target platform.");
+    };
+
+    let cache_file = utils::get_release_cache_file_path();
+    let local = match utils::list_cached_versions(&cache_file, None, true).await {
+        Ok(cached) => newest_stable(cached.iter().map(|r| r.version.as_str())).map(String::from),
+        Err(_) => utils::get_active_version().await,
+    };
+
+    Ok(update_status(remote, local.as_deref()))
+}
+
+/// Reports whether `remote` is newer than the `local` newest stable release.
+///
+/// # Returns
+///
+/// `0` if `local` is at least as new as `remote`, otherwise
+/// `config::UPDATE_AVAILABLE_EXIT_CODE`.
+fn update_status(remote: &str, local: Option<&str>) -> i32 {
+    match local {
+        Some(local) if utils::cmp_versions(remote, local) != Ordering::Greater => {
+            success!("Up to date: {} is the newest stable release.", local);
+            0
+        }
+        Some(local) => {
+            info!("Update available: {} (currently {}).", remote, local);
+            config::UPDATE_AVAILABLE_EXIT_CODE
+        }
+        None => {
+            info!(
+                "Update available: {} (no cached or active version).",
+                remote
+            );
+            config::UPDATE_AVAILABLE_EXIT_CODE
+        }
+    }
+}
+
+/// Makes sure a read-side command works with a fresh enough release cache.
+///
+/// The cache counts as stale if it is older than `GVM_CACHE_MAX_AGE` hours
+/// (24 by default). Depending on `refresh`, a stale cache only yields a hint,
+/// or the cache is updated first.
+///
+/// # Parameters
+///
+/// * `refresh`: How to deal with a stale or missing cache.
+///
+/// # Returns
+///
+/// Returns a `Res<()>`, which is `Ok(())` if the cache is fresh, was updated,
+/// or only a hint was printed. Returns an error if updating the cache fails.
+pub(crate) async fn refresh_release_cache(refresh: utils::CacheRefresh) -> Res<()> {
+    let age = utils::get_release_cache_age();
+    let stale = age.is_none_or(|age| age > utils::get_release_cache_max_age());
+
+    match refresh {
+        utils::CacheRefresh::Always => update(false, false).await,
+        utils::CacheRefresh::IfStale if stale => update(false, false).await,
+        utils::CacheRefresh::Hint if stale => {
+            if let Some(age) = age {
+                warning!(
+                    "Release cache is {} hours old. Run 'gvm update' or pass --auto-update to refresh it.",
+                    age.as_secs() / 3600
+                );
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+    };
+
+    fn file(filename: &str, os: &str, arch: &str, kind: &str) -> File {
+        File {
+            filename: filename.to_string(),
+            os: os.to_string(),
+            arch: arch.to_string(),
+            kind: kind.to_string(),
+            sha256: String::new(),
+        }
+    }
+
+    fn release(version: &str, files: Vec<File>) -> Release {
+        Release {
+            version: version.to_string(),
+            stable: true,
+            files,
+        }
+    }
+
+    fn base() -> reqwest::Url {
+        reqwest::Url::parse("https://go.dev/dl/").unwrap()
+    }
+
+    #[test]
+    fn update_status_compares_the_newest_stable_releases() {
+        let cached = ["go1.22.0", "go1.22.1", "go1.23rc1"];
+        let remote = ["go1.22.0", "go1.22.1", "go1.22.2", "go1.23rc2"];
+        let newest = |versions: &[&'static str]| newest_stable(versions.iter().copied()).unwrap();
+
+        assert_eq!(newest(&cached), "go1.22.1");
+        assert_eq!(newest(&remote), "go1.22.2");
+        assert_eq!(
+            update_status(newest(&remote), Some(newest(&cached))),
+            config::UPDATE_AVAILABLE_EXIT_CODE
+        );
+        assert_eq!(update_status(newest(&cached), Some(newest(&cached))), 0);
+        assert_eq!(update_status(newest(&cached), Some(newest(&remote))), 0);
+        assert_eq!(
+            update_status(newest(&remote), None),
+            config::UPDATE_AVAILABLE_EXIT_CODE
+        );
+    }
+
+    #[test]
+    fn filter_releases_uses_the_download_mirror() {
+        let mirror = reqwest::Url::parse("https://golang.google.cn/dl/").unwrap();
+        let releases = vec![release(
+            "go1.22.0",
+            vec![file(
+                "go1.22.0.linux-amd64.tar.gz",
+                "linux",
+                "amd64",
+                "archive",
+            )],
+        )];
+        let filtered = filter_releases(releases, "linux", "amd64", &mirror).unwrap();
+        assert_eq!(
+            filtered[0].url,
+            "https://golang.google.cn/dl/go1.22.0.linux-amd64.tar.gz"
+        );
+    }
+
+    #[test]
+    fn filter_releases_drops_duplicates() {
+        let releases = vec![
+            release(
+                "go1.22.0",
+                vec![
+                    file("go1.22.0.linux-amd64.tar.gz", "linux", "amd64", "archive"),
+                    file("go1.22.0.linux-amd64.tar.gz", "linux", "amd64", "archive"),
+                ],
+            ),
+            release(
+                "go1.22.0",
+                vec![file(
+                    "go1.22.0.linux-amd64.tar.gz",
+                    "linux",
+                    "amd64",
+                    "archive",
+                )],
+            ),
+            release(
+                "go1.21.0",
+                vec![file(
+                    "go1.21.0.linux-amd64.tar.gz",
+                    "linux",
+                    "amd64",
+                    "archive",
+                )],
+            ),
+        ];
+
+        let filtered = filter_releases(releases, "linux", "amd64", &base()).unwrap();
+
+        let versions: Vec<_> = filtered.iter().map(|r| r.version.as_str()).collect();
+        assert_eq!(versions, ["go1.22.0", "go1.21.0"]);
+        assert_eq!(
+            filtered[0].url,
+            "https://go.dev/dl/go1.22.0.linux-amd64.tar.gz"
+        );
+    }
+
+    #[test]
+    fn filter_releases_selects_the_target_arch() {
+        let releases = vec![release(
+            "go1.22.0",
+            vec![
+                file("go1.22.0.linux-amd64.tar.gz", "linux", "amd64", "archive"),
+                file("go1.22.0.linux-arm64.tar.gz", "linux", "arm64", "archive"),
+                file("go1.22.0.linux-armv6l.tar.gz", "linux", "armv6l", "archive"),
+                file("go1.22.0.linux-ppc64.tar.gz", "linux", "ppc64", "archive"),
+                file(
+                    "go1.22.0.linux-ppc64le.tar.gz",
+                    "linux",
+                    "ppc64le",
+                    "archive",
+                ),
+            ],
+        )];
+
+        for arch in ["amd64", "arm64", "armv6l", "ppc64", "ppc64le"] {
+            let filtered = filter_releases(
+                releases_for(&releases, "linux", arch),
+                "linux",
+                arch,
+                &base(),
+            )
+            .unwrap();
+            assert_eq!(filtered.len(), 1, "{}", arch);
+            assert_eq!(filtered[0].arch, arch);
+            assert_eq!(
+                filtered[0].url,
+                format!("https://go.dev/dl/go1.22.0.linux-{}.tar.gz", arch)
+            );
+        }
+    }
+
+    #[test]
+    fn filter_releases_selects_the_target_os() {
+        let releases = vec![
+            release(
+                "go1.22.0",
+                vec![
+                    file("go1.22.0.linux-arm64.tar.gz", "linux", "arm64", "archive"),
+                    file("go1.22.0.darwin-arm64.tar.gz", "darwin", "arm64", "archive"),
+                    file("go1.22.0.darwin-arm64.pkg", "darwin", "arm64", "installer"),
+                ],
+            ),
+            release(
+                "go1.21.0",
+                vec![file(
+                    "go1.21.0.linux-arm64.tar.gz",
+                    "linux",
+                    "arm64",
+                    "archive",
+                )],
+            ),
+        ];
+
+        let darwin = filter_releases(
+            releases_for(&releases, "darwin", "arm64"),
+            "darwin",
+            "arm64",
+            &base(),
+        )
+        .unwrap();
+        let linux = filter_releases(
+            releases_for(&releases, "linux", "arm64"),
+            "linux",
+            "arm64",
+            &base(),
+        )
+        .unwrap();
+
+        let versions = |releases: &[utils::FilteredRelease]| -> Vec<String> {
+            releases.iter().map(|r| r.version.clone()).collect()
+        };
+        assert_eq!(versions(&darwin), ["go1.22.0"]);
+        assert_eq!(darwin[0].os, "darwin");
+        assert!(darwin[0].url.ends_with("go1.22.0.darwin-arm64.tar.gz"));
+        assert_eq!(versions(&linux), ["go1.22.0", "go1.21.0"]);
+    }
+
+    #[test]
+    fn parse_releases_reads_chunks_across_boundaries() {
+        let releases = vec![
+            release(
+                "go1.22.0",
+                vec![
+                    file("go1.22.0.linux-amd64.tar.gz", "linux", "amd64", "archive"),
+                    file("go1.22.0.darwin-arm64.tar.gz", "darwin", "arm64", "archive"),
+                ],
+            ),
+            release(
+                "go1.21.0",
+                vec![file(
+                    "go1.21.0.darwin-arm64.tar.gz",
+                    "darwin",
+                    "arm64",
+                    "archive",
+                )],
+            ),
+        ];
+        let body = serde_json::to_vec(&releases).unwrap();
+
+        let (sender, chunks) = mpsc::channel(RELEASE_CHUNK_BUFFER);
+        let feeder = std::thread::spawn(move || {
+            for chunk in body.chunks(7) {
+                sender
+                    .blocking_send(bytes::Bytes::copy_from_slice(chunk))
+                    .unwrap();
+            }
+        });
+        let reader = ChunkReader {
+            chunks,
+            current: bytes::Bytes::new(),
+        };
+        let parsed = parse_releases(reader, "linux", "amd64").unwrap();
+        feeder.join().unwrap();
+
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].version, "go1.22.0");
+        assert_eq!(parsed[0].files[0].filename, "go1.22.0.linux-amd64.tar.gz");
+    }
+
+    /// Counts the bytes allocated by each thread, see `peak_allocation`.
+    struct CountingAllocator;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    thread_local! {
+        static ALLOCATED: Cell<isize> = const { Cell::new(0) };
+        static PEAK: Cell<isize> = const { Cell::new(0) };
+    }
+
+    fn track_allocation(delta: isize) {
+        // the counters are gone while a thread shuts down
+        let _ = ALLOCATED.try_with(|allocated| {
+            allocated.set(allocated.get() + delta);
+            let _ = PEAK.try_with(|peak| peak.set(peak.get().max(allocated.get())));
+        });
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                track_allocation(layout.size() as isize);
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            track_allocation(-(layout.size() as isize));
+        }
+    }
+
+    /// Runs `f` and returns its result with the most memory this thread held
+    /// at once meanwhile, including the result.
+    fn peak_allocation<T>(f: impl FnOnce() -> T) -> (T, usize) {
+        let start = ALLOCATED.with(Cell::get);
+        PEAK.with(|peak| peak.set(start));
+        let value = f();
+        (value, (PEAK.with(Cell::get) - start) as usize)
+    }
+
+    /// A release list with an archive for each of `platforms` per release,
+    /// generated one release at a time so the body is never held in memory.
+    struct GeneratedReleases {
+        releases: usize,
+        platforms: usize,
+        next: usize,
+        pending: io::Cursor<Vec<u8>>,
+    }
+
+    impl GeneratedReleases {
+        fn new(releases: usize, platforms: usize) -> Self {
+            GeneratedReleases {
+                releases,
+                platforms,
+                next: 0,
+                pending: io::Cursor::new(Vec::new()),
+            }
+        }
+
+        /// Returns the JSON of a release, only the first platform is linux/amd64.
+        fn release(&self, index: usize) -> String {
+            let version = format!("go1.{}.{}", index / 100, index % 100);
+            let files: Vec<String> = (0..self.platforms)
+                .map(|platform| {
+                    let (os, arch) = match platform {
+                        0 => ("linux".to_string(), "amd64".to_string()),
+                        _ => ("plan9".to_string(), format!("arch{}", platform)),
+                    };
+                    format!(
+                        r#"{{"filename":"{}.{}-{}.tar.gz","os":"{}","arch":"{}","kind":"archive","sha256":"{}"}}"#,
+                        version,
+                        os,
+                        arch,
+                        os,
+                        arch,
+                        "ab".repeat(32)
+                    )
+                })
+                .collect();
+            format!(
+                r#"{{"version":"{}","stable":true,"files":[{}]}}"#,
+                version,
+                files.join(",")
+            )
+        }
+    }
+
+    impl Read for GeneratedReleases {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            loop {
+                let read = self.pending.read(buf)?;
+                if read > 0 || self.next > self.releases {
+                    return Ok(read);
+                }
+                let chunk = match self.next {
+                    0 => format!("[{}", self.release(0)),
+                    next if next == self.releases => "]".to_string(),
+                    next => format!(",{}", self.release(next)),
+                };
+                self.pending = io::Cursor::new(chunk.into_bytes());
+                self.next += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn parse_releases_drops_foreign_files_while_streaming() {
+        let body = || GeneratedReleases::new(1000, 40);
+
+        let (streamed, streamed_peak) =
+            peak_allocation(|| parse_releases(body(), "linux", "amd64").unwrap());
+        let (collected, collected_peak) = peak_allocation(|| {
+            serde_json::from_reader::<_, Vec<Release>>(BufReader::new(body())).unwrap()
+        });
+
+        assert_eq!(streamed.len(), 1000);
+        assert!(streamed
+            .iter()
+            .all(|r| r.files.len() == 1 && r.files.capacity() == 1));
+        assert_eq!(
+            collected.iter().map(|r| r.files.len()).sum::<usize>(),
+            40_000
+        );
+        // only the archives for the platform are ever held, not those of all 40
+        assert!(
+            streamed_peak * 10 < collected_peak,
+            "streamed {} bytes, collected {} bytes",
+            streamed_peak,
+            collected_peak
+        );
+    }
+
+    #[test]
+    fn parse_releases_rejects_a_truncated_body() {
+        let body = br#"[{"version":"go1.22.0","stable":true,"files":["#;
+        assert!(parse_releases(&body[..], "linux", "amd64").is_err());
+        assert!(parse_releases(&br#"[] []"#[..], "linux", "amd64").is_err());
+    }
+
+    /// Runs `releases` through `PlatformReleases` like `fetch_releases` does.
+    fn releases_for(releases: &[Release], os: &str, arch: &str) -> Vec<Release> {
+        let json = serde_json::to_string(releases).unwrap();
+        let mut deserializer = serde_json::Deserializer::from_str(&json);
+        PlatformReleases { os, arch }
+            .deserialize(&mut deserializer)
+            .unwrap()
+    }
+
+    #[test]
+    fn filter_releases_keeps_only_archives_for_the_platform() {
+        let releases = vec![release(
+            "go1.22.0",
+            vec![
+                file("go1.22.0.linux-amd64.tar.gz", "linux", "amd64", "archive"),
+                file("go1.22.0.linux-arm64.tar.gz", "linux", "arm64", "archive"),
+                file("go1.22.0.darwin-amd64.pkg", "darwin", "amd64", "installer"),
+                file("go1.22.0.src.tar.gz", "", "", "source"),
+            ],
+        )];
+
+        let filtered = filter_releases(releases, "linux", "amd64", &base()).unwrap();
+
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].arch, "amd64");
+        assert_eq!(filtered[0].os, "linux");
+    }
 }
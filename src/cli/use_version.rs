@@ -1,10 +1,87 @@
-use crate::{error, success, utils, Res};
+use std::env;
 
-pub async fn use_version(version: String) -> Res<()> {
-    let real_verison = utils::get_real_version(version);
+use crate::{
+    error, info, success,
+    utils::{self, ShellSyntax},
+    Res,
+};
+
+/// Switches to an installed version.
+///
+/// If no version is given, the version pinned by the current project is used.
+/// It is looked up by walking up from the current directory, with the
+/// precedence `.go-version` > `toolchain` directive > `go` directive in `go.mod`.
+///
+/// # Parameters
+///
+/// * `version`: The version to activate, or `None` to use the project's version.
+///   `latest` (or `latest-stable`) picks the newest installed stable version,
+///   and a minor version like "1.22" the newest installed patch release of it.
+///   `-` switches back to the previously active version.
+/// * `no_activate_default`: When `true`, the default alias is left unchanged.
+/// * `print`: When set, the environment script for the version is printed in
+///   this syntax instead of activating the version, for use with `eval`.
+/// * `local`: When `true`, the version is pinned in `.go-version` in the
+///   current directory instead of changing the global active version, see
+///   `local`. Unlike `gvm local`, an uninstalled version is rejected.
+/// * `from_gomod`: When `true` and no version is given, only the nearest
+///   `go.mod` is consulted, see `utils::go_mod_version`.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` if the version is active afterwards, or
+/// an error if it is not installed or cannot be resolved.
+pub async fn use_version(
+    version: Option<String>,
+    no_activate_default: bool,
+    print: Option<ShellSyntax>,
+    local: bool,
+    from_gomod: bool,
+) -> Res<()> {
+    let version = match version {
+        Some(v) => v,
+        None if from_gomod => {
+            let project_version = utils::go_mod_version(&env::current_dir()?).await?;
+            if print.is_none() {
+                info!(
+                    "Using version {} from the {} in {}.",
+                    project_version.version,
+                    project_version.source,
+                    project_version.path.display()
+                );
+            }
+            project_version.version
+        }
+        None => match utils::resolve_project_version(&env::current_dir()?).await {
+            Some(project_version) if print.is_some() => project_version.version,
+            Some(project_version) => {
+                info!(
+                    "Using version {} from {}.",
+                    project_version.version,
+                    project_version.path.display()
+                );
+                project_version.version
+            }
+            None => error!(
+                "No version given and no .go-version or go.mod found. Please specify a version."
+            ),
+        },
+    };
+    utils::validate_name("version", &version)?;
+
+    // "-" switches back to the version that was active before
+    let version = if version == "-" {
+        match utils::read_previous_file().await {
+            Some(previous) => previous,
+            None => error!("No previously active version recorded. Switch versions with 'gvm use <version>' first."),
+        }
+    } else {
+        version
+    };
 
     // get installed versions
     let installed_versions: Vec<String> = utils::list_installed_versions().await?;
+    let real_verison = resolve_installed_version(version, &installed_versions)?;
 
     // check if version is already installed
     if !installed_versions.contains(&real_verison) {
@@ -14,6 +91,15 @@ pub async fn use_version(version: String) -> Res<()> {
         );
     }
 
+    if let Some(shell) = print {
+        print!("{}", utils::render_env_script(&real_verison, shell));
+        return Ok(());
+    }
+
+    if local {
+        return super::local::local(Some(real_verison)).await;
+    }
+
     // check if version is already active
     if utils::is_version_active(&real_verison).await {
         success!("Version {} is already active.", real_verison);
@@ -21,5 +107,110 @@ pub async fn use_version(version: String) -> Res<()> {
     }
 
     // activate version
-    utils::activate_version(real_verison).await
+    utils::activate_version(real_verison.clone(), !no_activate_default).await?;
+    info!(
+        "To apply it to the current shell right away, run: eval \"$(gvm use --print {})\"",
+        real_verison
+    );
+
+    Ok(())
+}
+
+/// Resolves a version argument against the installed versions.
+///
+/// # Parameters
+///
+/// * `version`: The version to resolve. `latest` (or `latest-stable`) picks the
+///   newest installed stable version, and a minor version like "1.22" the newest
+///   installed patch release of it.
+/// * `installed_versions`: The names of the installed version directories.
+///
+/// # Returns
+///
+/// Returns the resolved version, which is only installed if one matched, or an
+/// error if the version is invalid or `latest` finds no stable version.
+pub(super) fn resolve_installed_version(
+    version: String,
+    installed_versions: &[String],
+) -> Res<String> {
+    let real_version = if utils::is_latest_keyword(&version) {
+        match utils::latest_stable_version(installed_versions.iter().map(String::as_str)) {
+            Some(latest) => latest,
+            None => error!("No stable version installed. Run 'gvm install latest' first."),
+        }
+    } else {
+        utils::parse_version_arg(version)?
+    };
+
+    // a minor version like "go1.22" picks its newest installed patch release
+    if installed_versions.contains(&real_version) {
+        return Ok(real_version);
+    }
+    Ok(
+        utils::latest_patch_version(&real_version, installed_versions.iter().map(String::as_str))
+            .unwrap_or(real_version),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::{
+        assert_rejected, clear_activation, fake_version, lock_activation, MALICIOUS_NAMES,
+    };
+
+    #[tokio::test]
+    async fn use_prev_switches_back_to_the_previous_version() {
+        let _lock = lock_activation().await;
+        clear_activation();
+        fake_version("go1.33.1");
+        fake_version("go1.33.2");
+        let switch =
+            |version: &str| use_version(Some(version.to_string()), true, None, false, false);
+
+        let err = switch("-").await.unwrap_err();
+        assert!(err.to_string().contains("No previously active"), "{}", err);
+
+        switch("go1.33.1").await.unwrap();
+        switch("go1.33.2").await.unwrap();
+        switch("-").await.unwrap();
+        assert_eq!(utils::read_active_file().await.as_deref(), Some("go1.33.1"));
+        assert_eq!(
+            utils::read_previous_file().await.as_deref(),
+            Some("go1.33.2")
+        );
+
+        // reactivating the active version keeps the toggle
+        switch("go1.33.1").await.unwrap();
+        assert_eq!(
+            utils::read_previous_file().await.as_deref(),
+            Some("go1.33.2")
+        );
+        clear_activation();
+    }
+
+    #[tokio::test]
+    async fn use_local_rejects_uninstalled_versions() {
+        let version_file = env::current_dir()
+            .unwrap()
+            .join(crate::config::GO_VERSION_FILE);
+        let pinned = version_file.exists();
+
+        let err = use_version(Some("1.30.99".to_string()), false, None, true, false)
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("not installed"), "{}", err);
+        assert_eq!(version_file.exists(), pinned);
+    }
+
+    #[tokio::test]
+    async fn use_version_rejects_malicious_versions() {
+        for name in MALICIOUS_NAMES {
+            assert_rejected(
+                name,
+                use_version(Some(name.to_string()), false, None, false, false).await,
+            );
+        }
+    }
 }
@@ -0,0 +1,40 @@
+use crate::{error, utils, Res};
+
+/// Prints the installation directory (GOROOT) of a version.
+///
+/// Only the absolute path is printed, so the output can be used directly in
+/// scripts, e.g. `export GOROOT=$(gvm which)`.
+///
+/// # Parameters
+///
+/// * `version`: An optional version to resolve. If `None`, the active version
+///   is used.
+///
+/// # Returns
+///
+/// Returns `Res<()>`, which is `Ok(())` if the path was printed, or an error
+/// if the version is not installed or the GVM directories cannot be read.
+pub async fn which(version: Option<String>) -> Res<()> {
+    let real_version = match version {
+        Some(v) => utils::parse_version_arg(v)?,
+        None => match utils::get_active_version().await {
+            Some(v) => v,
+            None => error!("No active version found. Use 'gvm list' to see available versions."),
+        },
+    };
+
+    let installed_versions = utils::list_installed_versions().await?;
+    if !installed_versions.contains(&real_version) {
+        error!(
+            "Version {} is not installed. Please install it first.",
+            real_version
+        );
+    }
+
+    println!(
+        "{}",
+        utils::get_version_file_path().join(&real_version).display()
+    );
+
+    Ok(())
+}
@@ -1,7 +1,17 @@
-//! Constants for GVM configuration paths.
+//! Constants for GVM configuration paths, and the optional settings file
+//! `~/.gvm/config.toml`.
+
+use std::{fs, io, path::Path, sync::OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{utils, warning, Res};
 
 /// Path to the main GVM directory.
 pub const GVM_MAIN_PATH: &str = ".gvm";
+/// Path to the main GVM directory inside the temporary directory, used when
+/// there is no home directory.
+pub const GVM_FALLBACK_PATH: &str = "gvm";
 /// Path to the GVM cache directory.
 pub const GVM_CACHE_PATH: &str = "cache";
 /// Path to the GVM environment directory.
@@ -10,10 +20,200 @@ pub const GVM_ENVIRONMENT_PATH: &str = "environment";
 pub const GVM_VERSION_PATH: &str = "version";
 /// Path to the GVM package directory.
 pub const GVM_PACKAGE_PATH: &str = "package";
+/// Name of the GOPATH shared by all versions inside the package directory,
+/// see `shared_gopath`.
+pub const SHARED_GOPATH_NAME: &str = "shared";
 /// Path to the GVM archive directory.
 pub const GVM_ARCHIVE_PATH: &str = "archive";
+/// Path of the verified release archives kept for reuse, inside the archive
+/// directory.
+pub const GVM_ARCHIVE_CACHE_PATH: &str = "cached";
 /// Path to the GVM alias directory.
 pub const GVM_ALIAS_PATH: &str = "alias";
+/// Path to the GVM shim directory, see `gvm shim install`.
+pub const GVM_SHIMS_PATH: &str = "shims";
+/// Path to the GVM log directory.
+pub const GVM_LOGS_PATH: &str = "logs";
+
+/// Name of the settings file in the GVM directory.
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// Name of the log file written with `--log-file` or `GVM_LOG`.
+pub const LOG_FILE: &str = "gvm.log";
+/// Size in bytes after which the log file is rotated to `gvm.log.1`.
+pub const LOG_FILE_MAX_BYTES: u64 = 1024 * 1024;
+
+/// Base URL release archives are downloaded from, unless overridden with
+/// `GVM_DOWNLOAD_BASE`.
+pub const DEFAULT_DOWNLOAD_BASE: &str = "https://go.dev/dl/";
+/// Query appended to the download base to list all releases as JSON.
+pub const RELEASES_QUERY: &str = "?mode=json&include=all";
+
+/// GitHub repository `gvm self-update` looks for new gvm releases in.
+pub const SELF_UPDATE_REPO: &str = "soundphilosopher/gvm";
+
+/// Seconds to wait for a connection to the download server.
+pub const HTTP_CONNECT_TIMEOUT_SECS: u64 = 10;
+/// Seconds a download may stall without receiving data before it fails.
+pub const HTTP_READ_TIMEOUT_SECS: u64 = 30;
+/// Seconds the whole release list request may take.
+pub const RELEASES_REQUEST_TIMEOUT_SECS: u64 = 60;
+
+/// Number of times a failed network request is retried, unless overridden
+/// with `--retries`.
+pub const NETWORK_RETRIES: u32 = 2;
+/// Delay in milliseconds before the first retry. It doubles with every retry.
+pub const RETRY_BACKOFF_MS: u64 = 1000;
+
+/// Exit code of `gvm update --check` when a newer stable release exists.
+pub const UPDATE_AVAILABLE_EXIT_CODE: i32 = 10;
+
+/// Number of versions `gvm install` installs at the same time, unless
+/// overridden with `--jobs`.
+pub const INSTALL_JOBS: usize = 2;
 
 /// Path to the GVM release cache file.
 pub const RELEASE_CACHE_FILE: &str = "releases.json";
+/// Age in hours after which the release cache is considered stale, unless
+/// overridden with `GVM_CACHE_MAX_AGE`.
+pub const RELEASE_CACHE_MAX_AGE_HOURS: u64 = 24;
+
+/// Names that cannot be used for aliases. `default` is managed by `gvm use`,
+/// `list` and `ls` list the aliases, and `active` and `previous` are the marker
+/// files of the active and the previously active version.
+pub const RESERVED_ALIAS_NAMES: &[&str] = &["default", "list", "ls", "active", "previous"];
+
+/// Name of the file pinning a project's Go version.
+pub const GO_VERSION_FILE: &str = ".go-version";
+/// Name of the Go module file.
+pub const GO_MOD_FILE: &str = "go.mod";
+
+/// Line starting the block `gvm init` writes to the shell profile.
+pub const INIT_START_MARKER: &str = "# >>> gvm initialize >>>";
+/// Line ending the block `gvm init` writes to the shell profile.
+pub const INIT_END_MARKER: &str = "# <<< gvm initialize <<<";
+
+/// Settings read from `~/.gvm/config.toml`.
+///
+/// Every setting is optional. Environment variables override the file, and
+/// built-in defaults apply when neither is set, see the getters in `utils`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Base URL release archives are downloaded from (`GVM_DOWNLOAD_BASE`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_base: Option<String>,
+    /// URL the release list is fetched from (`GVM_RELEASES_URL`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub releases_url: Option<String>,
+    /// Versions installed at the same time (`GVM_INSTALL_JOBS`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_jobs: Option<usize>,
+    /// Hours after which the release cache is stale (`GVM_CACHE_MAX_AGE`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_ttl_hours: Option<u64>,
+    /// Whether `gvm list-remote` hides pre-releases without `--all`
+    /// (`GVM_LIST_STABLE_BY_DEFAULT`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub list_stable_by_default: Option<bool>,
+    /// Whether all versions use one GOPATH instead of one per version
+    /// (`GVM_SHARED_GOPATH`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shared_gopath: Option<bool>,
+}
+
+/// Keys of `Config`, in the order `gvm config get` prints them.
+pub const CONFIG_KEYS: [&str; 6] = [
+    "download_base",
+    "releases_url",
+    "default_jobs",
+    "cache_ttl_hours",
+    "list_stable_by_default",
+    "shared_gopath",
+];
+
+/// The settings file, loaded on first use.
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Reads a settings file.
+///
+/// # Parameters
+///
+/// * `path`: The settings file to read.
+///
+/// # Returns
+///
+/// The settings, the defaults if the file does not exist, or an error if it
+/// cannot be read or is not valid TOML with known keys.
+pub fn read_config_file(path: &Path) -> Res<Config> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(toml::from_str(&content)
+            .map_err(|e| format!("Invalid settings file {}: {}", path.display(), e.message()))?),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+        Err(e) => Err(format!("Cannot read settings file {}: {}", path.display(), e).into()),
+    }
+}
+
+/// Returns the settings from `~/.gvm/config.toml`.
+///
+/// The file is read once. If it is broken, a warning is printed and the
+/// defaults are used, so a typo never locks the user out of gvm.
+pub fn load() -> &'static Config {
+    CONFIG.get_or_init(|| {
+        let path = utils::get_gvm_base_file_path().join(CONFIG_FILE);
+        read_config_file(&path).unwrap_or_else(|e| {
+            warning!("{}. Using the default settings.", e);
+            Config::default()
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_files_are_parsed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        fs::write(
+            &path,
+            "download_base = \"https://golang.google.cn/dl/\"\ndefault_jobs = 4\nshared_gopath = true\n",
+        )
+        .unwrap();
+
+        let config = read_config_file(&path).unwrap();
+        assert_eq!(
+            config.download_base.as_deref(),
+            Some("https://golang.google.cn/dl/")
+        );
+        assert_eq!(config.default_jobs, Some(4));
+        assert_eq!(config.shared_gopath, Some(true));
+        assert_eq!(config.releases_url, None);
+        assert_eq!(config.cache_ttl_hours, None);
+        assert_eq!(config.list_stable_by_default, None);
+    }
+
+    #[test]
+    fn a_missing_config_file_yields_the_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = read_config_file(&dir.path().join(CONFIG_FILE)).unwrap();
+
+        assert_eq!(
+            toml::to_string(&config).unwrap(),
+            toml::to_string(&Config::default()).unwrap()
+        );
+        assert!(config.download_base.is_none());
+    }
+
+    #[test]
+    fn broken_config_files_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        for content in ["default_jobs = \"four\"", "jobs = 4", "default_jobs ="] {
+            fs::write(&path, content).unwrap();
+            let err = read_config_file(&path).unwrap_err();
+            assert!(err.to_string().contains("Invalid settings file"), "{}", err);
+        }
+    }
+}
@@ -4,27 +4,74 @@ pub mod utils;
 
 pub type Res<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
 
+/// Prints a step. Suppressed by `--quiet`.
 #[macro_export]
 macro_rules! info {
   ($($arg:tt)*) => ({
     use colored::Colorize;
-    println!("[{}] {}", "o".yellow().bold(), std::format_args!($($arg)*));
+    let message = std::format!($($arg)*);
+    $crate::utils::log_line("INFO", &message);
+    $crate::utils::print_log(
+      $crate::utils::Verbosity::Normal,
+      std::format_args!("[{}] {}", "o".yellow().bold(), message),
+    );
   })
 }
 
+/// Prints the successful outcome of a step. Suppressed by `--quiet`.
 #[macro_export]
 macro_rules! success {
   ($($arg:tt)*) => ({
     use colored::Colorize;
-    println!("\t[{}] {}", "✓".green().bold(), std::format_args!($($arg)*));
+    let message = std::format!($($arg)*);
+    $crate::utils::log_line("SUCCESS", &message);
+    $crate::utils::print_log(
+      $crate::utils::Verbosity::Normal,
+      std::format_args!("\t[{}] {}", "✓".green().bold(), message),
+    );
   })
 }
 
+/// Prints extra detail. Only shown with `--verbose`.
+#[macro_export]
+macro_rules! debug {
+  ($($arg:tt)*) => ({
+    use colored::Colorize;
+    let message = std::format!($($arg)*);
+    $crate::utils::log_line("DEBUG", &message);
+    $crate::utils::print_log(
+      $crate::utils::Verbosity::Verbose,
+      std::format_args!("\t[{}] {}", "*".blue(), message),
+    );
+  })
+}
+
+/// Returns early with an error built from a format string.
+///
+/// The error is propagated through `Res<T>` and printed by `report_error` in
+/// `main`, so library code never exits the process.
 #[macro_export]
 macro_rules! error {
+  ($($arg:tt)*) => ({
+    return Err(std::format!($($arg)*).into());
+  })
+}
+
+/// Prints an error in the same style as the other log macros and mirrors it
+/// into the log file.
+pub fn report_error(err: &(dyn std::error::Error + Send + Sync)) {
+    use colored::Colorize;
+    utils::log_line("ERROR", &err.to_string());
+    eprintln!("\t[{}] {}", "!".red().bold(), err);
+}
+
+/// Prints a warning to stderr. Shown regardless of the verbosity.
+#[macro_export]
+macro_rules! warning {
   ($($arg:tt)*) => ({
     use colored::Colorize;
-    println!("\t[{}] {}", "!".red().bold(), std::format_args!($($arg)*));
-    std::process::exit(1);
+    let message = std::format!($($arg)*);
+    $crate::utils::log_line("WARNING", &message);
+    eprintln!("\t[{}] {}", "!".yellow().bold(), message);
   })
 }
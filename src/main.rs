@@ -1,5 +1,5 @@
-#[cfg(not(target_os = "linux"))]
-compile_error!("can only be compiled on linux ;)");
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+compile_error!("can only be compiled on linux or macOS ;)");
 
 use clap::{
     builder::{
@@ -10,9 +10,19 @@ use clap::{
 };
 use clap_complete::{generate, Shell};
 use gvm::{
-    cli::{alias, init, install, list, list_remote, remove, remove_alias, update, use_version},
+    cli::{
+        add_dynamic_completions, alias, cache, check_update, complete_aliases, complete_versions,
+        configure, current, diff, doctor, env, exec, export_env, freeze, hook, info, init, install,
+        install_completions, install_dry_run, install_many, list, list_remote, local, prune,
+        rehash, remove, remove_alias, rename_alias, restore, self_update, shim, update,
+        use_version, which, AliasOptions, CacheAction, ConfigAction, EnvFormat, HookShell,
+        InstallOptions, ListRemoteFormat, ShimAction,
+    },
+    config,
+    utils::{self, CacheRefresh, PathPosition, ShellSyntax},
     Res,
 };
+use std::{env, path::PathBuf, process};
 
 fn styles() -> Styles {
     Styles::styled()
@@ -34,8 +44,68 @@ fn styles() -> Styles {
 struct Opts {
     #[clap(subcommand)]
     command: Command,
+
+    #[clap(
+        long,
+        global = true,
+        value_parser = ["linux", "darwin"],
+        help = "Treat the host as this OS when caching and installing releases"
+    )]
+    assume_os: Option<String>,
+
+    #[clap(
+        long,
+        global = true,
+        value_parser = GO_ARCHES,
+        help = "Treat the host as this architecture when caching and installing releases"
+    )]
+    assume_arch: Option<String>,
+
+    #[clap(
+        long,
+        short,
+        global = true,
+        help = "Only print warnings and errors ('current' prints nothing and only sets the exit code)"
+    )]
+    quiet: bool,
+
+    #[clap(
+        long,
+        short,
+        global = true,
+        conflicts_with = "quiet",
+        help = "Print extra detail"
+    )]
+    verbose: bool,
+
+    #[clap(
+        long,
+        global = true,
+        help = "Also write the output to ~/.gvm/logs/gvm.log (or set GVM_LOG=1)"
+    )]
+    log_file: bool,
 }
 
+/// Operating systems go.dev publishes `tar.gz` archives for.
+const GO_OSES: [&str; 10] = [
+    "aix",
+    "darwin",
+    "dragonfly",
+    "freebsd",
+    "illumos",
+    "linux",
+    "netbsd",
+    "openbsd",
+    "plan9",
+    "solaris",
+];
+
+/// Architectures go.dev publishes archives for.
+const GO_ARCHES: [&str; 13] = [
+    "386", "amd64", "arm64", "armv6l", "loong64", "mips", "mipsle", "mips64", "mips64le", "ppc64",
+    "ppc64le", "riscv64", "s390x",
+];
+
 #[derive(Parser, Debug, Clone)]
 enum Command {
     #[clap(about = "Install golang version from source")]
@@ -50,6 +120,12 @@ enum Command {
     #[clap(about = "Remove alias")]
     RemoveAlias(RemoveAliasOption),
 
+    #[clap(
+        about = "Rename an alias, keeping its target",
+        long_about = "Rename an alias, keeping its target.\n\nThe alias is renamed in one step, so it never disappears in between. Aliases pointing to it follow the new name. 'default' cannot be renamed."
+    )]
+    RenameAlias(RenameAliasOption),
+
     #[clap(about = "Show all installed versions", alias = "ls")]
     List(ListOption),
 
@@ -59,29 +135,274 @@ enum Command {
     #[clap(about = "Get shell completions")]
     Completions(CompletionsOption),
 
-    #[clap(about = "Switch to specified version")]
+    #[clap(
+        about = "Switch to specified version",
+        long_about = "Switch to specified version.\n\nWithout a version, the project's version is used. It is looked up from the current directory upwards with the precedence: .go-version > toolchain directive in go.mod > go directive in go.mod."
+    )]
     Use(UseOption),
 
-    #[clap(about = "Update GVM version")]
+    #[clap(
+        about = "Update the cache of available Go releases",
+        long_about = "Update the cache of available Go releases.\n\nThis does not update gvm itself, see 'gvm self-update'."
+    )]
     Update(UpdateOption),
 
+    #[clap(
+        about = "Update gvm itself to the newest release",
+        long_about = "Update gvm itself to the newest release.\n\nThe binary for the host OS and architecture is downloaded from the latest GitHub release, verified against the .sha256 file published next to it, and replaces the running executable atomically. Set GVM_SELF_UPDATE_URL to use another release API endpoint."
+    )]
+    SelfUpdate(SelfUpdateOption),
+
     #[clap(about = "Init go environment")]
     Init(InitOption),
+
+    #[clap(
+        about = "Print go environment for a version",
+        long_about = "Print go environment for a version.\n\n--format picks the output: bash (export lines, the default), fish (set -gx lines), dotenv (KEY=value lines for .env files, without PATH) or json (an object of the variables, with the PATH entries in PATH_PREPEND)."
+    )]
+    Env(EnvOption),
+
+    #[clap(about = "Write installed versions, aliases and active version to a manifest")]
+    Freeze(FreezeOption),
+
+    #[clap(about = "Install versions, aliases and active version from a manifest")]
+    Restore(RestoreOption),
+
+    #[clap(about = "Compare two versions")]
+    Diff(DiffOption),
+
+    #[clap(about = "Rebuild go environment and default alias of the active version")]
+    Rehash,
+
+    #[clap(about = "Write a standalone environment script for a version")]
+    ExportEnv(ExportEnvOption),
+
+    #[clap(
+        about = "Remove versions that are not active or aliased",
+        long_about = "Remove versions that are not active or aliased.\n\nThe active version and every version an alias points to are always kept, even with --keep 0 or --keep-newest 0. --keep N also keeps the N newest versions of each minor version, e.g. the two newest go1.22.x with --keep 2. --keep-newest N also keeps the N newest stable versions overall. Given both, a version is kept if either keeps it."
+    )]
+    Prune(PruneOption),
+
+    #[clap(
+        about = "Print a shell hook that switches versions on directory change",
+        long_about = "Print a shell hook that switches versions on directory change.\n\nAdd it to your profile, e.g. eval \"$(gvm hook bash)\". Whenever the directory changes, the hook applies the version pinned by .go-version or go.mod to the current shell."
+    )]
+    Hook(HookOption),
+
+    #[clap(about = "Print the installation directory (GOROOT) of a version")]
+    Which(WhichOption),
+
+    #[clap(about = "Print the active version")]
+    Current,
+
+    #[clap(
+        about = "Show details about a version",
+        long_about = "Show details about a version.\n\nFor an installed version, prints its GOROOT, whether it is active, the aliases pointing to it, the size of its directory and the output of its 'go version'. For a version that is only in the release cache, prints the download URL and checksum of its archive."
+    )]
+    Info(InfoOption),
+
+    #[clap(
+        about = "Run a command under a version without activating it",
+        long_about = "Run a command under a version without activating it.\n\nThe command gets GOROOT, GOCACHE, GOPATH and GOENV of the version, and its bin directories are prepended to PATH. The active version and aliases are not changed. The exit code of the command is passed through.\n\nExample: gvm exec 1.21.5 -- go version"
+    )]
+    Exec(ExecOption),
+
+    #[clap(
+        about = "Diagnose the GVM installation",
+        long_about = "Diagnose the GVM installation.\n\nChecks the directory structure, aliases, the active version and its go.env file, and the shell profile, and prints a hint for every problem found. Exits with a non-zero status if any check fails."
+    )]
+    Doctor,
+
+    #[clap(
+        about = "Pin a version for the current directory or activate the pinned one",
+        long_about = "Pin a version for the current directory or activate the pinned one.\n\nWith a version, it is written to .go-version in the current directory. Without one, the nearest .go-version (searched up to the filesystem root) is activated. The 'default' alias is not changed, so new shells keep the global version until 'gvm local' (or 'gvm use' without a version) is run in the project again."
+    )]
+    Local(LocalOption),
+
+    #[clap(
+        about = "Inspect or clear the release and build caches",
+        long_about = "Inspect or clear the release and build caches.\n\nThe cache directory holds the release cache written by 'gvm update' and the go-build cache (GOCACHE) of every version. 'gvm cache clear --build' keeps the release cache."
+    )]
+    Cache(CacheOption),
+
+    #[clap(
+        about = "Read or change the settings in ~/.gvm/config.toml",
+        long_about = "Read or change the settings in ~/.gvm/config.toml.\n\nAvailable settings: download_base, releases_url, default_jobs, cache_ttl_hours, list_stable_by_default and shared_gopath. Environment variables (GVM_DOWNLOAD_BASE, GVM_RELEASES_URL, GVM_INSTALL_JOBS, GVM_CACHE_MAX_AGE, GVM_LIST_STABLE_BY_DEFAULT and GVM_SHARED_GOPATH) take precedence over the file."
+    )]
+    Config(ConfigOption),
+
+    #[clap(
+        about = "Install a go shim that follows .go-version in every directory",
+        long_about = "Install a go shim that follows .go-version in every directory.\n\n'gvm shim install' writes ~/.gvm/shims/go, which runs 'gvm exec <version> -- go ...' with the version from the nearest .go-version file, or the active version. The init script puts ~/.gvm/shims in front of PATH once it exists; re-run 'gvm init' if your profile was set up by an older gvm."
+    )]
+    Shim(ShimOption),
+
+    #[clap(name = "__complete_versions", alias = "complete-versions", hide = true)]
+    CompleteVersions,
+
+    #[clap(name = "__complete_aliases", alias = "complete-aliases", hide = true)]
+    CompleteAliases,
 }
 
 #[derive(Parser, Debug, Clone)]
 struct InstallOption {
-    #[clap(value_parser, index = 1)]
-    version: String,
+    #[clap(
+        value_parser,
+        index = 1,
+        required_unless_present = "from_gomod",
+        value_name = "VERSION"
+    )]
+    versions: Vec<String>,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["versions", "from_file"],
+        help = "Install the version required by the nearest go.mod"
+    )]
+    from_gomod: bool,
+
+    #[clap(
+        long,
+        value_name = "N",
+        help = "Install up to N versions at the same time [default: 2, see 'gvm config']"
+    )]
+    jobs: Option<usize>,
 
     #[clap(long, alias = "use")]
     use_version: bool,
+
+    #[clap(
+        long,
+        requires = "use_version",
+        help = "Activate without repointing the 'default' alias"
+    )]
+    no_activate_default: bool,
+
+    #[clap(
+        long,
+        value_name = "SHA256",
+        alias = "sha256",
+        help = "Verify the archive against this SHA-256 digest"
+    )]
+    checksum: Option<String>,
+
+    #[clap(
+        long,
+        help = "Verify the archive against the .sha256 file published next to it"
+    )]
+    verify_sidecar: bool,
+
+    #[clap(
+        long,
+        alias = "no-cache",
+        help = "Update the release cache before installing"
+    )]
+    refresh: bool,
+
+    #[clap(
+        long,
+        conflicts_with = "refresh",
+        help = "Update the release cache first if it is stale (see GVM_CACHE_MAX_AGE)"
+    )]
+    auto_update: bool,
+
+    #[clap(
+        long,
+        value_parser = GO_OSES,
+        conflicts_with = "use_version",
+        help = "Install the toolchain for this OS instead of the host's"
+    )]
+    goos: Option<String>,
+
+    #[clap(
+        long,
+        value_parser = GO_ARCHES,
+        conflicts_with = "use_version",
+        help = "Install the toolchain for this architecture instead of the host's"
+    )]
+    goarch: Option<String>,
+
+    #[clap(
+        long,
+        value_name = "N",
+        default_value_t = config::NETWORK_RETRIES,
+        help = "Retry failed network requests N times"
+    )]
+    retries: u32,
+
+    #[clap(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["verify_sidecar", "refresh", "auto_update", "goos", "goarch"],
+        help = "Install from a local release archive instead of downloading it"
+    )]
+    from_file: Option<PathBuf>,
+
+    #[clap(long, help = "Reinstall versions that are already installed")]
+    force: bool,
+
+    #[clap(
+        long,
+        conflicts_with = "from_file",
+        help = "Download the archive even if a verified copy is cached"
+    )]
+    no_archive_cache: bool,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["from_file", "refresh", "auto_update"],
+        help = "Only print what would be downloaded and where it would be installed"
+    )]
+    dry_run: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct SelfUpdateOption {
+    #[clap(long, help = "Only report whether a newer gvm release exists")]
+    check: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct CacheOption {
+    #[clap(subcommand)]
+    action: CacheAction,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ConfigOption {
+    #[clap(subcommand)]
+    action: ConfigAction,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ShimOption {
+    #[clap(subcommand)]
+    action: ShimAction,
 }
 
 #[derive(Parser, Debug, Clone)]
 struct RemoveOption {
-    #[clap(value_parser, index = 1)]
-    version: String,
+    #[clap(value_parser, index = 1, required_unless_present = "all")]
+    versions: Vec<String>,
+
+    #[clap(
+        long,
+        conflicts_with = "versions",
+        help = "Remove every installed version except the active one"
+    )]
+    all: bool,
+
+    #[clap(long, help = "Also deactivate and remove the active version")]
+    force: bool,
+
+    #[clap(
+        long,
+        help = "Keep aliases pointing to a removed version instead of removing them"
+    )]
+    keep_aliases: bool,
+
+    #[clap(long, short, help = "Remove without asking for confirmation")]
+    yes: bool,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -91,12 +412,64 @@ struct AliasOption {
 
     #[clap(value_parser, index = 2)]
     target: Option<String>,
+
+    #[clap(
+        long,
+        requires = "target",
+        help = "Repoint 'default' to the target and activate it atomically"
+    )]
+    force_default_switch: bool,
+
+    #[clap(
+        long,
+        requires = "target",
+        conflicts_with = "force_default_switch",
+        help = "Exchange the targets of two existing aliases"
+    )]
+    swap: bool,
+
+    #[clap(
+        long,
+        value_name = "ALIAS",
+        conflicts_with_all = ["target", "force_default_switch", "swap"],
+        help = "Point the alias at another alias instead of a version"
+    )]
+    to: Option<String>,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["target", "force_default_switch", "swap", "to"],
+        help = "Print the aliases as JSON (with 'list')"
+    )]
+    json: bool,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["force_default_switch", "swap", "json"],
+        help = "Repoint the alias if it already exists"
+    )]
+    force: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct RenameAliasOption {
+    #[clap(value_parser, index = 1)]
+    old: String,
+
+    #[clap(value_parser, index = 2)]
+    new: String,
+
+    #[clap(long, help = "Replace an existing alias with the new name")]
+    force: bool,
 }
 
 #[derive(Parser, Debug, Clone)]
 struct RemoveAliasOption {
     #[clap(value_parser, index = 1)]
     alias: String,
+
+    #[clap(long, short, help = "Remove without asking for confirmation")]
+    yes: bool,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -106,6 +479,22 @@ struct ListOption {
 
     #[clap(long)]
     stable: bool,
+
+    #[clap(long, help = "Print the versions as JSON")]
+    json: bool,
+
+    #[clap(
+        long,
+        help = "Annotate versions with a newer patch release from the release cache"
+    )]
+    outdated: bool,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["json", "outdated"],
+        help = "Print only the version names, one per line, for scripts"
+    )]
+    names_only: bool,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -113,66 +502,767 @@ struct ListRemoteOption {
     #[clap(value_parser, index = 1)]
     version: Option<String>,
 
-    #[clap(long)]
+    #[clap(long, help = "Include pre-releases (release candidates and betas)")]
+    all: bool,
+
+    #[clap(
+        long,
+        conflicts_with = "all",
+        help = "Show only stable versions (the default, kept for compatibility)"
+    )]
     stable: bool,
+
+    #[clap(
+        long,
+        value_name = "N",
+        visible_alias = "last",
+        conflicts_with = "oldest",
+        help = "Show only the N newest versions"
+    )]
+    newest: Option<usize>,
+
+    #[clap(long, value_name = "N", help = "Show only the N oldest versions")]
+    oldest: Option<usize>,
+
+    #[clap(long, help = "Update the release cache before listing")]
+    refresh: bool,
+
+    #[clap(
+        long,
+        conflicts_with = "refresh",
+        help = "Update the release cache first if it is stale (see GVM_CACHE_MAX_AGE)"
+    )]
+    auto_update: bool,
+
+    #[clap(long, help = "Print the versions as JSON")]
+    json: bool,
+
+    #[clap(
+        long,
+        conflicts_with = "json",
+        help = "Print one line per minor version with its latest patch"
+    )]
+    minor: bool,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["not_installed", "minor"],
+        help = "Show only the versions that are installed"
+    )]
+    installed: bool,
+
+    #[clap(
+        long,
+        conflicts_with = "minor",
+        help = "Show only the versions that are not installed"
+    )]
+    not_installed: bool,
+
+    #[clap(
+        long,
+        alias = "goos",
+        value_parser = GO_OSES,
+        help = "List the releases published for this OS instead of the host's"
+    )]
+    os: Option<String>,
+
+    #[clap(
+        long,
+        alias = "goarch",
+        value_parser = GO_ARCHES,
+        help = "List the releases published for this architecture instead of the host's"
+    )]
+    arch: Option<String>,
+
+    #[clap(
+        long,
+        value_name = "N",
+        default_value_t = config::NETWORK_RETRIES,
+        help = "Retry failed network requests N times"
+    )]
+    retries: u32,
 }
 
 #[derive(Parser, Debug, Clone)]
 struct UseOption {
     #[clap(value_parser, index = 1)]
-    version: String,
+    version: Option<String>,
+
+    #[clap(long, help = "Activate without repointing the 'default' alias")]
+    no_activate_default: bool,
+
+    #[clap(
+        long,
+        conflicts_with = "no_activate_default",
+        help = "Print the environment for eval instead of activating"
+    )]
+    print: bool,
+
+    #[clap(long, value_enum, default_value = "bash", requires = "print")]
+    shell: ShellSyntax,
+
+    #[clap(
+        long,
+        requires = "version",
+        conflicts_with_all = ["no_activate_default", "print"],
+        help = "Pin the version in .go-version in the current directory instead"
+    )]
+    local: bool,
+
+    #[clap(
+        long,
+        conflicts_with = "version",
+        help = "Use the version required by the nearest go.mod, ignoring .go-version"
+    )]
+    from_gomod: bool,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["version", "from_gomod", "local"],
+        help = "Switch back to the previously active version (same as 'gvm use -')"
+    )]
+    prev: bool,
 }
 
 #[derive(Parser, Debug, Clone)]
-struct UpdateOption {}
+struct UpdateOption {
+    #[clap(
+        long,
+        conflicts_with_all = ["prune_cache", "include_all"],
+        help = "Only report whether a newer stable release exists (exit code 10 if so)"
+    )]
+    check: bool,
+
+    #[clap(
+        long,
+        conflicts_with = "include_all",
+        help = "Drop releases older than the two most recent minor versions (or GVM_MIN_VERSION)"
+    )]
+    prune_cache: bool,
+
+    #[clap(
+        long,
+        help = "Keep every release in the cache, ignoring GVM_MIN_VERSION"
+    )]
+    include_all: bool,
+
+    #[clap(long, value_parser = GO_OSES, help = "Cache releases for this OS instead of the host's")]
+    goos: Option<String>,
+
+    #[clap(
+        long,
+        value_parser = GO_ARCHES,
+        help = "Cache releases for this architecture instead of the host's"
+    )]
+    goarch: Option<String>,
+
+    #[clap(
+        long,
+        value_name = "N",
+        default_value_t = config::NETWORK_RETRIES,
+        help = "Retry failed network requests N times"
+    )]
+    retries: u32,
+}
 
 #[derive(Parser, Debug, Clone)]
 struct CompletionsOption {
     shell: Shell,
+
+    #[clap(
+        long,
+        help = "Write the completions to the shell's conventional completion directory"
+    )]
+    install: bool,
+
+    #[clap(
+        long,
+        value_name = "PATH",
+        help = "Write the completions into this directory instead of printing them"
+    )]
+    dir: Option<PathBuf>,
 }
 
 #[derive(Parser, Debug, Clone)]
 struct InitOption {
     #[clap(value_parser, index = 1)]
     version: Option<String>,
+
+    #[clap(
+        long,
+        conflicts_with = "append_path",
+        help = "Put go binaries in front of PATH (default)"
+    )]
+    prepend_path: bool,
+
+    #[clap(long, help = "Put go binaries at the end of PATH")]
+    append_path: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct EnvOption {
+    #[clap(value_parser, index = 1)]
+    version: Option<String>,
+
+    #[clap(long, value_enum, help = "Output format [default: bash]")]
+    format: Option<EnvFormat>,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["shell", "format"],
+        help = "Same as --format json"
+    )]
+    json: bool,
+
+    #[clap(
+        long,
+        value_enum,
+        default_value = "bash",
+        conflicts_with = "format",
+        help = "Same as --format bash or --format fish"
+    )]
+    shell: ShellSyntax,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct FreezeOption {
+    #[clap(value_parser, index = 1, default_value = "gvm.lock")]
+    file: PathBuf,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct RestoreOption {
+    #[clap(value_parser, index = 1, default_value = "gvm.lock")]
+    file: PathBuf,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct DiffOption {
+    #[clap(value_parser, index = 1)]
+    first: String,
+
+    #[clap(value_parser, index = 2)]
+    second: String,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ExportEnvOption {
+    #[clap(value_parser, index = 1)]
+    version: String,
+
+    #[clap(long, value_enum, default_value = "bash")]
+    shell: ShellSyntax,
+
+    #[clap(long, short, help = "Write to this file instead of stdout")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct PruneOption {
+    #[clap(
+        long,
+        value_name = "N",
+        help = "Also keep the N newest versions of each minor version"
+    )]
+    keep: Option<usize>,
+
+    #[clap(
+        long,
+        value_name = "N",
+        help = "Also keep the N newest stable versions overall"
+    )]
+    keep_newest: Option<usize>,
+
+    #[clap(long, help = "Only list the versions that would be removed")]
+    dry_run: bool,
+
+    #[clap(long, short, help = "Remove without asking for confirmation")]
+    yes: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct HookOption {
+    #[clap(value_enum, index = 1)]
+    shell: HookShell,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct WhichOption {
+    #[clap(value_parser, index = 1)]
+    version: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct InfoOption {
+    #[clap(value_parser, index = 1)]
+    version: String,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ExecOption {
+    #[clap(value_parser)]
+    version: String,
+
+    #[clap(last = true, required = true, value_name = "COMMAND")]
+    command: Vec<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct LocalOption {
+    #[clap(value_parser, index = 1)]
+    version: Option<String>,
+}
+
+/// Maps the `--refresh` and `--auto-update` flags to a cache refresh policy.
+fn cache_refresh(refresh: bool, auto_update: bool) -> CacheRefresh {
+    if refresh {
+        CacheRefresh::Always
+    } else if auto_update {
+        CacheRefresh::IfStale
+    } else {
+        CacheRefresh::Hint
+    }
 }
 
 #[tokio::main]
-async fn main() -> Res<()> {
+async fn main() {
     let opts = Opts::parse();
+    if let Err(err) = run(opts).await {
+        gvm::report_error(err.as_ref());
+        process::exit(1);
+    }
+}
+
+/// Generates the completion script for a shell, see `add_dynamic_completions`.
+fn completion_script(shell: Shell) -> Res<String> {
+    // clap_complete joins subcommand paths with "__" in bash scripts and panics
+    // on names starting with it; the hidden helpers are never offered anyway,
+    // so they are generated under their old names
+    let mut cmd = Opts::command_for_update()
+        .mut_subcommand("__complete_versions", |sc| {
+            sc.name("complete-versions").alias(None)
+        })
+        .mut_subcommand("__complete_aliases", |sc| {
+            sc.name("complete-aliases").alias(None)
+        });
+    let name = cmd.get_name().to_string();
+    let mut script = Vec::new();
+    generate(shell, &mut cmd, name, &mut script);
+    add_dynamic_completions(shell, String::from_utf8(script)?)
+}
+
+/// Returns how much the log macros print for the given options.
+///
+/// Commands printing JSON are always quiet, so steps such as refreshing the
+/// release cache don't print into the JSON on stdout.
+fn verbosity(opts: &Opts) -> utils::Verbosity {
+    let json = match &opts.command {
+        Command::List(opt) => opt.json,
+        Command::ListRemote(opt) => opt.json,
+        Command::Alias(opt) => opt.json,
+        _ => false,
+    };
+    match (opts.quiet || json, opts.verbose) {
+        (true, _) => utils::Verbosity::Quiet,
+        (_, true) => utils::Verbosity::Verbose,
+        _ => utils::Verbosity::Normal,
+    }
+}
+
+async fn run(opts: Opts) -> Res<()> {
+    if let Some(Err(e)) = utils::get_gvm_root() {
+        return Err(e);
+    }
+    if opts.log_file {
+        utils::enable_log_file();
+    }
+    utils::set_verbosity(verbosity(&opts));
+
+    // --goos/--goarch of a subcommand take precedence over the global --assume-* options.
+    let (goos, goarch) = match &opts.command {
+        Command::Install(opt) => (opt.goos.clone(), opt.goarch.clone()),
+        Command::Update(opt) => (opt.goos.clone(), opt.goarch.clone()),
+        Command::ListRemote(opt) => (opt.os.clone(), opt.arch.clone()),
+        _ => (None, None),
+    };
+    utils::set_assumed_platform(goos.or(opts.assume_os), goarch.or(opts.assume_arch));
+
+    let retries = match &opts.command {
+        Command::Install(opt) => Some(opt.retries),
+        Command::Update(opt) => Some(opt.retries),
+        Command::ListRemote(opt) => Some(opt.retries),
+        _ => None,
+    };
+    if let Some(retries) = retries {
+        utils::set_network_retries(retries);
+    }
+    if matches!(&opts.command, Command::Install(opt) if opt.no_archive_cache) {
+        utils::disable_archive_cache();
+    }
 
-    Ok(match opts.command {
-        Command::Update(_opt) => {
-            update().await?;
+    match opts.command {
+        Command::Update(opt) => {
+            if opt.check {
+                let code = check_update().await?;
+                if code != 0 {
+                    process::exit(code);
+                }
+            } else {
+                update(opt.prune_cache, opt.include_all).await?;
+            }
         }
-        Command::Install(opt) => {
-            install(opt.version, opt.use_version).await?;
+        Command::Install(opt) if opt.versions.len() > 1 => {
+            if opt.use_version || opt.checksum.is_some() || opt.from_file.is_some() {
+                return Err(
+                    "--use, --checksum and --from-file can only be used with a single version."
+                        .into(),
+                );
+            }
+            if opt.dry_run {
+                install_dry_run(
+                    opt.versions,
+                    InstallOptions {
+                        verify_sidecar: opt.verify_sidecar,
+                        force: opt.force,
+                        ..InstallOptions::default()
+                    },
+                )
+                .await?;
+                return Ok(());
+            }
+            install_many(
+                opt.versions,
+                opt.jobs.unwrap_or_else(utils::get_install_jobs),
+                InstallOptions {
+                    verify_sidecar: opt.verify_sidecar,
+                    refresh: cache_refresh(opt.refresh, opt.auto_update),
+                    force: opt.force,
+                    ..InstallOptions::default()
+                },
+            )
+            .await?;
+        }
+        Command::Install(mut opt) => {
+            let version = if opt.from_gomod {
+                let project_version = utils::go_mod_version(&env::current_dir()?).await?;
+                gvm::info!(
+                    "Using version {} from the {} in {}.",
+                    project_version.version,
+                    project_version.source,
+                    project_version.path.display()
+                );
+                project_version.version
+            } else {
+                opt.versions.remove(0)
+            };
+            let options = InstallOptions {
+                activate: opt.use_version.then_some(!opt.no_activate_default),
+                checksum: opt.checksum,
+                verify_sidecar: opt.verify_sidecar,
+                refresh: cache_refresh(opt.refresh, opt.auto_update),
+                from_file: opt.from_file,
+                force: opt.force,
+            };
+            if opt.dry_run {
+                install_dry_run(vec![version], options).await?;
+                return Ok(());
+            }
+            install(version, options).await?;
         }
         Command::Remove(opt) => {
-            remove(opt.version).await?;
+            remove(opt.versions, opt.all, opt.force, opt.keep_aliases, opt.yes).await?;
         }
         Command::List(opt) => {
-            list(opt.version, opt.stable).await?;
+            list(
+                opt.version,
+                opt.stable,
+                opt.json,
+                opt.outdated,
+                opt.names_only,
+            )
+            .await?;
         }
         Command::ListRemote(opt) => {
-            list_remote(opt.version, opt.stable).await?;
+            list_remote(
+                opt.version,
+                opt.stable || (!opt.all && utils::list_stable_by_default()),
+                opt.newest,
+                opt.oldest,
+                cache_refresh(opt.refresh, opt.auto_update),
+                if opt.json {
+                    ListRemoteFormat::Json
+                } else if opt.minor {
+                    ListRemoteFormat::Minor
+                } else {
+                    ListRemoteFormat::Text
+                },
+                if opt.installed {
+                    Some(true)
+                } else if opt.not_installed {
+                    Some(false)
+                } else {
+                    None
+                },
+            )
+            .await?;
         }
         Command::Alias(opt) => {
-            alias(opt.alias, opt.target).await?;
+            alias(
+                opt.alias,
+                opt.target,
+                AliasOptions {
+                    force_default_switch: opt.force_default_switch,
+                    swap: opt.swap,
+                    to_alias: opt.to,
+                    json: opt.json,
+                    force: opt.force,
+                },
+            )
+            .await?;
         }
         Command::RemoveAlias(opt) => {
-            remove_alias(opt.alias).await?;
+            remove_alias(opt.alias, opt.yes).await?;
+        }
+        Command::RenameAlias(opt) => {
+            rename_alias(opt.old, opt.new, opt.force).await?;
         }
         Command::Use(opt) => {
-            use_version(opt.version).await?;
+            let print = opt.print.then_some(opt.shell);
+            use_version(
+                if opt.prev {
+                    Some("-".to_string())
+                } else {
+                    opt.version
+                },
+                opt.no_activate_default,
+                print,
+                opt.local,
+                opt.from_gomod,
+            )
+            .await?;
         }
         Command::Completions(opt) => {
-            let mut cmd = Opts::command_for_update();
-            let name = cmd.get_name().to_string();
-            generate(opt.shell, &mut cmd, name, &mut std::io::stdout())
+            let script = completion_script(opt.shell)?;
+            if opt.install || opt.dir.is_some() {
+                install_completions(opt.shell, &script, opt.dir)?;
+            } else {
+                print!("{}", script);
+            }
+        }
+        Command::Init(opt) => {
+            let path_position = if opt.append_path {
+                Some(PathPosition::Append)
+            } else if opt.prepend_path {
+                Some(PathPosition::Prepend)
+            } else {
+                None
+            };
+            init(path_position).await?;
+        }
+        Command::Env(opt) => {
+            let format = opt.format.unwrap_or(if opt.json {
+                EnvFormat::Json
+            } else {
+                opt.shell.into()
+            });
+            env(opt.version, format).await?;
+        }
+        Command::Freeze(opt) => {
+            freeze(opt.file).await?;
+        }
+        Command::Restore(opt) => {
+            restore(opt.file).await?;
+        }
+        Command::Diff(opt) => {
+            diff(opt.first, opt.second).await?;
+        }
+        Command::Rehash => {
+            rehash().await?;
+        }
+        Command::ExportEnv(opt) => {
+            export_env(opt.version, opt.shell, opt.output).await?;
+        }
+        Command::Prune(opt) => {
+            prune(opt.keep, opt.keep_newest, opt.dry_run, opt.yes).await?;
+        }
+        Command::Hook(opt) => {
+            hook(opt.shell).await?;
+        }
+        Command::Which(opt) => {
+            which(opt.version).await?;
+        }
+        Command::Current => {
+            if !current(opts.quiet).await? {
+                process::exit(1);
+            }
+        }
+        Command::Info(opt) => {
+            info(opt.version).await?;
+        }
+        Command::Exec(opt) => {
+            let code = exec(opt.version, opt.command).await?;
+            if code != 0 {
+                process::exit(code);
+            }
+        }
+        Command::Doctor => {
+            doctor().await?;
+        }
+        Command::SelfUpdate(opt) => {
+            self_update(opt.check).await?;
         }
-        Command::Init(_opt) => {
-            init().await?;
+        Command::Cache(opt) => {
+            cache(opt.action).await?;
         }
-    })
+        Command::Config(opt) => {
+            configure(opt.action).await?;
+        }
+        Command::Shim(opt) => {
+            shim(opt.action).await?;
+        }
+        Command::Local(opt) => {
+            local(opt.version).await?;
+        }
+        Command::CompleteVersions => {
+            complete_versions().await?;
+        }
+        Command::CompleteAliases => {
+            complete_aliases().await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completion_scripts_complete_versions_dynamically() {
+        for shell in [Shell::Bash, Shell::Zsh] {
+            let script = completion_script(shell).unwrap();
+            assert!(script.contains("_gvm_clap() {"), "{}", shell);
+            assert!(script.contains("\n_gvm() {"), "{}", shell);
+            assert!(script.contains("_gvm_clap \"$@\""), "{}", shell);
+            assert!(script.contains("gvm __complete_versions"), "{}", shell);
+            assert!(script.contains("gvm __complete_aliases"), "{}", shell);
+        }
+
+        let zsh = completion_script(Shell::Zsh).unwrap();
+        let wrapper = zsh.find("\n_gvm() {").unwrap();
+        let dispatch = zsh.rfind("if [ \"$funcstack[1]\" = \"_gvm\" ]").unwrap();
+        assert!(
+            wrapper < dispatch,
+            "the zsh dispatch must follow the wrapper"
+        );
+
+        let fish = completion_script(Shell::Fish).unwrap();
+        assert!(fish.contains("-a \"(gvm __complete_versions)\""));
+        assert!(fish.contains("-a \"(gvm __complete_aliases)\""));
+    }
+
+    #[test]
+    fn completion_helpers_keep_their_old_names() {
+        for args in [["gvm", "__complete_versions"], ["gvm", "complete-versions"]] {
+            let opts = Opts::try_parse_from(args).unwrap();
+            assert!(matches!(opts.command, Command::CompleteVersions));
+        }
+        for args in [["gvm", "__complete_aliases"], ["gvm", "complete-aliases"]] {
+            let opts = Opts::try_parse_from(args).unwrap();
+            assert!(matches!(opts.command, Command::CompleteAliases));
+        }
+    }
+
+    #[test]
+    fn force_default_switch_requires_a_target() {
+        assert!(
+            Opts::try_parse_from(["gvm", "alias", "default", "--force-default-switch"]).is_err()
+        );
+
+        let opts =
+            Opts::try_parse_from(["gvm", "alias", "default", "1.22", "--force-default-switch"])
+                .unwrap();
+        let Command::Alias(opt) = opts.command else {
+            panic!("expected the alias command");
+        };
+        assert!(opt.force_default_switch);
+        assert_eq!(opt.target.as_deref(), Some("1.22"));
+    }
+
+    #[test]
+    fn list_remote_selects_from_one_end_only() {
+        let opts = Opts::try_parse_from(["gvm", "list-remote", "--oldest", "3"]).unwrap();
+        let Command::ListRemote(opt) = opts.command else {
+            panic!("expected the list-remote command");
+        };
+        assert_eq!((opt.newest, opt.oldest), (None, Some(3)));
+
+        assert!(
+            Opts::try_parse_from(["gvm", "list-remote", "--newest", "2", "--oldest", "3"]).is_err()
+        );
+    }
+
+    #[test]
+    fn list_remote_last_is_an_alias_of_newest() {
+        let opts = Opts::try_parse_from(["gvm", "list-remote", "--last", "5", "--json"]).unwrap();
+        let Command::ListRemote(opt) = opts.command else {
+            panic!("expected the list-remote command");
+        };
+        assert_eq!(opt.newest, Some(5));
+        assert!(opt.json);
+    }
+
+    #[test]
+    fn list_remote_keeps_stable_as_a_synonym_of_the_default() {
+        let parse = |args: &[&str]| match Opts::try_parse_from(args).unwrap().command {
+            Command::ListRemote(opt) => (opt.stable, opt.all),
+            _ => panic!("expected the list-remote command"),
+        };
+
+        assert_eq!(parse(&["gvm", "list-remote"]), (false, false));
+        assert_eq!(parse(&["gvm", "list-remote", "--stable"]), (true, false));
+        assert_eq!(
+            parse(&["gvm", "list-remote", "--all", "1.22.*"]),
+            (false, true)
+        );
+        assert!(Opts::try_parse_from(["gvm", "list-remote", "--all", "--stable"]).is_err());
+    }
+
+    fn verbosity_of(args: &[&str]) -> utils::Verbosity {
+        verbosity(&Opts::try_parse_from(args).unwrap())
+    }
+
+    #[test]
+    fn verbosity_follows_the_global_flags() {
+        use utils::Verbosity::{Normal, Quiet, Verbose};
+
+        assert_eq!(verbosity_of(&["gvm", "list"]), Normal);
+        assert_eq!(verbosity_of(&["gvm", "--quiet", "list"]), Quiet);
+        assert_eq!(verbosity_of(&["gvm", "list", "-q"]), Quiet);
+        assert_eq!(verbosity_of(&["gvm", "--verbose", "list"]), Verbose);
+        assert!(Opts::try_parse_from(["gvm", "--quiet", "--verbose", "list"]).is_err());
+    }
+
+    #[test]
+    fn json_output_is_quiet() {
+        use utils::Verbosity::{Normal, Quiet};
+
+        assert_eq!(verbosity_of(&["gvm", "list-remote"]), Normal);
+        assert_eq!(verbosity_of(&["gvm", "list-remote", "--json"]), Quiet);
+        assert_eq!(
+            verbosity_of(&["gvm", "list-remote", "--json", "--refresh"]),
+            Quiet
+        );
+        assert_eq!(
+            verbosity_of(&["gvm", "--verbose", "list-remote", "--json", "--auto-update"]),
+            Quiet
+        );
+        assert_eq!(
+            verbosity_of(&["gvm", "list-remote", "--os", "darwin", "--json"]),
+            Quiet
+        );
+        assert_eq!(verbosity_of(&["gvm", "list", "--json"]), Quiet);
+        assert_eq!(verbosity_of(&["gvm", "alias", "list", "--json"]), Quiet);
+    }
 }
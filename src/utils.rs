@@ -1,691 +1,3644 @@
-use futures_lite::stream::StreamExt;
+use futures_lite::{io::AsyncWriteExt, stream::StreamExt};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::{
     cmp::Ordering,
     env,
     error::Error,
-    io,
+    ffi::OsStr,
+    fmt, fs,
+    io::{self, IsTerminal, Write},
     path::{Path, PathBuf},
+    process,
+    str::FromStr,
+    sync::{
+        atomic::{self, AtomicUsize},
+        Mutex, OnceLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 #[cfg(unix)]
 use std::os::unix::fs as unix_fs;
 
-use crate::{config, error, info, success, Res};
+use crate::{config, error, info, success, warning, Res};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct FilteredRelease {
     pub version: String,
     pub url: String,
+    /// SHA-256 digest of the archive as published by go.dev. Caches written
+    /// before checksums were recorded don't have it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Go operating system name the archive is built for (e.g. "linux").
+    /// Filled in by `parse_release_cache` for caches written before it was
+    /// recorded.
+    #[serde(default)]
+    pub os: String,
+    /// Go architecture name the archive is built for (e.g. "amd64").
+    #[serde(default)]
+    pub arch: String,
 }
 
-/// Returns `true` if the version is stable. It strips the "go" prefix and
-/// considers a version unstable if it contains "rc", "beta", or "alpha".
-pub fn is_stable_version(version: &str) -> bool {
-    let trimmed = version.strip_prefix("go").unwrap_or(version);
-    !(trimmed.contains("rc") || trimmed.contains("beta") || trimmed.contains("alpha"))
+/// Position at which the Go binary directories are added to PATH.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathPosition {
+    /// Go binaries win over other binaries with the same name.
+    Prepend,
+    /// Other binaries with the same name win over Go binaries.
+    Append,
 }
 
-/// Ensures that a given version string is prefixed with "go".
-///
-/// This function takes a version string and checks if it starts with "go".
-/// If it doesn't, it prepends "go" to the version string.
+impl PathPosition {
+    /// Parses a position as written in `GVM_PATH_POSITION`, ignoring case and
+    /// surrounding whitespace.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "prepend" => Some(PathPosition::Prepend),
+            "append" => Some(PathPosition::Append),
+            _ => None,
+        }
+    }
+
+    /// Reads the position from `GVM_PATH_POSITION` (`prepend` or `append`).
+    ///
+    /// Falls back to `Prepend` if the variable is unset or empty, and warns
+    /// about any other value instead of silently prepending.
+    pub fn from_env() -> Self {
+        let value = match env::var("GVM_PATH_POSITION") {
+            Ok(value) if !value.trim().is_empty() => value,
+            _ => return PathPosition::Prepend,
+        };
+        PathPosition::parse(&value).unwrap_or_else(|| {
+            warning!(
+                "Ignoring GVM_PATH_POSITION={:?}, expected 'prepend' or 'append'. Prepending to PATH.",
+                value
+            );
+            PathPosition::Prepend
+        })
+    }
+}
+
+/// How a command reading the release cache deals with a stale cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheRefresh {
+    /// Print a hint to run `gvm update` if the cache is stale.
+    #[default]
+    Hint,
+    /// Update the cache first if it is stale or missing.
+    IfStale,
+    /// Always update the cache first.
+    Always,
+}
+
+/// Reads a setting from an environment variable, then from `config.toml`.
 ///
 /// # Parameters
 ///
-/// * `version`: A String representing the version number, which may or may not start with "go".
-///
-/// # Returns
-///
-/// A String that always starts with "go", followed by the version number.
-pub fn get_real_version(version: String) -> String {
-    let real_version = if version.starts_with("go") {
-        version
-    } else {
-        format!("go{}", version)
-    };
+/// * `name`: The environment variable, which wins if it holds a valid value.
+/// * `configured`: The value from `config.toml`, if set.
+/// * `default`: The value used if neither is set.
+fn setting<T: FromStr>(name: &str, configured: Option<T>, default: T) -> T {
+    setting_from(env::var(name).ok().as_deref(), configured, default)
+}
 
-    real_version
+/// Picks a setting from the value of its environment variable, see `setting`.
+///
+/// An unset or unparsable `env_value` falls back to `configured`, then to
+/// `default`.
+fn setting_from<T: FromStr>(env_value: Option<&str>, configured: Option<T>, default: T) -> T {
+    env_value
+        .and_then(|v| v.trim().parse::<T>().ok())
+        .or(configured)
+        .unwrap_or(default)
 }
 
-/// Parses a version string into its numeric base parts and an optional suffix.
-/// For example:
-///   - "go1.24.0"  => (vec![1, 24, 0], "")
-///   - "go1.24rc1" => (vec![1, 24], "rc1")
-fn parse_version_parts(version: &str) -> (Vec<u32>, String) {
-    // Regex to capture the numeric part and the rest.
-    // Captures: 1) the numeric part (e.g. "1.24" or "1.24.0") and 2) any trailing characters.
-    let re = Regex::new(r"^go(\d+(?:\.\d+)*)(.*)$").unwrap();
-    if let Some(caps) = re.captures(version) {
-        let base = &caps[1];
-        let suffix = &caps[2];
-        let base_parts: Vec<u32> = base
-            .split('.')
-            .filter_map(|s| s.parse::<u32>().ok())
-            .collect();
-        return (base_parts, suffix.to_string());
-    }
-    (vec![], String::new())
+/// Returns the age after which the release cache is considered stale.
+///
+/// Reads the number of hours from `GVM_CACHE_MAX_AGE`, then `cache_ttl_hours`
+/// in `config.toml`, and falls back to `config::RELEASE_CACHE_MAX_AGE_HOURS`.
+pub fn get_release_cache_max_age() -> Duration {
+    let hours = setting(
+        "GVM_CACHE_MAX_AGE",
+        config::load().cache_ttl_hours,
+        config::RELEASE_CACHE_MAX_AGE_HOURS,
+    );
+    Duration::from_secs(hours * 60 * 60)
 }
 
-/// Custom comparator for version strings.
-/// 1. Compares the numeric parts.
-/// 2. If the base versions are equal, then:
-///    - If one version is unstable (non‑empty suffix) and the other is stable,
-///      the unstable version comes first.
-///    - If both are unstable, compare the suffixes lexicographically.
-pub fn cmp_versions(a: &str, b: &str) -> Ordering {
-    let (base_a, suffix_a) = parse_version_parts(a);
-    let (base_b, suffix_b) = parse_version_parts(b);
+/// Returns how many versions `gvm install` installs at the same time.
+///
+/// Reads `GVM_INSTALL_JOBS`, then `default_jobs` in `config.toml`, and falls
+/// back to `config::INSTALL_JOBS`. `--jobs` overrides all of them.
+pub fn get_install_jobs() -> usize {
+    setting(
+        "GVM_INSTALL_JOBS",
+        config::load().default_jobs,
+        config::INSTALL_JOBS,
+    )
+}
 
-    // First compare the numeric (base) versions.
-    match base_a.cmp(&base_b) {
-        Ordering::Equal => {
-            // For the same base version, we want unstable versions (non‑empty suffix) first.
-            match (suffix_a.is_empty(), suffix_b.is_empty()) {
-                (false, true) => Ordering::Less,    // a is unstable, b is stable
-                (true, false) => Ordering::Greater, // a is stable, b is unstable
-                (true, true) => Ordering::Equal,    // both are stable
-                (false, false) => suffix_a.cmp(&suffix_b), // both unstable: sort lexically
-            }
-        }
-        ord => ord,
-    }
+/// Returns `true` if `gvm list-remote` hides pre-releases unless `--all` is given.
+///
+/// Reads `GVM_LIST_STABLE_BY_DEFAULT` (`true`/`false`), then
+/// `list_stable_by_default` in `config.toml`, and defaults to `true`.
+pub fn list_stable_by_default() -> bool {
+    setting(
+        "GVM_LIST_STABLE_BY_DEFAULT",
+        config::load().list_stable_by_default,
+        true,
+    )
 }
 
-/// Attempts to retrieve the user's home directory.
+/// Returns `true` if all versions share one GOPATH, see `get_gopath`.
 ///
-/// This function tries to get the value of the "HOME" environment variable,
-/// which typically represents the user's home directory on Unix-like systems.
+/// Reads `GVM_SHARED_GOPATH` (`true`/`false`), then `shared_gopath` in
+/// `config.toml`, and defaults to `false`.
+pub fn is_gopath_shared() -> bool {
+    setting("GVM_SHARED_GOPATH", config::load().shared_gopath, false)
+}
+
+/// Returns how long ago the release cache was last written.
 ///
 /// # Returns
 ///
-/// - `Some(String)` containing the path to the user's home directory if the
-///   "HOME" environment variable is set and valid.
-/// - `None` if the "HOME" environment variable is not set or cannot be read.
-fn get_home_dir() -> PathBuf {
-    dirs::home_dir().unwrap_or_else(|| error!("Cannot access HOME dir"))
+/// The age of the cache file, or `None` if it does not exist or its
+/// modification time cannot be read.
+pub fn get_release_cache_age() -> Option<Duration> {
+    let modified = fs::metadata(get_release_cache_file_path())
+        .and_then(|m| m.modified())
+        .ok()?;
+    SystemTime::now().duration_since(modified).ok()
 }
 
-/// Retrieves the path of the current shell.
+/// HTTP client shared by all network requests.
+static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+/// Returns the HTTP client shared by all network requests.
 ///
-/// This function retrieves the value of the "SHELL" environment variable,
-/// which represents the path to the currently active shell.
+/// The client honors `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` (and their
+/// lowercase variants), and fails fast if a server cannot be reached or a
+/// download stalls, instead of hanging indefinitely. No total timeout is set,
+/// as toolchain archives may legitimately take minutes to download.
 ///
-/// # Returns
+/// # Errors
 ///
-/// * `Some(String)`: If the "SHELL" environment variable is set, the function
-///   returns `Some` containing the path to the shell as a string.
-/// * `None`: If the "SHELL" environment variable is not set or is empty,
-///   the function returns `None`.
-fn get_shell() -> Option<String> {
-    env::var("SHELL").ok()
+/// Returns an error if the client cannot be built, e.g. because the TLS
+/// backend fails to initialize.
+pub fn http_client() -> Res<&'static reqwest::Client> {
+    if let Some(client) = HTTP_CLIENT.get() {
+        return Ok(client);
+    }
+
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("gvm/", env!("CARGO_PKG_VERSION")))
+        .connect_timeout(Duration::from_secs(config::HTTP_CONNECT_TIMEOUT_SECS))
+        .read_timeout(Duration::from_secs(config::HTTP_READ_TIMEOUT_SECS))
+        .build()?;
+    Ok(HTTP_CLIENT.get_or_init(|| client))
 }
 
-/// Determines the path to the shell configuration file based on the current shell.
+/// How much the log macros print.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only warnings and errors.
+    Quiet,
+    /// Every step, the default.
+    Normal,
+    /// Every step and extra detail printed with `debug!`.
+    Verbose,
+}
+
+impl Verbosity {
+    /// Returns `true` if a message needing `level` is printed at this verbosity.
+    pub fn shows(self, level: Verbosity) -> bool {
+        self >= level
+    }
+}
+
+/// The verbosity selected with `--quiet`/`--verbose`, if any.
+static VERBOSITY: OnceLock<Verbosity> = OnceLock::new();
+
+/// Sets how much the log macros print.
 ///
-/// This function attempts to identify the user's shell (bash or zsh) and returns
-/// the path to the appropriate configuration file (.bashrc or .zshrc).
+/// Only the first call has an effect.
 ///
-/// # Returns
+/// # Parameters
 ///
-/// A `String` containing the path to the shell configuration file.
+/// * `verbosity`: The verbosity for the rest of the process.
+pub fn set_verbosity(verbosity: Verbosity) {
+    let _ = VERBOSITY.set(verbosity);
+}
+
+/// Returns how much the log macros print, `Verbosity::Normal` unless set.
+pub fn get_verbosity() -> Verbosity {
+    VERBOSITY.get().copied().unwrap_or(Verbosity::Normal)
+}
+
+/// Prints a line of the log macros if the verbosity shows it.
 ///
-/// # Panics
+/// # Parameters
 ///
-/// This function will panic with an error message in the following cases:
-/// - If the shell is neither bash nor zsh.
-/// - If the SHELL environment variable cannot be retrieved.
-/// - If the home directory cannot be determined for the identified shell.
-pub fn get_shell_config_file_path() -> Result<PathBuf, String> {
-    match get_shell() {
-        Some(shell_path) => {
-            if shell_path.ends_with("/bash") {
-                let home = get_home_dir();
-                return Ok(home.join(".bashrc"));
-            } else if shell_path.ends_with("/zsh") {
-                let home = get_home_dir();
-                return Ok(home.join(".zshrc"));
-            } else {
-                return Err(format!("Unsupported shell: {}", shell_path));
-            }
-        }
-        None => {
-            return Err("Failed to retrieve SHELL environment variable".to_string());
-        }
+/// * `level`: The verbosity the line needs, see `Verbosity::shows`.
+/// * `line`: The formatted line.
+pub fn print_log(level: Verbosity, line: fmt::Arguments) {
+    #[cfg(test)]
+    if test_support::capture_log(level, line) {
+        return;
+    }
+    if get_verbosity().shows(level) {
+        println!("{}", line);
     }
 }
 
-/// Returns the base file path for the GVM (Go Version Manager) system.
-///
-/// This function determines the location of the base directory used by GVM.
-/// It first attempts to use the user's home directory. If available, it appends
-/// the GVM-specific path. If the home directory cannot be determined,
-/// it falls back to a temporary directory.
-///
-/// # Returns
+/// Whether log lines are mirrored into the log file, once decided.
+static LOG_ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// The open log file, or `None` if it could not be opened.
+static LOG_FILE: OnceLock<Option<Mutex<fs::File>>> = OnceLock::new();
+
+/// Turns on mirroring log lines into the log file (`--log-file`).
 ///
-/// A `String` representing the full path to the GVM base directory:
-/// - `~/.gvm` if the home directory is available
-/// - `/tmp/gvm` as a fallback if the home directory cannot be determined
-pub fn get_gvm_base_file_path() -> PathBuf {
-    let home = get_home_dir();
-    home.join(config::GVM_MAIN_PATH)
+/// Only the first call has an effect. Without it, the log file is written if
+/// `GVM_LOG` is set to anything but an empty string or `0`.
+pub fn enable_log_file() {
+    let _ = LOG_ENABLED.set(true);
 }
 
-/// Returns the path to the cache directory for the GVM (Go Version Manager) system.
+/// Returns `true` if log lines are mirrored into the log file.
+fn is_log_file_enabled() -> bool {
+    *LOG_ENABLED.get_or_init(|| env::var("GVM_LOG").is_ok_and(|v| !v.is_empty() && v != "0"))
+}
+
+/// Opens the log file for appending, rotating it first if it is too large.
+///
+/// The log directory is created on first use. A log file larger than
+/// `config::LOG_FILE_MAX_BYTES` is renamed to `gvm.log.1`, replacing the
+/// previous one.
+fn open_log_file() -> io::Result<fs::File> {
+    let log_dir = get_gvm_base_file_path().join(config::GVM_LOGS_PATH);
+    fs::create_dir_all(&log_dir)?;
+    let log_file = log_dir.join(config::LOG_FILE);
+    if fs::metadata(&log_file).is_ok_and(|m| m.len() > config::LOG_FILE_MAX_BYTES) {
+        fs::rename(&log_file, log_dir.join(format!("{}.1", config::LOG_FILE)))?;
+    }
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_file)
+}
+
+/// Formats a point in time as an ISO 8601 UTC timestamp, e.g.
+/// "2024-02-06T17:04:05Z".
+fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default() as i64;
+    let (days, secs_of_day) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+
+    // civil date from days since 1970-01-01, see
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+/// Mirrors a log line into the log file, if enabled.
 ///
-/// This function determines the location of the cache directory used by GVM.
-/// It first attempts to use the user's home directory. If available, it appends
-/// the GVM-specific cache path. If the home directory cannot be determined,
-/// it falls back to a temporary directory.
+/// Used by the log macros and `report_error`. Failing to open or write the
+/// log file is ignored, so logging never breaks the actual operation.
 ///
-/// # Returns
+/// # Parameters
 ///
-/// A `String` representing the full path to the cache directory:
-/// - `~/.gvm/cache` if the home directory is available
-/// - `/tmp/gvm/cache` as a fallback if the home directory cannot be determined
-pub fn get_cache_dir() -> PathBuf {
-    let gvm_path = get_gvm_base_file_path();
-    gvm_path.join(config::GVM_CACHE_PATH)
+/// * `level`: The kind of line, e.g. "INFO" or "ERROR".
+/// * `message`: The line without the console decoration.
+pub fn log_line(level: &str, message: &str) {
+    if !is_log_file_enabled() {
+        return;
+    }
+    let log_file = LOG_FILE.get_or_init(|| open_log_file().ok().map(Mutex::new));
+    if let Some(Ok(mut file)) = log_file.as_ref().map(Mutex::lock) {
+        let _ = writeln!(
+            file,
+            "{} {:<7} {}",
+            format_timestamp(SystemTime::now()),
+            level,
+            message
+        );
+    }
 }
 
-/// Returns the file path for the environment configuration used by GVM (Go Version Manager).
+/// Number of times a failed network request is retried, if overridden.
+static NETWORK_RETRIES: OnceLock<u32> = OnceLock::new();
+
+/// Overrides how often a failed network request is retried.
 ///
-/// This function determines the location of the environment file used by the GVM system.
-/// It first attempts to use the user's home directory. If available, it appends the GVM-specific path.
-/// If the home directory cannot be determined, it falls back to a temporary directory.
+/// Only the first call has an effect.
 ///
-/// # Returns
+/// # Parameters
 ///
-/// A `String` representing the full path to the environment file:
-/// - `~/.gvm/environment` if the home directory is available
-/// - `/tmp/gvm/environment` as a fallback if the home directory cannot be determined
-pub fn get_environment_file_path() -> PathBuf {
-    let gvm_path = get_gvm_base_file_path();
-    gvm_path.join(config::GVM_ENVIRONMENT_PATH)
+/// * `retries`: The number of retries after the first attempt.
+pub fn set_network_retries(retries: u32) {
+    let _ = NETWORK_RETRIES.set(retries);
 }
 
-/// Returns the file path for the version configuration.
-///
-/// This function determines the location of the version file used by the GVM (Go Version Manager) system.
-/// It first attempts to use the user's home directory. If available, it appends the GVM-specific path.
-/// If the home directory cannot be determined, it falls back to a temporary directory.
-///
-/// # Returns
+/// Returns how often a failed network request is retried.
+pub fn get_network_retries() -> u32 {
+    NETWORK_RETRIES
+        .get()
+        .copied()
+        .unwrap_or(config::NETWORK_RETRIES)
+}
+
+/// Whether cached release archives are reused, `false` after `--no-archive-cache`.
+static ARCHIVE_CACHE: OnceLock<bool> = OnceLock::new();
+
+/// Makes installs download release archives even if a verified copy is cached.
 ///
-/// A `String` representing the full path to the version file:
-/// - `~/.gvm/version` if the home directory is available
-/// - `/tmp/gvm/version` as a fallback if the home directory cannot be determined
-pub fn get_version_file_path() -> PathBuf {
-    let gvm_path = get_gvm_base_file_path();
-    gvm_path.join(config::GVM_VERSION_PATH)
+/// Only the first call has an effect.
+pub fn disable_archive_cache() {
+    let _ = ARCHIVE_CACHE.set(false);
 }
 
-/// Returns the file path for the package configuration.
+/// Returns `true` if installs reuse verified release archives from earlier downloads.
+pub fn is_archive_cache_enabled() -> bool {
+    ARCHIVE_CACHE.get().copied().unwrap_or(true)
+}
+
+/// Checks whether a failed network request is worth retrying.
 ///
-/// This function determines the location of the package file used by the GVM (Go Version Manager) system.
-/// It first attempts to use the user's home directory. If available, it appends the GVM-specific path.
-/// If the home directory cannot be determined, it falls back to a temporary directory.
+/// Connection failures, timeouts, interrupted transfers and server errors
+/// (5xx) are transient. Client errors such as 404 are not, and neither are
+/// local errors like a failed file write.
 ///
-/// # Returns
+/// # Parameters
 ///
-/// A `String` representing the full path to the package file:
-/// - `~/.gvm/package` if the home directory is available
-/// - `/tmp/gvm/package` as a fallback if the home directory cannot be determined
-pub fn get_package_file_path() -> PathBuf {
-    let gvm_path = get_gvm_base_file_path();
-    gvm_path.join(config::GVM_PACKAGE_PATH)
+/// * `err`: The error of the failed attempt.
+pub fn is_transient_error(err: &(dyn Error + Send + Sync + 'static)) -> bool {
+    if let Some(err) = err.downcast_ref::<reqwest::Error>() {
+        return match err.status() {
+            Some(status) => status.is_server_error(),
+            None => err.is_timeout() || err.is_connect() || err.is_request() || err.is_body(),
+        };
+    }
+    if let Some(err) = err.downcast_ref::<io::Error>() {
+        return matches!(
+            err.kind(),
+            io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::TimedOut
+                | io::ErrorKind::UnexpectedEof
+        );
+    }
+    false
 }
 
-/// Returns the file path for the archive configuration.
+/// Runs a network operation, retrying transient failures with exponential backoff.
 ///
-/// This function determines the location of the archive file used by the GVM (Go Version Manager) system.
-/// It first attempts to use the user's home directory. If available, it appends the GVM-specific path.
-/// If the home directory cannot be determined, it falls back to a temporary directory.
+/// The operation is attempted once plus `get_network_retries()` times. The
+/// delay starts at `config::RETRY_BACKOFF_MS` and doubles with every retry.
+///
+/// # Parameters
+///
+/// * `what`: A short description of the operation for the retry message.
+/// * `operation`: Creates the future performing one attempt.
 ///
 /// # Returns
 ///
-/// A `String` representing the full path to the archive file:
-/// - `~/.gvm/archive` if the home directory is available
-/// - `/tmp/gvm/archive` as a fallback if the home directory cannot be determined
-pub fn get_archive_file_path() -> PathBuf {
-    let gvm_path = get_gvm_base_file_path();
-    gvm_path.join(config::GVM_ARCHIVE_PATH)
+/// The result of the first successful attempt, or the error of the last one.
+/// Errors that are not transient are returned immediately.
+pub async fn with_retries<T, F, Fut>(what: &str, mut operation: F) -> Res<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Res<T>>,
+{
+    let retries = get_network_retries();
+    let mut attempt = 0;
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < retries && is_transient_error(err.as_ref()) => {
+                let delay = Duration::from_millis(config::RETRY_BACKOFF_MS << attempt.min(16));
+                attempt += 1;
+                warning!(
+                    "{} failed: {}. Retrying in {}s ({}/{}) ...",
+                    what,
+                    err,
+                    delay.as_secs(),
+                    attempt,
+                    retries
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Err(err) => return Err(err),
+        }
+    }
 }
 
-/// Returns the file path for the alias configuration.
+/// Parses a mirror URL read from an environment variable.
 ///
-/// This function determines the location of the alias file used by the GVM (Go Version Manager) system.
-/// It first attempts to use the user's home directory. If available, it appends the GVM-specific path.
-/// If the home directory cannot be determined, it falls back to a temporary directory.
+/// # Parameters
+///
+/// * `name`: The name of the variable, used in the error message.
+/// * `value`: The value of the variable, if set.
 ///
 /// # Returns
 ///
-/// A `String` representing the full path to the alias file:
-/// - `~/.gvm/alias` if the home directory is available
-/// - `/tmp/gvm/alias` as a fallback if the home directory cannot be determined
-pub fn get_alias_file_path() -> PathBuf {
-    let gvm_path = get_gvm_base_file_path();
-    gvm_path.join(config::GVM_ALIAS_PATH)
+/// `Ok(None)` if the variable is unset or empty, the parsed URL if it is a
+/// well-formed `http` or `https` URL, otherwise an error naming the variable.
+fn url_from_env(name: &str, value: Option<String>) -> Res<Option<reqwest::Url>> {
+    match value {
+        Some(value) if !value.trim().is_empty() => parse_url_setting(name, value.trim()).map(Some),
+        _ => Ok(None),
+    }
 }
 
-/// Lists all installed Go versions managed by GVM.
+/// Parses the value of a URL setting.
 ///
-/// This function scans the GVM version directory and collects the names of all
-/// subdirectories, which are assumed to represent installed Go versions.
+/// # Parameters
+///
+/// * `name`: The name of the setting, used in the error message.
+/// * `value`: The value to parse.
 ///
 /// # Returns
 ///
-/// Returns a `Result` containing:
-/// - `Ok(Vec<String>)`: A vector of strings, where each string is the name of an
-///   installed Go version.
-/// - `Err(Box<dyn Error + Send + Sync>)`: An error if the version directory
-///   cannot be read or if there are issues accessing the directory entries.
+/// The parsed URL if it is a well-formed `http` or `https` URL, otherwise an
+/// error naming the setting.
+pub fn parse_url_setting(name: &str, value: &str) -> Res<reqwest::Url> {
+    match reqwest::Url::parse(value) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") && url.has_host() => Ok(url),
+        Ok(_) => error!("{} must be an http(s) URL, got '{}'.", name, value),
+        Err(e) => error!("{} is not a valid URL '{}': {}", name, value, e),
+    }
+}
+
+/// Returns the base URL release archives are downloaded from.
+///
+/// Reads `GVM_DOWNLOAD_BASE` (e.g. `https://golang.google.cn/dl/`), then
+/// `download_base` in `config.toml`, and falls back to
+/// `config::DEFAULT_DOWNLOAD_BASE`. The returned URL always ends with a slash,
+/// so archive names can be joined onto it.
 ///
 /// # Errors
 ///
-/// This function will return an error if:
-/// - The version directory path cannot be retrieved or accessed.
-/// - There are issues reading the directory entries.
-/// - The directory entry names cannot be converted to strings.
-pub async fn list_installed_versions() -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
-    let version_path = get_version_file_path();
-    let mut versions = Vec::new();
-
-    let mut entries = async_fs::read_dir(&version_path).await?;
+/// Returns an error if `GVM_DOWNLOAD_BASE` or `download_base` is set but not a
+/// valid URL.
+pub fn get_download_base() -> Res<reqwest::Url> {
+    download_base_from(
+        env::var("GVM_DOWNLOAD_BASE").ok(),
+        config::load().download_base.as_deref(),
+    )
+}
 
-    while let Some(entry) = entries.try_next().await? {
-        if entry.file_type().await?.is_dir() {
-            let version_name = entry.file_name().into_string().unwrap_or_default();
-            versions.push(version_name);
-        }
+/// Returns the download base from the value of `GVM_DOWNLOAD_BASE` and the
+/// configured `download_base`, see `get_download_base`.
+fn download_base_from(env_value: Option<String>, configured: Option<&str>) -> Res<reqwest::Url> {
+    let mut base = match (url_from_env("GVM_DOWNLOAD_BASE", env_value)?, configured) {
+        (Some(base), _) => base,
+        (None, Some(base)) => parse_url_setting("download_base", base)?,
+        (None, None) => reqwest::Url::parse(config::DEFAULT_DOWNLOAD_BASE)?,
+    };
+    if !base.path().ends_with('/') {
+        let path = format!("{}/", base.path());
+        base.set_path(&path);
     }
-
-    Ok(versions)
+    Ok(base)
 }
 
-/// Lists all aliases defined in the GVM (Go Version Manager) system.
+/// Returns the URL the list of releases is fetched from.
 ///
-/// This function reads the alias directory and collects the names of all
-/// files, which are assumed to represent defined aliases.
-///
-/// # Returns
-///
-/// Returns a `Result` containing:
-/// - `Ok(Vec<String>)`: A vector of strings, where each string is the name of a
-///   defined alias.
-/// - `Err(Box<dyn Error + Send + Sync>)`: An error if the alias directory
-///   cannot be read or if there are issues accessing the directory entries.
+/// Reads `GVM_RELEASES_URL`, then `releases_url` in `config.toml`, and
+/// otherwise lists the releases of the download base, so setting the download
+/// base alone is enough for a full mirror.
 ///
 /// # Errors
 ///
-/// This function will return an error if:
-/// - The alias directory path cannot be retrieved or accessed.
-/// - There are issues reading the directory entries.
-/// - The directory entry names cannot be converted to strings.
-pub async fn list_aliases() -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
-    let alias_path = get_alias_file_path();
-    let mut aliases = Vec::new();
-
-    let mut entries = async_fs::read_dir(&alias_path).await?;
+/// Returns an error if one of the URLs is set but not a valid URL.
+pub fn get_releases_url() -> Res<reqwest::Url> {
+    releases_url_from(
+        env::var("GVM_RELEASES_URL").ok(),
+        config::load().releases_url.as_deref(),
+        get_download_base,
+    )
+}
 
-    while let Some(enty) = entries.try_next().await? {
-        let alias_name = enty.file_name().into_string().unwrap_or_default();
-        aliases.push(alias_name);
+/// Returns the releases URL from the value of `GVM_RELEASES_URL` and the
+/// configured `releases_url`, see `get_releases_url`. `download_base` is only
+/// called if neither is set.
+fn releases_url_from(
+    env_value: Option<String>,
+    configured: Option<&str>,
+    download_base: impl FnOnce() -> Res<reqwest::Url>,
+) -> Res<reqwest::Url> {
+    match (url_from_env("GVM_RELEASES_URL", env_value)?, configured) {
+        (Some(url), _) => Ok(url),
+        (None, Some(url)) => parse_url_setting("releases_url", url),
+        (None, None) => Ok(download_base()?.join(config::RELEASES_QUERY)?),
     }
-
-    Ok(aliases)
 }
 
-/// Reads the cache file and returns all cached releases, applying filtering criteria,
-/// and then sorts the list in ascending order (so that the latest version is at the bottom).
-///
-/// - `cache_file`: Path to the cached file.
-/// - `version_filter`: Optional filter for the version string (e.g. "1.21.1" for exact match
-///   or "1.21.*" for wildcard matching). If the provided filter does not start with "go", it will be prefixed.
-/// - `stable_only`: When `true`, only releases with stable version strings are returned.
-pub async fn list_cached_versions<P: AsRef<Path>>(
-    cache_file: P,
-    version_filter: Option<String>,
-    stable_only: bool,
-) -> Result<Vec<FilteredRelease>, Box<dyn Error + Send + Sync>> {
-    // Read and deserialize the cached JSON file.
-    let data = async_fs::read_to_string(&cache_file).await?;
-    let mut releases: Vec<FilteredRelease> = serde_json::from_str(&data)?;
-
-    // Ensure the version filter (if provided) starts with "go".
-    let version_filter = version_filter.map(|f| {
-        if f.starts_with("go") {
-            f
-        } else {
-            format!("go{}", f)
-        }
-    });
-
-    // Filter releases based on stability and version string.
-    releases.retain(|r: &FilteredRelease| {
-        if stable_only && !is_stable_version(&r.version) {
-            return false;
-        }
-        if let Some(ref filter) = version_filter {
-            if filter.ends_with('*') {
-                let prefix = &filter[..filter.len() - 1];
-                r.version.starts_with(prefix)
-            } else {
-                r.version == *filter
-            }
-        } else {
-            true
-        }
-    });
-
-    // Sort the filtered releases in ascending order using our custom comparator.
-    releases.sort_by(|a, b| cmp_versions(&a.version, &b.version));
-
-    Ok(releases)
-}
+/// Operating system and architecture assumed instead of the host's, if overridden.
+static ASSUMED_PLATFORM: OnceLock<(Option<String>, Option<String>)> = OnceLock::new();
 
-/// Removes an existing symbolic link if it exists.
+/// Overrides the operating system and architecture used to select release archives.
 ///
-/// This function checks if the given path exists and is a symbolic link.
-/// If so, it removes the symbolic link. This operation is only performed
-/// on Unix-like systems.
+/// This only affects which archives are cached and installed. Running a
+/// toolchain built for a foreign platform is not supported. Only the first call
+/// has an effect.
 ///
 /// # Parameters
 ///
-/// * `link`: A path-like object representing the potential symbolic link to be removed.
-///
-/// # Returns
-///
-/// Returns `io::Result<()>`:
-/// - `Ok(())` if the operation was successful (either the symlink was removed or didn't exist).
-/// - `Err(e)` if an error occurred during the file system operations.
-///
-/// # Platform-specific behavior
-///
-/// The actual removal of the symlink is only performed on Unix-like systems.
-pub async fn remove_existing_symlink<P: AsRef<Path>>(link: P) -> io::Result<()> {
-    let link = link.as_ref();
-    if link.exists() {
-        // Use symlink_metadata to avoid following the symlink.
-        let metadata = async_fs::symlink_metadata(link).await?;
-        if metadata.file_type().is_symlink() {
-            info!("Removing existing symlink: {}", link.display());
-            #[cfg(unix)]
-            {
-                async_fs::remove_file(link).await?;
-            }
-        }
-    }
-    Ok(())
+/// * `os`: The Go operating system name to assume (e.g., "linux").
+/// * `arch`: The Go architecture name to assume (e.g., "arm64").
+pub fn set_assumed_platform(os: Option<String>, arch: Option<String>) {
+    let _ = ASSUMED_PLATFORM.set((os, arch));
 }
 
-/// Creates a symbolic link.
+/// Maps a Rust operating system name to the name Go uses in release archives.
 ///
-/// This function creates a symbolic link pointing to the `original` path at the `link` location.
-/// It is only available on Unix-like systems.
+/// Unknown operating systems are passed through unchanged.
 ///
 /// # Parameters
 ///
-/// * `original`: A path-like object representing the target that the symlink will point to.
-/// * `link`: A path-like object representing the location where the symlink will be created.
+/// * `os`: The Rust operating system name (e.g., "macos").
 ///
 /// # Returns
 ///
-/// Returns `io::Result<()>`:
-/// - `Ok(())` if the symlink was successfully created.
-/// - `Err(e)` if an error occurred during the creation of the symlink.
-///
-/// # Platform-specific behavior
-///
-/// This function is only available on Unix-like systems. On other platforms, it will not be compiled.
-pub async fn create_symlink<P: AsRef<Path>, Q: AsRef<Path>>(
-    original: P,
-    link: Q,
-) -> io::Result<()> {
-    let link = link.as_ref();
-    let original = original.as_ref();
-    // Remove an existing symlink, if any.
-    match remove_existing_symlink(link).await {
-        Ok(()) => success!("Removed existing symlink: {}", link.display()),
-        Err(e) => error!("Error removing existing symlink: {}", e),
+/// The Go operating system name (e.g., "darwin").
+pub fn go_os_name(os: &str) -> &str {
+    match os {
+        "macos" => "darwin",
+        other => other,
     }
+}
 
-    #[cfg(unix)]
-    {
-        info!(
-            "Creating symlink: {} -> {}",
-            original.display(),
-            link.display()
-        );
-        unix_fs::symlink(original, link)
-    }
+/// Returns the Go operating system name of the host.
+pub fn get_host_os() -> String {
+    go_os_name(std::env::consts::OS).to_string()
 }
 
-/// Activates a specified Go version in the GVM (Go Version Manager) system.
+/// Returns the Go operating system name release archives are selected for.
+///
+/// This is the host OS unless overridden with `set_assumed_platform`.
+pub fn get_target_os() -> String {
+    ASSUMED_PLATFORM
+        .get()
+        .and_then(|(os, _)| os.clone())
+        .unwrap_or_else(get_host_os)
+}
+
+/// Maps a Rust architecture name to the name Go uses in release archives.
 ///
-/// This function performs the following tasks:
-/// 1. Verifies if the specified version exists.
-/// 2. Sets the version as active by writing it to the active file.
-/// 3. Creates a default alias for the active version.
+/// Rust uses the same name for both byte orders of ppc64 and mips, while Go
+/// publishes separate little-endian archives, so the byte order of the build
+/// decides. Unknown architectures are passed through unchanged.
 ///
 /// # Parameters
 ///
-/// * `version`: A String representing the Go version to activate. It can be with or without the "go" prefix.
+/// * `arch`: The Rust architecture name (e.g., "x86_64").
 ///
 /// # Returns
 ///
-/// * `Ok(())` if the version is successfully activated.
-/// * `Err(Box<dyn Error + Send + Sync>)` if an error occurs during the activation process,
-///   such as the specified version not being found or issues with file operations.
-///
-/// # Errors
-///
-/// This function will return an error if:
-/// * The specified version is not found in the GVM system.
-/// * There are issues writing to the active file.
-/// * There are problems creating the default alias symlink.
-pub async fn activate_version(version: String) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let real_version = get_real_version(version);
-    let version_path = get_version_file_path();
-    let release_dir = version_path.join(&real_version);
+/// The Go architecture name (e.g., "amd64").
+pub fn go_arch_name(arch: &str) -> &str {
+    go_arch_name_for_endian(arch, cfg!(target_endian = "little"))
+}
 
-    if !release_dir.is_dir() {
-        error!(
-            "Version '{}' not found. Use 'gvm list' to see available versions.",
-            real_version
-        );
+/// Maps a Rust architecture name to the Go name for the given byte order.
+///
+/// See `go_arch_name`.
+fn go_arch_name_for_endian(arch: &str, little_endian: bool) -> &str {
+    match (arch, little_endian) {
+        ("x86_64", _) => "amd64",
+        ("x86", _) => "386",
+        ("aarch64", _) => "arm64",
+        ("arm", _) => "armv6l",
+        ("loongarch64", _) => "loong64",
+        ("powerpc64", true) => "ppc64le",
+        ("powerpc64", false) => "ppc64",
+        ("mips64", true) => "mips64le",
+        ("mips", true) => "mipsle",
+        (other, _) => other,
     }
+}
 
-    info!("Activating version '{}' ...", real_version);
-    let active_path = version_path.join("active");
-
-    match async_fs::write(active_path, &real_version).await {
-        Ok(_) => info!("Version '{}' activated.", real_version),
-        Err(e) => error!("Error writing to active file: {}", e),
-    }
+/// Returns the Go architecture name of the host.
+pub fn get_host_arch() -> String {
+    go_arch_name(std::env::consts::ARCH).to_string()
+}
 
-    info!("Create default alias for version '{}' ...", real_version);
-    let alias_path = get_alias_file_path();
-    let alias_file_path = alias_path.join("default");
-    match create_symlink(&release_dir, alias_file_path).await {
-        Ok(()) => success!("Default alias for version '{}' created.", real_version),
-        Err(e) => error!(
-            "Error creating default alias for version '{}': {}",
-            real_version, e
-        ),
-    }
+/// Returns the Go architecture name release archives are selected for.
+///
+/// This is the host architecture unless overridden with `set_assumed_platform`.
+pub fn get_target_arch() -> String {
+    ASSUMED_PLATFORM
+        .get()
+        .and_then(|(_, arch)| arch.clone())
+        .unwrap_or_else(get_host_arch)
+}
 
-    info!("Create build cache for version '{}' ...", real_version);
-    let cache_dir = get_cache_dir();
-    let version_build_cache_dir = cache_dir.join(&real_version).join("go-build");
-    match async_fs::create_dir_all(&version_build_cache_dir).await {
-        Ok(_) => success!("Build cache for version '{}' created.", real_version),
-        Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => {
-            info!("Build cache for version '{}' already exists.", real_version)
-        }
-        Err(e) => error!(
-            "Error creating build cache for version '{}': {}",
-            real_version, e
-        ),
-    }
+/// Returns `true` if release archives are selected for a platform other than the host's.
+pub fn is_foreign_platform() -> bool {
+    get_target_os() != get_host_os() || get_target_arch() != get_host_arch()
+}
 
-    info!("Create go package path for version '{}' ...", real_version);
-    let package_path = get_package_file_path();
-    let version_package_path = package_path.join(&real_version).join("bin");
-    match async_fs::create_dir_all(&version_package_path).await {
-        Ok(_) => success!("Go package path for version '{}' created.", real_version),
-        Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => {
-            info!(
-                "Go package path for version '{}' already exists.",
-                real_version
-            )
-        }
-        Err(e) => error!(
-            "Error creating go package path for version '{}': {}",
-            real_version, e
-        ),
+/// Returns the name of the directory a version is installed to.
+///
+/// Toolchains for a foreign platform get the platform appended, e.g.
+/// "go1.22.0.darwin-arm64", so they never collide with the host toolchain.
+pub fn get_install_dir_name(version: &str) -> String {
+    if is_foreign_platform() {
+        format!("{}.{}-{}", version, get_target_os(), get_target_arch())
+    } else {
+        version.to_string()
     }
+}
 
-    init_go_environment(Some(real_version.clone())).await?;
-
-    success!(
-        "Go version '{}' activated successfully. Please reload profile.",
-        real_version
-    );
+/// Returns `true` if the release archive was built for the target os and arch.
+pub fn is_target_release(release: &FilteredRelease) -> bool {
+    release.os == get_target_os() && release.arch == get_target_arch()
+}
 
-    Ok(())
+/// Reads the os and arch from a release archive URL.
+///
+/// Go archive names follow the pattern `go<version>.<os>-<arch>.tar.gz`.
+///
+/// # Returns
+///
+/// The os and arch, or `None` if the archive name doesn't follow the pattern.
+fn archive_platform(url: &str) -> Option<(String, String)> {
+    let name = url.rsplit('/').next()?;
+    let (_, platform) = name.strip_suffix(".tar.gz")?.rsplit_once('.')?;
+    let (os, arch) = platform.split_once('-')?;
+    Some((os.to_string(), arch.to_string()))
 }
 
-/// Initializes the Go environment for a specified version.
+/// Parses the content of a release cache written by `gvm update`.
 ///
-/// This function sets up the necessary environment variables for a given Go version,
-/// including GOROOT, GOPATH, GOCACHE, and updates the PATH.
+/// Caches written before the platform was recorded get it from the archive
+/// name, or "linux"/"amd64" if the name doesn't tell, so they keep loading.
 ///
 /// # Parameters
 ///
-/// * `version`: An `Option<String>` representing the Go version to initialize.
-///              If `Some`, it should contain the version string (e.g., "go1.16.5").
-///              If `None`, an error message will be logged.
+/// * `data`: The JSON content of the cache file.
 ///
 /// # Returns
 ///
-/// * `Res<()>`: A Result type alias. Returns `Ok(())` if the environment is successfully
-///              initialized, or an error if the initialization fails or no version is provided.
-///
-/// # Errors
-///
-/// This function will return an error if:
-/// * No version is provided (i.e., `version` is `None`).
-/// * There are issues setting environment variables or reading the current PATH.
-pub async fn init_go_environment(version: Option<String>) -> Res<()> {
-    let active_version = match version {
-        Some(v) => v,
-        None => match get_active_version().await {
-            Some(v) => v,
-            None => error!("No active version found. Use 'gvm list' to see available versions."),
-        },
-    };
-
-    info!(
-        "Prepare go environment for version '{}' ...",
-        active_version
-    );
-
-    info!("Prepare environment for version {} ...", &active_version);
-    let environment_path = get_environment_file_path();
-    match async_fs::create_dir_all(&environment_path).await {
-        Ok(_) => success!("Environment directory created."),
-        Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => {
-            info!("Environment directory already exists.")
-        }
-        Err(e) => error!("Error creating environment directory: {}", e),
-    }
-
-    let environment_file_path = environment_path.join("go.env");
-    let version_path = get_version_file_path();
-    let cache_dir = get_cache_dir();
-    let package_path = get_package_file_path();
-
-    let goroot = version_path.join(&active_version);
-    let gocache = cache_dir.join(&active_version).join("go-build");
-    let gopath = package_path.join(&active_version);
-
-    let env_vars = vec![
-        ("GOROOT", goroot.to_string_lossy()),
-        ("GOCACHE", gocache.to_string_lossy()),
-        ("GOPATH", gopath.to_string_lossy()),
-        ("GOENV", environment_file_path.to_string_lossy()),
-    ];
-
-    let mut env_content = String::new();
-
-    for (env_key, env_value) in env_vars {
-        if env_value.contains(' ') || env_value.contains('"') || env_value.contains('\'') {
-            env_content.push_str(&format!(
-                "{}=\"{}\"\n",
-                env_key,
-                env_value.replace('"', "\\\"")
-            ));
-        } else {
-            env_content.push_str(&format!("{}={}\n", env_key, env_value));
-        }
+/// The cached releases, or an error if the content is not a release cache.
+pub fn parse_release_cache(data: &str) -> serde_json::Result<Vec<FilteredRelease>> {
+    let mut releases: Vec<FilteredRelease> = serde_json::from_str(data)?;
+    for release in releases
+        .iter_mut()
+        .filter(|r| r.os.is_empty() || r.arch.is_empty())
+    {
+        let (os, arch) = archive_platform(&release.url)
+            .unwrap_or_else(|| ("linux".to_string(), "amd64".to_string()));
+        release.os = os;
+        release.arch = arch;
     }
+    Ok(releases)
+}
 
-    async_fs::write(&environment_file_path, env_content).await?;
-
-    success!("Go environment prepared for version '{}'.", &active_version);
-
-    Ok(())
+/// Returns `true` if the version argument asks for the newest stable release,
+/// i.e. it is `latest` or `latest-stable`.
+pub fn is_latest_keyword(version: &str) -> bool {
+    matches!(version, "latest" | "latest-stable")
 }
 
-/// Retrieves the currently active Go version managed by GVM.
+/// Returns the newest stable version from a list of versions.
 ///
-/// This function reads the 'active' file in the GVM version directory
-/// to determine which Go version is currently set as active.
+/// Pre-releases and installs for a foreign platform (e.g.
+/// "go1.22.0.darwin-arm64") are ignored.
 ///
 /// # Returns
 ///
-/// - `Some(String)`: The active Go version as a string (e.g., "go1.16.5"),
-///   if a valid version is found and it starts with "go".
-/// - `None`: If no active version is set, the file can't be read,
-///   or the content doesn't represent a valid Go version (i.e., doesn't start with "go").
-pub async fn get_active_version() -> Option<String> {
-    let version_path = get_version_file_path();
-    let active_path = version_path.join("active");
-
-    async_fs::read_to_string(active_path)
-        .await
-        .ok()
-        .and_then(|active_version| {
-            if active_version.starts_with("go") {
-                Some(active_version)
-            } else {
-                None
-            }
-        })
+/// The newest stable version, or `None` if there is none.
+pub fn latest_stable_version<'a>(versions: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    versions
+        .into_iter()
+        .filter(|v| is_stable_version(v) && parse_version_parts(v).1.is_empty())
+        .max_by(|a, b| cmp_versions(a, b))
+        .map(str::to_string)
 }
 
-/// Checks if a given Go version is currently active in the GVM (Go Version Manager) system.
+/// Returns the newest stable patch release of a minor version.
 ///
-/// This function compares the provided version string with the currently active version
-/// retrieved from the GVM configuration.
+/// Only applies to versions naming just a major and minor version, e.g.
+/// "go1.22", which matches "go1.22" and every "go1.22.x". Pre-releases match
+/// only when named explicitly, so they are never picked here.
 ///
 /// # Parameters
 ///
-/// * `version`: A String representing the Go version to check. It should include the "go" prefix
-///              (e.g., "go1.16.5").
+/// * `version`: The requested version including the "go" prefix.
+/// * `versions`: The versions to choose from.
 ///
 /// # Returns
 ///
-/// * `true` if the provided version matches the currently active version.
-/// * `false` if the versions don't match or if there is no active version set.
-pub async fn is_version_active(version: &str) -> bool {
-    let active_version = get_active_version();
-    active_version
-        .await
-        .map(|av| av == version)
-        .unwrap_or(false)
+/// The newest matching stable version, or `None` if `version` is not a bare
+/// minor version or nothing matches.
+pub fn latest_patch_version<'a>(
+    version: &str,
+    versions: impl IntoIterator<Item = &'a str>,
+) -> Option<String> {
+    let (minor, suffix) = parse_version_parts(version);
+    if minor.len() != 2 || !suffix.is_empty() {
+        return None;
+    }
+    versions
+        .into_iter()
+        .filter(|v| {
+            let (parts, suffix) = parse_version_parts(v);
+            suffix.is_empty() && parts.starts_with(&minor)
+        })
+        .max_by(|a, b| cmp_versions(a, b))
+        .map(str::to_string)
+}
+
+/// Returns `true` if the version is stable. It strips the "go" prefix and
+/// considers a version unstable if it contains "rc", "beta", or "alpha".
+pub fn is_stable_version(version: &str) -> bool {
+    let trimmed = version.strip_prefix("go").unwrap_or(version);
+    !(trimmed.contains("rc") || trimmed.contains("beta") || trimmed.contains("alpha"))
+}
+
+/// Ensures that a given version string is prefixed with "go".
+///
+/// This function takes a version string and checks if it starts with "go".
+/// If it doesn't, it prepends "go" to the version string.
+///
+/// # Parameters
+///
+/// * `version`: A String representing the version number, which may or may not start with "go".
+///
+/// # Returns
+///
+/// A String that always starts with "go", followed by the version number.
+pub fn get_real_version(version: String) -> String {
+    let real_version = if version.starts_with("go") {
+        version
+    } else {
+        format!("go{}", version)
+    };
+
+    real_version
+}
+
+/// Normalizes a version given on the command line and checks that it is a Go
+/// version.
+///
+/// Accepted are `1.22`, `1.22.0` and `go1.22`, optionally followed by a
+/// pre-release suffix (`go1.23rc1`) and, for toolchains installed for another
+/// platform, the platform (`go1.22.0.darwin-arm64`). Anything else, e.g.
+/// "golang1.22" or "v1.22", is rejected instead of being turned into a
+/// directory name that can never match.
+///
+/// # Parameters
+///
+/// * `version`: The version as given by the user.
+///
+/// # Returns
+///
+/// The version with the "go" prefix, or an error if it is not a Go version.
+pub fn parse_version_arg(version: String) -> Res<String> {
+    static VERSION_RE: OnceLock<Regex> = OnceLock::new();
+    let re = VERSION_RE.get_or_init(|| {
+        Regex::new(r"^go\d+(\.\d+)*((alpha|beta|rc)\d+)?(\.[a-z0-9]+-[a-z0-9]+)?$").unwrap()
+    });
+
+    let real_version = get_real_version(version.trim().to_string());
+    if !re.is_match(&real_version) {
+        error!(
+            "Invalid version '{}'. Expected a Go version like 1.22, 1.22.0, go1.22 or go1.23rc1.",
+            version
+        );
+    }
+    Ok(real_version)
+}
+
+/// Returns `true` if the string is a well-formed SHA-256 digest (64 hex characters).
+pub fn is_valid_sha256(checksum: &str) -> bool {
+    checksum.len() == 64 && checksum.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Computes the SHA-256 digest of a file.
+///
+/// # Parameters
+///
+/// * `path`: The file to hash.
+///
+/// # Returns
+///
+/// Returns `io::Result<String>` with the lowercase hex digest of the file content.
+pub fn sha256_file<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Parses a version string into its numeric base parts and an optional suffix.
+/// For example:
+///   - "go1.24.0"  => (vec![1, 24, 0], "")
+///   - "go1.24rc1" => (vec![1, 24], "rc1")
+pub fn parse_version_parts(version: &str) -> (Vec<u32>, String) {
+    // Regex to capture the numeric part and the rest.
+    // Captures: 1) the numeric part (e.g. "1.24" or "1.24.0") and 2) any trailing characters.
+    let re = Regex::new(r"^go(\d+(?:\.\d+)*)(.*)$").unwrap();
+    if let Some(caps) = re.captures(version) {
+        let base = &caps[1];
+        let suffix = &caps[2];
+        let base_parts: Vec<u32> = base
+            .split('.')
+            .filter_map(|s| s.parse::<u32>().ok())
+            .collect();
+        return (base_parts, suffix.to_string());
+    }
+    (vec![], String::new())
+}
+
+/// Splits a pre-release suffix like "rc10" into its kind and number.
+///
+/// Kinds are ranked alpha < beta < rc; unknown kinds rank after rc. A missing
+/// number is treated as 0.
+///
+/// # Returns
+///
+/// A tuple of the kind's rank, the kind itself and the number.
+fn parse_prerelease_suffix(suffix: &str) -> (u8, &str, u32) {
+    let split = suffix
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(suffix.len());
+    let (kind, number) = suffix.split_at(split);
+    let rank = match kind {
+        "alpha" => 0,
+        "beta" => 1,
+        "rc" => 2,
+        _ => 3,
+    };
+    (rank, kind, number.parse().unwrap_or(0))
+}
+
+/// Custom comparator for version strings.
+/// 1. Compares the numeric parts.
+/// 2. If the base versions are equal, then:
+///    - If one version is unstable (non‑empty suffix) and the other is stable,
+///      the unstable version comes first.
+///    - If both are unstable, compare the pre-release kinds (alpha < beta < rc)
+///      and then their numbers numerically, so "rc2" sorts before "rc10".
+pub fn cmp_versions(a: &str, b: &str) -> Ordering {
+    let (base_a, suffix_a) = parse_version_parts(a);
+    let (base_b, suffix_b) = parse_version_parts(b);
+
+    // First compare the numeric (base) versions.
+    match base_a.cmp(&base_b) {
+        Ordering::Equal => {
+            // For the same base version, we want unstable versions (non‑empty suffix) first.
+            match (suffix_a.is_empty(), suffix_b.is_empty()) {
+                (false, true) => Ordering::Less,    // a is unstable, b is stable
+                (true, false) => Ordering::Greater, // a is stable, b is unstable
+                (true, true) => Ordering::Equal,    // both are stable
+                (false, false) => {
+                    // both unstable: sort by kind, then numerically
+                    parse_prerelease_suffix(&suffix_a).cmp(&parse_prerelease_suffix(&suffix_b))
+                }
+            }
+        }
+        ord => ord,
+    }
+}
+
+/// Attempts to retrieve the user's home directory.
+///
+/// This function tries to get the value of the "HOME" environment variable,
+/// which typically represents the user's home directory on Unix-like systems.
+///
+/// # Returns
+///
+/// - `Ok(PathBuf)` containing the path to the user's home directory if the
+///   "HOME" environment variable is set and valid.
+/// - `Err(String)` if the "HOME" environment variable is not set or cannot be read.
+fn get_home_dir() -> Result<PathBuf, String> {
+    dirs::home_dir().ok_or_else(|| "Cannot access HOME dir".to_string())
+}
+
+/// Retrieves the path of the current shell.
+///
+/// This function retrieves the value of the "SHELL" environment variable,
+/// which represents the path to the currently active shell.
+///
+/// # Returns
+///
+/// * `Some(String)`: If the "SHELL" environment variable is set, the function
+///   returns `Some` containing the path to the shell as a string.
+/// * `None`: If the "SHELL" environment variable is not set or is empty,
+///   the function returns `None`.
+fn get_shell() -> Option<String> {
+    env::var("SHELL").ok()
+}
+
+/// Login shells GVM can be initialized for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl LoginShell {
+    /// Detects the shell from the path of its executable, e.g. `/bin/bash`.
+    ///
+    /// # Returns
+    ///
+    /// The detected shell, or `None` if the shell is not supported.
+    pub fn from_path(shell_path: &str) -> Option<Self> {
+        if shell_path.ends_with("/bash") {
+            Some(LoginShell::Bash)
+        } else if shell_path.ends_with("/zsh") {
+            Some(LoginShell::Zsh)
+        } else if shell_path.ends_with("/fish") {
+            Some(LoginShell::Fish)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the path of the shell's configuration file below `home`.
+    fn config_file_path(self, home: &Path) -> PathBuf {
+        match self {
+            LoginShell::Bash if cfg!(target_os = "macos") => home.join(".bash_profile"),
+            LoginShell::Bash => home.join(".bashrc"),
+            LoginShell::Zsh => home.join(".zshrc"),
+            LoginShell::Fish => home.join(".config").join("fish").join("config.fish"),
+        }
+    }
+}
+
+impl fmt::Display for LoginShell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoginShell::Bash => write!(f, "bash"),
+            LoginShell::Zsh => write!(f, "zsh"),
+            LoginShell::Fish => write!(f, "fish"),
+        }
+    }
+}
+
+/// Detects the user's login shell from the SHELL environment variable.
+///
+/// # Returns
+///
+/// The detected shell, or an error message if SHELL is unset or the shell is
+/// not supported.
+pub fn get_login_shell() -> Result<LoginShell, String> {
+    match get_shell() {
+        Some(shell_path) => LoginShell::from_path(&shell_path)
+            .ok_or_else(|| format!("Unsupported shell: {}", shell_path)),
+        None => Err("Failed to retrieve SHELL environment variable".to_string()),
+    }
+}
+
+/// Determines the path to the shell configuration file based on the current shell.
+///
+/// This function attempts to identify the user's shell (bash, zsh or fish) and
+/// returns the path to the appropriate configuration file (.bashrc, .zshrc or
+/// .config/fish/config.fish). On macOS, terminals start bash as a login shell,
+/// which reads .bash_profile instead of .bashrc.
+///
+/// # Returns
+///
+/// A `PathBuf` containing the path to the shell configuration file.
+///
+/// # Errors
+///
+/// This function returns an error message in the following cases:
+/// - If the shell is neither bash, zsh nor fish.
+/// - If the SHELL environment variable cannot be retrieved.
+pub fn get_shell_config_file_path() -> Result<PathBuf, String> {
+    let home = get_home_dir()?;
+    Ok(get_login_shell()?.config_file_path(&home))
+}
+
+/// Expands a leading `~` and `$VAR` or `${VAR}` references in a path.
+///
+/// # Parameters
+///
+/// * `value`: The path to expand, e.g. "~/sdks/gvm" or "$HOME/gvm".
+///
+/// # Returns
+///
+/// The expanded path, or an error if it refers to the home directory or a
+/// variable that is not available. A `$` not followed by a variable name is
+/// kept as is.
+pub fn expand_path(value: &str) -> Res<PathBuf> {
+    let value = match value.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => match dirs::home_dir() {
+            Some(home) => format!("{}{}", home.display(), rest),
+            None => error!("Cannot expand '~' in '{}': no home directory found.", value),
+        },
+        _ => value.to_string(),
+    };
+
+    static VAR_RE: OnceLock<Regex> = OnceLock::new();
+    let re = VAR_RE.get_or_init(|| {
+        Regex::new(r"\$(?:\{([A-Za-z_][A-Za-z0-9_]*)\}|([A-Za-z_][A-Za-z0-9_]*))").unwrap()
+    });
+    let mut expanded = String::new();
+    let mut last = 0;
+    for caps in re.captures_iter(&value) {
+        let (Some(whole), Some(name)) = (caps.get(0), caps.get(1).or(caps.get(2))) else {
+            continue;
+        };
+        let var = match env::var(name.as_str()) {
+            Ok(var) => var,
+            Err(_) => error!("Cannot expand '{}': ${} is not set.", value, name.as_str()),
+        };
+        expanded.push_str(&value[last..whole.start()]);
+        expanded.push_str(&var);
+        last = whole.end();
+    }
+    expanded.push_str(&value[last..]);
+
+    Ok(PathBuf::from(expanded))
+}
+
+/// Resolves the `GVM_ROOT` environment variable.
+///
+/// # Returns
+///
+/// `None` if `GVM_ROOT` is not set or empty, otherwise the expanded path (see
+/// `expand_path`), or an error if it cannot be expanded or is not absolute,
+/// since a relative root would change with the working directory.
+pub fn get_gvm_root() -> Option<Res<PathBuf>> {
+    let gvm_root = env::var_os("GVM_ROOT").filter(|root| !root.is_empty())?;
+    Some(parse_gvm_root(&gvm_root))
+}
+
+/// Expands a `GVM_ROOT` value and checks that it is absolute.
+///
+/// # Parameters
+///
+/// * `gvm_root`: The value of `GVM_ROOT`.
+///
+/// # Returns
+///
+/// The expanded path (see `expand_path`), or an error if it cannot be
+/// expanded or is not absolute.
+fn parse_gvm_root(gvm_root: &OsStr) -> Res<PathBuf> {
+    let path = match gvm_root.to_str() {
+        Some(value) => match expand_path(value) {
+            Ok(path) => path,
+            Err(e) => error!("Invalid GVM_ROOT: {}", e),
+        },
+        None => PathBuf::from(gvm_root),
+    };
+    if !path.is_absolute() {
+        error!(
+            "Invalid GVM_ROOT '{}': it must be an absolute path.",
+            path.display()
+        );
+    }
+    Ok(path)
+}
+
+/// The base directory, resolved on first use by `get_gvm_base_file_path`.
+static GVM_BASE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Returns the base file path for the GVM (Go Version Manager) system.
+///
+/// This function determines the location of the base directory used by GVM.
+/// If the `GVM_ROOT` environment variable is set, it is used after expanding
+/// `~` and `$VAR`, so GVM can be relocated (e.g. onto a larger disk or for CI
+/// isolation). An invalid `GVM_ROOT` is rejected when gvm starts, see
+/// `get_gvm_root`, so here it is used as given if it cannot be resolved. Otherwise it
+/// attempts to use the user's home directory. If available, it appends
+/// the GVM-specific path. If the home directory cannot be determined,
+/// it falls back to a temporary directory.
+///
+/// The directory is resolved once and reused, since every other path is
+/// derived from it. Unit tests get a temporary directory instead, see
+/// `test_support::gvm_root`.
+///
+/// # Returns
+///
+/// A `String` representing the full path to the GVM base directory:
+/// - `$GVM_ROOT` if the variable is set and not empty
+/// - `~/.gvm` if the home directory is available
+/// - `/tmp/gvm` as a fallback if the home directory cannot be determined
+pub fn get_gvm_base_file_path() -> PathBuf {
+    #[cfg(not(test))]
+    let resolve = || gvm_base_file_path(env::var_os("GVM_ROOT").as_deref(), dirs::home_dir());
+    #[cfg(test)]
+    let resolve = test_support::gvm_root;
+
+    GVM_BASE.get_or_init(resolve).clone()
+}
+
+/// Resolves the base directory from `GVM_ROOT` and the home directory.
+///
+/// # Parameters
+///
+/// * `gvm_root`: The value of `GVM_ROOT`, if set.
+/// * `home`: The home directory, if known.
+///
+/// # Returns
+///
+/// The base directory, see `get_gvm_base_file_path`.
+fn gvm_base_file_path(gvm_root: Option<&OsStr>, home: Option<PathBuf>) -> PathBuf {
+    if let Some(gvm_root) = gvm_root.filter(|root| !root.is_empty()) {
+        return parse_gvm_root(gvm_root).unwrap_or_else(|_| PathBuf::from(gvm_root));
+    }
+
+    match home {
+        Some(home) => home.join(config::GVM_MAIN_PATH),
+        None => env::temp_dir().join(config::GVM_FALLBACK_PATH),
+    }
+}
+
+/// Returns the path to the cache directory for the GVM (Go Version Manager) system.
+///
+/// This function determines the location of the cache directory used by GVM.
+/// It first attempts to use the user's home directory. If available, it appends
+/// the GVM-specific cache path. If the home directory cannot be determined,
+/// it falls back to a temporary directory.
+///
+/// # Returns
+///
+/// A `String` representing the full path to the cache directory:
+/// - `~/.gvm/cache` if the home directory is available
+/// - `/tmp/gvm/cache` as a fallback if the home directory cannot be determined
+pub fn get_cache_dir() -> PathBuf {
+    let gvm_path = get_gvm_base_file_path();
+    gvm_path.join(config::GVM_CACHE_PATH)
+}
+
+/// Returns the path of the release cache file written by `gvm update`.
+///
+/// Every command reading or writing the release cache uses this path. Caches
+/// for a foreign platform get the platform in their name, e.g.
+/// "releases.darwin-arm64.json", so they don't replace the host's cache.
+pub fn get_release_cache_file_path() -> PathBuf {
+    let cache_file = get_cache_dir().join(config::RELEASE_CACHE_FILE);
+    if is_foreign_platform() {
+        cache_file.with_extension(format!("{}-{}.json", get_target_os(), get_target_arch()))
+    } else {
+        cache_file
+    }
+}
+
+/// Returns the file path for the environment configuration used by GVM (Go Version Manager).
+///
+/// This function determines the location of the environment file used by the GVM system.
+/// It first attempts to use the user's home directory. If available, it appends the GVM-specific path.
+/// If the home directory cannot be determined, it falls back to a temporary directory.
+///
+/// # Returns
+///
+/// A `String` representing the full path to the environment file:
+/// - `~/.gvm/environment` if the home directory is available
+/// - `/tmp/gvm/environment` as a fallback if the home directory cannot be determined
+pub fn get_environment_file_path() -> PathBuf {
+    let gvm_path = get_gvm_base_file_path();
+    gvm_path.join(config::GVM_ENVIRONMENT_PATH)
+}
+
+/// Returns the file path for the version configuration.
+///
+/// This function determines the location of the version file used by the GVM (Go Version Manager) system.
+/// It first attempts to use the user's home directory. If available, it appends the GVM-specific path.
+/// If the home directory cannot be determined, it falls back to a temporary directory.
+///
+/// # Returns
+///
+/// A `String` representing the full path to the version file:
+/// - `~/.gvm/version` if the home directory is available
+/// - `/tmp/gvm/version` as a fallback if the home directory cannot be determined
+pub fn get_version_file_path() -> PathBuf {
+    let gvm_path = get_gvm_base_file_path();
+    gvm_path.join(config::GVM_VERSION_PATH)
+}
+
+/// Returns the file path for the package configuration.
+///
+/// This function determines the location of the package file used by the GVM (Go Version Manager) system.
+/// It first attempts to use the user's home directory. If available, it appends the GVM-specific path.
+/// If the home directory cannot be determined, it falls back to a temporary directory.
+///
+/// # Returns
+///
+/// A `String` representing the full path to the package file:
+/// - `~/.gvm/package` if the home directory is available
+/// - `/tmp/gvm/package` as a fallback if the home directory cannot be determined
+pub fn get_package_file_path() -> PathBuf {
+    let gvm_path = get_gvm_base_file_path();
+    gvm_path.join(config::GVM_PACKAGE_PATH)
+}
+
+/// Sums the size of all files below a directory.
+///
+/// Symlinks are not followed, so only what is stored in the directory itself
+/// is counted.
+///
+/// # Parameters
+///
+/// * `path`: The directory to measure.
+///
+/// # Returns
+///
+/// The size in bytes, or an error if a directory cannot be read.
+pub fn dir_size(path: &Path) -> io::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.path().symlink_metadata()?;
+        if metadata.is_dir() {
+            size += dir_size(&entry.path())?;
+        } else {
+            size += metadata.len();
+        }
+    }
+    Ok(size)
+}
+
+/// Returns the GOPATH of a specified version.
+///
+/// This is `~/.gvm/package/<version>`, or `~/.gvm/package/shared` for every
+/// version if the GOPATH is shared, see `is_gopath_shared`.
+///
+/// # Parameters
+///
+/// * `version`: The Go version including the "go" prefix (e.g., "go1.16.5").
+pub fn get_gopath(version: &str) -> PathBuf {
+    gopath(version, is_gopath_shared())
+}
+
+/// Returns the GOPATH of a version in the given mode, see `get_gopath`.
+fn gopath(version: &str, shared: bool) -> PathBuf {
+    let name = if shared {
+        config::SHARED_GOPATH_NAME
+    } else {
+        version
+    };
+    get_package_file_path().join(name)
+}
+
+/// Returns the file path for the archive configuration.
+///
+/// This function determines the location of the archive file used by the GVM (Go Version Manager) system.
+/// It first attempts to use the user's home directory. If available, it appends the GVM-specific path.
+/// If the home directory cannot be determined, it falls back to a temporary directory.
+///
+/// # Returns
+///
+/// A `String` representing the full path to the archive file:
+/// - `~/.gvm/archive` if the home directory is available
+/// - `/tmp/gvm/archive` as a fallback if the home directory cannot be determined
+pub fn get_archive_file_path() -> PathBuf {
+    let gvm_path = get_gvm_base_file_path();
+    gvm_path.join(config::GVM_ARCHIVE_PATH)
+}
+
+/// Returns the directory `gvm shim install` writes the `go` shim to,
+/// `~/.gvm/shims`.
+pub fn get_shims_path() -> PathBuf {
+    get_gvm_base_file_path().join(config::GVM_SHIMS_PATH)
+}
+
+/// Returns the directory verified release archives are kept in for reuse,
+/// `~/.gvm/archive/cached`.
+pub fn get_archive_cache_path() -> PathBuf {
+    get_archive_file_path().join(config::GVM_ARCHIVE_CACHE_PATH)
+}
+
+/// Returns the file path for the alias configuration.
+///
+/// This function determines the location of the alias file used by the GVM (Go Version Manager) system.
+/// It first attempts to use the user's home directory. If available, it appends the GVM-specific path.
+/// If the home directory cannot be determined, it falls back to a temporary directory.
+///
+/// # Returns
+///
+/// A `String` representing the full path to the alias file:
+/// - `~/.gvm/alias` if the home directory is available
+/// - `/tmp/gvm/alias` as a fallback if the home directory cannot be determined
+pub fn get_alias_file_path() -> PathBuf {
+    let gvm_path = get_gvm_base_file_path();
+    gvm_path.join(config::GVM_ALIAS_PATH)
+}
+
+/// Lists all installed Go versions managed by GVM.
+///
+/// This function scans the GVM version directory and collects the names of all
+/// subdirectories that are named like a Go version (e.g. "go1.22.0"). The
+/// `active` marker and other entries are skipped.
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<String>)`: A vector of strings, where each string is the name of an
+///   installed Go version.
+/// - `Err(Box<dyn Error + Send + Sync>)`: An error if the version directory
+///   cannot be read or if there are issues accessing the directory entries.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The version directory path cannot be retrieved or accessed.
+/// - There are issues reading the directory entries.
+/// - The directory entry names cannot be converted to strings.
+pub async fn list_installed_versions() -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+    let version_path = get_version_file_path();
+    let mut versions = Vec::new();
+
+    let mut entries = async_fs::read_dir(&version_path).await?;
+
+    while let Some(entry) = entries.try_next().await? {
+        if !entry.file_type().await?.is_dir() {
+            continue;
+        }
+        let version_name = entry.file_name().into_string().unwrap_or_default();
+        // Skip the `active` marker and leftovers like a partially extracted `go` directory.
+        if !parse_version_parts(&version_name).0.is_empty() {
+            versions.push(version_name);
+        }
+    }
+
+    Ok(versions)
+}
+
+/// Lists all aliases defined in the GVM (Go Version Manager) system.
+///
+/// This function reads the alias directory and collects the names of all
+/// files, which are assumed to represent defined aliases. Hidden entries are
+/// temporary links of an alias being replaced and are skipped.
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<String>)`: A vector of strings, where each string is the name of a
+///   defined alias.
+/// - `Err(Box<dyn Error + Send + Sync>)`: An error if the alias directory
+///   cannot be read or if there are issues accessing the directory entries.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The alias directory path cannot be retrieved or accessed.
+/// - There are issues reading the directory entries.
+/// - The directory entry names cannot be converted to strings.
+pub async fn list_aliases() -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+    let alias_path = get_alias_file_path();
+    let mut aliases = Vec::new();
+
+    let mut entries = async_fs::read_dir(&alias_path).await?;
+
+    while let Some(enty) = entries.try_next().await? {
+        let alias_name = enty.file_name().into_string().unwrap_or_default();
+        if alias_name.starts_with('.') {
+            continue;
+        }
+        aliases.push(alias_name);
+    }
+
+    Ok(aliases)
+}
+
+/// Asks the user to confirm an irreversible action.
+///
+/// The question is printed to stderr followed by `[y/N]`, and only `y` or
+/// `yes` read from stdin count as confirmation. If stdin is not a terminal,
+/// nobody can answer, so `yes` has to be given instead; otherwise piped or
+/// scripted invocations would delete without anyone noticing.
+///
+/// # Parameters
+///
+/// * `question`: The question to ask, e.g. "Remove go1.21.5?".
+/// * `yes`: Whether `--yes` was given, which confirms without asking.
+///
+/// # Returns
+///
+/// `Ok(true)` if the action is confirmed, `Ok(false)` if the user declined,
+/// or an error if stdin is not a terminal and `yes` is not set.
+pub fn confirm(question: &str, yes: bool) -> Res<bool> {
+    let stdin = io::stdin();
+    confirm_from(question, yes, stdin.is_terminal(), &mut stdin.lock())
+}
+
+/// Asks for confirmation like `confirm`, reading the answer from `input`.
+///
+/// # Parameters
+///
+/// * `interactive`: Whether `input` is a terminal someone can answer on.
+fn confirm_from(
+    question: &str,
+    yes: bool,
+    interactive: bool,
+    input: &mut impl io::BufRead,
+) -> Res<bool> {
+    if yes {
+        return Ok(true);
+    }
+    if !interactive {
+        error!(
+            "{} Pass --yes to confirm, since stdin is not a terminal.",
+            question
+        );
+    }
+
+    eprint!("{} [y/N] ", question);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Checks that a version or alias name given on the command line is safe to
+/// join to a GVM directory.
+///
+/// # Parameters
+///
+/// * `kind`: What the name is, used in the error message (e.g. "version").
+/// * `name`: The name to check.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the name is usable, or an error if it is empty, `.`,
+/// or contains a path separator, `..` or a NUL character, any of which could
+/// point outside the GVM directory.
+pub fn validate_name(kind: &str, name: &str) -> Res<()> {
+    if name.is_empty() || name == "." || name.contains("..") || name.contains(['/', '\\', '\0']) {
+        error!(
+            "Invalid {} name '{}'. Names must not be empty or contain path separators, '..' or NUL characters.",
+            kind,
+            name.escape_default()
+        );
+    }
+    Ok(())
+}
+
+/// Checks that a name can be used for a user-defined alias.
+///
+/// # Parameters
+///
+/// * `alias`: The alias name to check.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the name is usable, or an error if it is one of
+/// `config::RESERVED_ALIAS_NAMES`, starts with a dot (those are temporary
+/// links, see `replace_symlink`) or is rejected by `validate_name`.
+pub fn validate_alias_name(alias: &str) -> Res<()> {
+    if config::RESERVED_ALIAS_NAMES.contains(&alias) {
+        error!(
+            "'{}' is a reserved name and cannot be used as alias. Reserved names are: {}.",
+            alias,
+            config::RESERVED_ALIAS_NAMES.join(", ")
+        );
+    }
+    validate_name("alias", alias)?;
+    if alias.starts_with('.') {
+        error!(
+            "Invalid alias name '{}'. Alias names must not start with a dot.",
+            alias
+        );
+    }
+    Ok(())
+}
+
+/// Resolves the version an alias points to.
+///
+/// This function reads the alias symlink and returns the name of the version
+/// directory it points to. Aliases of aliases are followed to the version at
+/// the end of the chain. If the chain is broken or cyclic, the name of the
+/// immediate target is returned instead.
+///
+/// # Parameters
+///
+/// * `alias`: The name of the alias to resolve.
+///
+/// # Returns
+///
+/// - `Some(String)`: The version the alias points to (e.g., "go1.16.5").
+/// - `None`: If the alias does not exist or is not a symlink.
+pub async fn get_alias_target(alias: &str) -> Option<String> {
+    let alias_path = get_alias_file_path().join(alias);
+    let link = async_fs::read_link(&alias_path).await.ok()?;
+    let target = resolve_alias(alias).await.unwrap_or(link);
+    target.file_name().map(|n| n.to_string_lossy().to_string())
+}
+
+/// Follows an alias, and any aliases it points to, to the version directory.
+///
+/// # Parameters
+///
+/// * `alias`: The name of the alias to resolve.
+///
+/// # Returns
+///
+/// The canonical path of the version directory, or an error if the alias does
+/// not exist, the chain ends at a missing directory, or the chain is cyclic.
+pub async fn resolve_alias(alias: &str) -> io::Result<PathBuf> {
+    let alias_path = get_alias_file_path().join(alias);
+    let target = async_fs::canonicalize(&alias_path).await?;
+    if !target.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("alias {} does not point to a version directory", alias),
+        ));
+    }
+    Ok(target)
+}
+
+/// Returns the alias an alias points to, if it is an alias of an alias.
+///
+/// # Parameters
+///
+/// * `alias`: The name of the alias to inspect.
+///
+/// # Returns
+///
+/// The name of the immediate target alias, or `None` if the alias points to a
+/// version directory or does not exist.
+pub async fn get_parent_alias(alias: &str) -> Option<String> {
+    let alias_dir = get_alias_file_path();
+    let link = async_fs::read_link(alias_dir.join(alias)).await.ok()?;
+    if link.parent() != Some(alias_dir.as_path()) {
+        return None;
+    }
+    link.file_name().map(|n| n.to_string_lossy().to_string())
+}
+
+/// Reads the cache file and returns all cached releases, applying filtering criteria,
+/// and then sorts the list in ascending order (so that the latest version is at the bottom).
+///
+/// - `cache_file`: Path to the cached file.
+/// - `version_filter`: Optional filter for the version string (e.g. "1.21.1" for exact match
+///   or "1.21.*" for wildcard matching). If the provided filter does not start with "go", it will be prefixed.
+/// - `stable_only`: When `true`, only releases with stable version strings are returned.
+///
+/// Only releases built for the target os and arch are returned.
+pub async fn list_cached_versions<P: AsRef<Path>>(
+    cache_file: P,
+    version_filter: Option<String>,
+    stable_only: bool,
+) -> Result<Vec<FilteredRelease>, Box<dyn Error + Send + Sync>> {
+    // Read and deserialize the cached JSON file.
+    let data = async_fs::read_to_string(&cache_file).await?;
+    let mut releases = parse_release_cache(&data)?;
+
+    // Ensure the version filter (if provided) starts with "go".
+    let version_filter = version_filter.map(|f| {
+        if f.starts_with("go") {
+            f
+        } else {
+            format!("go{}", f)
+        }
+    });
+
+    // Filter releases based on stability and version string.
+    releases.retain(|r: &FilteredRelease| {
+        if !is_target_release(r) {
+            return false;
+        }
+        if stable_only && !is_stable_version(&r.version) {
+            return false;
+        }
+        if let Some(ref filter) = version_filter {
+            if filter.ends_with('*') {
+                let prefix = &filter[..filter.len() - 1];
+                r.version.starts_with(prefix)
+            } else {
+                r.version == *filter
+            }
+        } else {
+            true
+        }
+    });
+
+    // Sort the filtered releases in ascending order using our custom comparator.
+    releases.sort_by(|a, b| cmp_versions(&a.version, &b.version));
+
+    Ok(releases)
+}
+
+/// Removes an existing symbolic link if it exists.
+///
+/// This function checks if the given path is a symbolic link, dangling or
+/// not. If so, it removes the symbolic link. This operation is only performed
+/// on Unix-like systems.
+///
+/// # Parameters
+///
+/// * `link`: A path-like object representing the potential symbolic link to be removed.
+///
+/// # Returns
+///
+/// Returns `io::Result<()>`:
+/// - `Ok(())` if the operation was successful (either the symlink was removed or didn't exist).
+/// - `Err(e)` if an error occurred during the file system operations.
+///
+/// # Platform-specific behavior
+///
+/// The actual removal of the symlink is only performed on Unix-like systems.
+pub async fn remove_existing_symlink<P: AsRef<Path>>(link: P) -> io::Result<()> {
+    let link = link.as_ref();
+    // Use symlink_metadata to avoid following the symlink, so dangling
+    // symlinks are removed as well.
+    if let Ok(metadata) = async_fs::symlink_metadata(link).await {
+        if metadata.file_type().is_symlink() {
+            info!("Removing existing symlink: {}", link.display());
+            #[cfg(unix)]
+            {
+                async_fs::remove_file(link).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Creates a symbolic link.
+///
+/// This function creates a symbolic link pointing to the `original` path at the `link` location.
+/// It is only available on Unix-like systems. An existing symlink is replaced atomically (see
+/// `replace_symlink`), so there is no moment in which `link` does not resolve.
+///
+/// # Parameters
+///
+/// * `original`: A path-like object representing the target that the symlink will point to.
+/// * `link`: A path-like object representing the location where the symlink will be created.
+///
+/// # Returns
+///
+/// Returns `io::Result<()>`:
+/// - `Ok(())` if the symlink was successfully created.
+/// - `Err(e)` if an error occurred during the creation of the symlink.
+///
+/// # Platform-specific behavior
+///
+/// This function is only available on Unix-like systems. On other platforms, it will not be compiled.
+pub async fn create_symlink<P: AsRef<Path>, Q: AsRef<Path>>(
+    original: P,
+    link: Q,
+) -> io::Result<()> {
+    let link = link.as_ref();
+    let original = original.as_ref();
+    let replaces_symlink = async_fs::symlink_metadata(link)
+        .await
+        .is_ok_and(|m| m.file_type().is_symlink());
+
+    #[cfg(unix)]
+    {
+        info!(
+            "Creating symlink: {} -> {}",
+            original.display(),
+            link.display()
+        );
+        replace_symlink(original, link).await?;
+    }
+    if replaces_symlink {
+        success!("Replaced existing symlink: {}", link.display());
+    }
+    Ok(())
+}
+
+/// Activates a specified Go version in the GVM (Go Version Manager) system.
+///
+/// This function performs the following tasks:
+/// 1. Verifies if the specified version exists.
+/// 2. Creates the build cache and package path for the version, unless they already exist.
+/// 3. Writes `go.env`, the active file and (unless disabled) the default alias for the version.
+///
+/// Step 3 is all-or-nothing: if any of the files cannot be written, the
+/// previously active version is restored.
+///
+/// # Parameters
+///
+/// * `version`: A String representing the Go version to activate. It can be with or without the "go" prefix.
+/// * `update_default`: When `false`, the default alias keeps pointing at its current version.
+///
+/// # Returns
+///
+/// * `Ok(())` if the version is successfully activated.
+/// * `Err(Box<dyn Error + Send + Sync>)` if an error occurs during the activation process,
+///   such as the specified version not being found or issues with file operations.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The specified version is not found in the GVM system.
+/// * There are issues writing to the active file.
+/// * There are problems creating the default alias symlink.
+pub async fn activate_version(
+    version: String,
+    update_default: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let real_version = get_real_version(version);
+    let version_path = get_version_file_path();
+    let release_dir = version_path.join(&real_version);
+
+    if !release_dir.is_dir() {
+        error!(
+            "Version '{}' not found. Use 'gvm list' to see available versions.",
+            real_version
+        );
+    }
+
+    let version_build_cache_dir = get_cache_dir().join(&real_version).join("go-build");
+    if !version_build_cache_dir.is_dir() {
+        match async_fs::create_dir_all(&version_build_cache_dir).await {
+            Ok(_) => success!("Build cache for version '{}' created.", real_version),
+            Err(e) => error!(
+                "Error creating build cache for version '{}': {}",
+                real_version, e
+            ),
+        }
+    }
+
+    let version_package_path = get_gopath(&real_version).join("bin");
+    if !version_package_path.is_dir() {
+        match async_fs::create_dir_all(&version_package_path).await {
+            Ok(_) => success!("Go package path for version '{}' created.", real_version),
+            Err(e) => error!(
+                "Error creating go package path for version '{}': {}",
+                real_version, e
+            ),
+        }
+    }
+
+    info!("Activating version '{}' ...", real_version);
+    let previous_state = ActivationState::capture().await;
+    match switch_active_version(&real_version, &release_dir, update_default).await {
+        Ok(()) if update_default => success!(
+            "Version '{}' activated and default alias created.",
+            real_version
+        ),
+        Ok(()) => success!(
+            "Version '{}' activated. Default alias left unchanged.",
+            real_version
+        ),
+        Err(e) => {
+            previous_state.restore().await;
+            error!(
+                "Error activating version '{}': {}. Previous state restored.",
+                real_version, e
+            );
+        }
+    }
+
+    success!(
+        "Go version '{}' activated successfully. Please reload profile.",
+        real_version
+    );
+
+    Ok(())
+}
+
+/// Snapshot of the files that make up the active version.
+///
+/// It is captured before a version switch and written back if the switch fails,
+/// so the active file, the default alias and `go.env` never disagree.
+struct ActivationState {
+    active: Option<String>,
+    previous: Option<String>,
+    default_target: Option<PathBuf>,
+    go_env: Option<String>,
+}
+
+impl ActivationState {
+    /// Reads the current active and previous files, default alias target and
+    /// `go.env` content.
+    async fn capture() -> Self {
+        let active_path = get_version_file_path().join("active");
+        let previous_path = get_version_file_path().join("previous");
+        let default_path = get_alias_file_path().join("default");
+        let go_env_path = get_environment_file_path().join("go.env");
+
+        ActivationState {
+            active: async_fs::read_to_string(active_path).await.ok(),
+            previous: async_fs::read_to_string(previous_path).await.ok(),
+            default_target: async_fs::read_link(default_path).await.ok(),
+            go_env: async_fs::read_to_string(go_env_path).await.ok(),
+        }
+    }
+
+    /// Writes the captured state back, removing files that did not exist before.
+    async fn restore(self) {
+        let active_path = get_version_file_path().join("active");
+        let previous_path = get_version_file_path().join("previous");
+        let default_path = get_alias_file_path().join("default");
+        let go_env_path = get_environment_file_path().join("go.env");
+
+        let _ = match self.active {
+            Some(active) => write_file_atomic(&active_path, active).await,
+            None => async_fs::remove_file(&active_path).await,
+        };
+        let _ = match self.previous {
+            Some(previous) => write_file_atomic(&previous_path, previous).await,
+            None => async_fs::remove_file(&previous_path).await,
+        };
+        let _ = match self.default_target {
+            Some(target) => replace_symlink(&target, &default_path).await,
+            None => async_fs::remove_file(&default_path).await,
+        };
+        let _ = match self.go_env {
+            Some(go_env) => write_file_atomic(&go_env_path, go_env).await,
+            None => async_fs::remove_file(&go_env_path).await,
+        };
+    }
+}
+
+/// Points the active file, `go.env` and, if requested, the default alias at a version.
+///
+/// If another version was active, it is recorded in the `previous` file for
+/// `gvm use --prev`. Every file is first written under a temporary name and then renamed over the
+/// original, so each individual file is always either old or new. The default
+/// alias is read back afterwards and an error is returned if it does not point
+/// at `release_dir`.
+async fn switch_active_version(version: &str, release_dir: &Path, update_default: bool) -> Res<()> {
+    let environment_path = get_environment_file_path();
+    async_fs::create_dir_all(&environment_path).await?;
+    write_file_atomic(environment_path.join("go.env"), go_env_content(version)).await?;
+
+    let version_path = get_version_file_path();
+    if let Some(previous) = read_active_file().await.filter(|active| active != version) {
+        write_file_atomic(version_path.join("previous"), previous).await?;
+    }
+    write_file_atomic(version_path.join("active"), version).await?;
+
+    if update_default {
+        let default_link = get_alias_file_path().join("default");
+        replace_symlink(release_dir, &default_link).await?;
+        // read the link back, so a filesystem that silently kept the old link
+        // fails here instead of leaving a mismatched toolchain behind
+        let target = async_fs::read_link(&default_link).await?;
+        if target != release_dir {
+            error!(
+                "{} points at {} instead of {}",
+                default_link.display(),
+                target.display(),
+                release_dir.display()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Number of temporary paths handed out by `unique_tmp_path` in this process.
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns a hidden sibling of `path` that is not used by any other call or
+/// process, `.<name>.<pid>.<n>.tmp`.
+fn unique_tmp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let n = TMP_COUNTER.fetch_add(1, atomic::Ordering::Relaxed);
+    path.with_file_name(format!(".{}.{}.{}.tmp", name, process::id(), n))
+}
+
+/// Writes a file by writing a temporary sibling and renaming it over the target.
+///
+/// The temporary file is created exclusively and removed again if the rename
+/// fails, so no existing file other than `path` is ever touched.
+///
+/// # Parameters
+///
+/// * `path`: The file to write.
+/// * `contents`: The new file contents.
+///
+/// # Returns
+///
+/// Returns `io::Result<()>`, which is `Ok(())` once the new content is in place.
+pub async fn write_file_atomic<P: AsRef<Path>, C: AsRef<[u8]>>(
+    path: P,
+    contents: C,
+) -> io::Result<()> {
+    let path = path.as_ref();
+    let tmp_path = unique_tmp_path(path);
+    let mut file = async_fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&tmp_path)
+        .await?;
+    let written = async {
+        file.write_all(contents.as_ref()).await?;
+        file.flush().await?;
+        drop(file);
+        async_fs::rename(&tmp_path, path).await
+    }
+    .await;
+    if written.is_err() {
+        let _ = async_fs::remove_file(&tmp_path).await;
+    }
+    written
+}
+
+/// Replaces a symbolic link by creating a temporary link and renaming it over the target.
+///
+/// The temporary link is a unique hidden sibling (see `unique_tmp_path`) and is
+/// removed again if the rename fails, so `link` either keeps its old target or
+/// points to `original`, and no other file is touched.
+///
+/// # Parameters
+///
+/// * `original`: The path the symlink should point to.
+/// * `link`: The location of the symlink.
+///
+/// # Returns
+///
+/// Returns `io::Result<()>`, which is `Ok(())` once the link points to `original`.
+pub async fn replace_symlink<P: AsRef<Path>, Q: AsRef<Path>>(
+    original: P,
+    link: Q,
+) -> io::Result<()> {
+    let link = link.as_ref();
+    let tmp_link = unique_tmp_path(link);
+    unix_fs::symlink(original, &tmp_link)?;
+    let renamed = async_fs::rename(&tmp_link, link).await;
+    if renamed.is_err() {
+        let _ = async_fs::remove_file(&tmp_link).await;
+    }
+    renamed
+}
+
+/// Computes the Go environment variables for a specified version.
+///
+/// This function derives GOROOT, GOCACHE, GOPATH and GOENV from the GVM
+/// directory layout without touching the filesystem, so the same values can be
+/// written to `go.env`, printed by `gvm env` or passed to `gvm exec`:
+///
+/// - `GOROOT`: `~/.gvm/version/<version>`
+/// - `GOCACHE`: `~/.gvm/cache/<version>/go-build`
+/// - `GOPATH`: `~/.gvm/package/<version>`, or `~/.gvm/package/shared` if the
+///   GOPATH is shared
+/// - `GOENV`: `~/.gvm/environment/go.env`
+///
+/// `~/.gvm` stands for the base directory, see `get_gvm_base_file_path`.
+///
+/// # Parameters
+///
+/// * `version`: The Go version including the "go" prefix (e.g., "go1.16.5").
+///
+/// # Returns
+///
+/// A vector of `(name, path)` pairs in the order they are written to `go.env`.
+pub fn go_env_vars(version: &str) -> Vec<(&'static str, PathBuf)> {
+    let environment_file_path = get_environment_file_path().join("go.env");
+    let goroot = get_version_file_path().join(version);
+    let gocache = get_cache_dir().join(version).join("go-build");
+    let gopath = get_gopath(version);
+
+    vec![
+        ("GOROOT", goroot),
+        ("GOCACHE", gocache),
+        ("GOPATH", gopath),
+        ("GOENV", environment_file_path),
+    ]
+}
+
+/// Returns the directories that have to be prepended to PATH for a specified version.
+///
+/// These are `GOROOT/bin` and `GOPATH/bin`, in that order.
+pub fn go_path_entries(version: &str) -> Vec<PathBuf> {
+    vec![
+        get_version_file_path().join(version).join("bin"),
+        get_gopath(version).join("bin"),
+    ]
+}
+
+/// Shell syntax used when rendering an environment script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ShellSyntax {
+    /// `export KEY="value"` lines for bash, zsh and other POSIX shells.
+    Bash,
+    /// `set -gx KEY "value"` lines for fish.
+    Fish,
+}
+
+/// Quotes a value for POSIX shells (bash, zsh, sh).
+///
+/// Values made up only of characters without special meaning are returned as
+/// is. Everything else is wrapped in single quotes, with embedded single quotes
+/// written as `'\''`, so `$`, backticks, backslashes and spaces are never
+/// interpreted by the shell.
+///
+/// # Parameters
+///
+/// * `value`: The value to quote.
+///
+/// # Returns
+///
+/// The value as a single shell word.
+pub fn shell_quote(value: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "_-./:,+=@%".contains(c);
+    if !value.is_empty() && value.chars().all(is_safe) {
+        return value.to_string();
+    }
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Quotes a value for fish.
+///
+/// The value is wrapped in single quotes. Unlike POSIX shells, fish treats
+/// `\\` and `\'` as escapes inside single quotes, so both are escaped.
+///
+/// # Parameters
+///
+/// * `value`: The value to quote.
+///
+/// # Returns
+///
+/// The value as a single fish word.
+pub fn fish_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Quotes a value for dotenv files.
+///
+/// Values made up only of characters without special meaning are returned as
+/// is. Values without single quotes are wrapped in single quotes, which dotenv
+/// parsers take literally. Everything else is wrapped in double quotes, with
+/// `\`, `"`, `$` and backticks escaped so no parser expands them.
+///
+/// # Parameters
+///
+/// * `value`: The value to quote.
+///
+/// # Returns
+///
+/// The value as a single dotenv value.
+pub fn dotenv_quote(value: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "_-./:,+@%".contains(c);
+    if !value.is_empty() && value.chars().all(is_safe) {
+        return value.to_string();
+    }
+    if !value.contains('\'') {
+        return format!("'{}'", value);
+    }
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        if matches!(c, '\\' | '"' | '$' | '`') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Renders a script that sets up the Go environment for a specified version.
+///
+/// The script sets every variable from `go_env_vars` and prepends the
+/// directories from `go_path_entries` to PATH. Directories already in PATH are
+/// not added again, so evaluating the script repeatedly doesn't grow PATH.
+/// Values are quoted with `shell_quote` or `fish_quote`.
+///
+/// # Parameters
+///
+/// * `version`: The Go version including the "go" prefix (e.g., "go1.16.5").
+/// * `shell`: The shell syntax to render.
+///
+/// # Returns
+///
+/// The script as a `String`, one statement per line.
+pub fn render_env_script(version: &str, shell: ShellSyntax) -> String {
+    let mut script = String::new();
+    let quote = match shell {
+        ShellSyntax::Bash => shell_quote,
+        ShellSyntax::Fish => fish_quote,
+    };
+    let path_entries: Vec<String> = go_path_entries(version)
+        .iter()
+        .map(|p| quote(&p.to_string_lossy()))
+        .collect();
+
+    match shell {
+        ShellSyntax::Bash => {
+            for (env_key, env_path) in go_env_vars(version) {
+                script.push_str(&format!(
+                    "export {}={}\n",
+                    env_key,
+                    quote(&env_path.to_string_lossy())
+                ));
+            }
+            // prepend in reverse so the entries end up in their original order
+            for entry in path_entries.iter().rev() {
+                script.push_str(&format!(
+                    "case \":$PATH:\" in *:{0}:*) ;; *) export PATH={0}:\"$PATH\" ;; esac\n",
+                    entry
+                ));
+            }
+        }
+        ShellSyntax::Fish => {
+            for (env_key, env_path) in go_env_vars(version) {
+                script.push_str(&format!(
+                    "set -gx {} {}\n",
+                    env_key,
+                    quote(&env_path.to_string_lossy())
+                ));
+            }
+            for entry in path_entries.iter().rev() {
+                script.push_str(&format!(
+                    "contains -- {0} $PATH; or set -gx PATH {0} $PATH\n",
+                    entry
+                ));
+            }
+        }
+    }
+
+    script
+}
+
+/// Renders the content of the `go.env` file for a specified version.
+///
+/// Every variable from `go_env_vars` is written as `KEY=value` on its own line.
+/// Values are quoted with `shell_quote`, as the init script sources the file.
+fn go_env_content(version: &str) -> String {
+    let mut env_content = String::new();
+
+    for (env_key, env_path) in go_env_vars(version) {
+        env_content.push_str(&format!(
+            "{}={}\n",
+            env_key,
+            shell_quote(&env_path.to_string_lossy())
+        ));
+    }
+
+    env_content
+}
+
+/// Initializes the Go environment for a specified version.
+///
+/// This function sets up the necessary environment variables for a given Go version,
+/// including GOROOT, GOPATH, GOCACHE, and updates the PATH.
+///
+/// # Parameters
+///
+/// * `version`: An `Option<String>` representing the Go version to initialize.
+///   If `Some`, it should contain the version string (e.g., "go1.16.5").
+///   If `None`, an error message will be logged.
+///
+/// # Returns
+///
+/// * `Res<()>`: A Result type alias. Returns `Ok(())` if the environment is successfully
+///   initialized, or an error if the initialization fails or no version is provided.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * No version is provided (i.e., `version` is `None`).
+/// * There are issues setting environment variables or reading the current PATH.
+pub async fn init_go_environment(version: Option<String>) -> Res<()> {
+    let active_version = match version {
+        Some(v) => v,
+        None => match get_active_version().await {
+            Some(v) => v,
+            None => error!("No active version found. Use 'gvm list' to see available versions."),
+        },
+    };
+
+    info!(
+        "Prepare go environment for version '{}' ...",
+        active_version
+    );
+
+    let environment_path = get_environment_file_path();
+    match async_fs::create_dir_all(&environment_path).await {
+        Ok(_) => success!("Environment directory created."),
+        Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            info!("Environment directory already exists.")
+        }
+        Err(e) => error!("Error creating environment directory: {}", e),
+    }
+
+    let environment_file_path = environment_path.join("go.env");
+    write_file_atomic(&environment_file_path, go_env_content(&active_version)).await?;
+
+    success!("Go environment prepared for version '{}'.", &active_version);
+
+    Ok(())
+}
+
+/// Reads the version recorded in the 'active' file in the GVM version directory.
+///
+/// Surrounding whitespace, such as a trailing newline from editing the file by
+/// hand, is ignored. Unlike `get_active_version`, the version is returned even
+/// if its directory no longer exists.
+///
+/// # Returns
+///
+/// - `Some(String)`: The recorded version (e.g., "go1.16.5"), if it starts
+///   with "go".
+/// - `None`: If no active version is set, the file can't be read,
+///   or the content doesn't represent a valid Go version (i.e., doesn't start with "go").
+pub async fn read_active_file() -> Option<String> {
+    let active_path = get_version_file_path().join("active");
+
+    async_fs::read_to_string(active_path)
+        .await
+        .ok()
+        .and_then(|active_version| {
+            let active_version = active_version.trim();
+            if active_version.starts_with("go") {
+                Some(active_version.to_string())
+            } else {
+                None
+            }
+        })
+}
+
+/// Reads the version that was active before the current one, recorded in the
+/// 'previous' file in the GVM version directory by every version switch.
+///
+/// # Returns
+///
+/// - `Some(String)`: The previous version (e.g., "go1.16.5").
+/// - `None`: If no version switch was recorded yet, or the file doesn't hold a
+///   Go version.
+pub async fn read_previous_file() -> Option<String> {
+    let previous_path = get_version_file_path().join("previous");
+
+    async_fs::read_to_string(previous_path)
+        .await
+        .ok()
+        .map(|previous| previous.trim().to_string())
+        .filter(|previous| previous.starts_with("go"))
+}
+
+/// Retrieves the currently active Go version managed by GVM.
+///
+/// This function reads the 'active' file in the GVM version directory
+/// to determine which Go version is currently set as active, and checks that
+/// the version is still installed. A version whose directory was deleted by
+/// hand is reported with a warning, since its `go.env` points at a GOROOT that
+/// no longer exists.
+///
+/// # Returns
+///
+/// - `Some(String)`: The active Go version as a string (e.g., "go1.16.5"),
+///   if a valid version is found and its directory exists.
+/// - `None`: If no active version is set, the file can't be read, the
+///   content doesn't represent a valid Go version, or the version's
+///   directory is missing.
+pub async fn get_active_version() -> Option<String> {
+    let active_version = read_active_file().await?;
+    if !get_version_file_path().join(&active_version).is_dir() {
+        warning!(
+            "Active version {} is no longer installed. Run 'gvm use <installed version>' to switch to another one.",
+            active_version
+        );
+        return None;
+    }
+    Some(active_version)
+}
+
+/// Checks if a given Go version is currently active in the GVM (Go Version Manager) system.
+///
+/// This function compares the provided version string with the currently active version
+/// retrieved from the GVM configuration.
+///
+/// # Parameters
+///
+/// * `version`: A String representing the Go version to check. It should include the "go" prefix
+///   (e.g., "go1.16.5").
+///
+/// # Returns
+///
+/// * `true` if the provided version matches the currently active version.
+/// * `false` if the versions don't match or if there is no active version set.
+pub async fn is_version_active(version: &str) -> bool {
+    let active_version = get_active_version();
+    active_version
+        .await
+        .map(|av| av == version)
+        .unwrap_or(false)
+}
+
+/// Where a project-pinned Go version was found.
+///
+/// The variants are listed in precedence order: a `.go-version` file wins over
+/// the `toolchain` directive, which wins over the `go` directive in `go.mod`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProjectVersionSource {
+    GoVersionFile,
+    Toolchain,
+    GoDirective,
+}
+
+impl fmt::Display for ProjectVersionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectVersionSource::GoVersionFile => write!(f, "{}", config::GO_VERSION_FILE),
+            ProjectVersionSource::Toolchain => write!(f, "toolchain directive"),
+            ProjectVersionSource::GoDirective => write!(f, "go directive"),
+        }
+    }
+}
+
+/// A Go version pinned by a project file.
+#[derive(Debug, Clone)]
+pub struct ProjectVersion {
+    pub version: String,
+    pub source: ProjectVersionSource,
+    pub path: PathBuf,
+}
+
+/// Searches `start` and its parent directories for a file with the given name.
+///
+/// The walk stops at the filesystem root.
+///
+/// # Returns
+///
+/// The path of the nearest matching file, or `None` if there is none.
+pub fn find_file_upwards(start: &Path, name: &str) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Parses the `go` and `toolchain` directives of a `go.mod` file.
+///
+/// Both `go 1.22` and `go 1.22.0` are accepted. A `toolchain default`
+/// directive is ignored.
+///
+/// # Returns
+///
+/// A tuple of the `go` directive and the `toolchain` directive, each
+/// normalized to start with "go".
+pub fn parse_go_mod(content: &str) -> (Option<String>, Option<String>) {
+    let mut go_directive = None;
+    let mut toolchain = None;
+
+    for line in content.lines() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        let mut fields = line.split_whitespace();
+        match (fields.next(), fields.next()) {
+            (Some("go"), Some(version)) => {
+                go_directive = Some(get_real_version(version.to_string()))
+            }
+            (Some("toolchain"), Some(version)) if version != "default" => {
+                toolchain = Some(get_real_version(version.to_string()))
+            }
+            _ => {}
+        }
+    }
+
+    (go_directive, toolchain)
+}
+
+/// Collects every Go version pinned for the project containing `start`.
+///
+/// The nearest `.go-version` file and the nearest `go.mod` are looked up
+/// independently by walking up from `start`.
+///
+/// # Returns
+///
+/// The pinned versions sorted by precedence (highest first).
+pub async fn find_project_versions(start: &Path) -> Vec<ProjectVersion> {
+    let mut versions = Vec::new();
+
+    if let Some(path) = find_file_upwards(start, config::GO_VERSION_FILE) {
+        if let Ok(content) = async_fs::read_to_string(&path).await {
+            let version = content.trim();
+            if !version.is_empty() {
+                versions.push(ProjectVersion {
+                    version: get_real_version(version.to_string()),
+                    source: ProjectVersionSource::GoVersionFile,
+                    path,
+                });
+            }
+        }
+    }
+
+    if let Some(path) = find_file_upwards(start, config::GO_MOD_FILE) {
+        if let Ok(content) = async_fs::read_to_string(&path).await {
+            let (go_directive, toolchain) = parse_go_mod(&content);
+            if let Some(version) = toolchain {
+                versions.push(ProjectVersion {
+                    version,
+                    source: ProjectVersionSource::Toolchain,
+                    path: path.clone(),
+                });
+            }
+            if let Some(version) = go_directive {
+                versions.push(ProjectVersion {
+                    version,
+                    source: ProjectVersionSource::GoDirective,
+                    path,
+                });
+            }
+        }
+    }
+
+    versions.sort_by_key(|v| v.source);
+    versions
+}
+
+/// Resolves the Go version pinned for the project containing `start`.
+///
+/// The precedence is `.go-version` > `toolchain` directive > `go` directive.
+/// If the sources disagree, the chosen version and the alternatives are printed.
+///
+/// # Returns
+///
+/// The pinned version with the highest precedence, or `None` if the project
+/// does not pin a version.
+pub async fn resolve_project_version(start: &Path) -> Option<ProjectVersion> {
+    let mut versions = find_project_versions(start).await.into_iter();
+    let chosen = versions.next()?;
+
+    let alternatives: Vec<ProjectVersion> =
+        versions.filter(|v| v.version != chosen.version).collect();
+    if !alternatives.is_empty() {
+        warning!(
+            "Using {} from {} ({}); it takes precedence over:",
+            chosen.version,
+            chosen.source,
+            chosen.path.display()
+        );
+        for alternative in alternatives {
+            warning!(
+                "  {} from {} ({})",
+                alternative.version,
+                alternative.source,
+                alternative.path.display()
+            );
+        }
+    }
+
+    Some(chosen)
+}
+
+/// Reads the Go version required by the nearest `go.mod`, ignoring
+/// `.go-version`.
+///
+/// The `toolchain` directive names an exact version and wins. Otherwise the
+/// `go` directive is used, which is only a minimum, so a stable `go 1.22` or
+/// `go 1.22.0` is widened to the minor version "go1.22" and later resolved to
+/// its newest patch release.
+///
+/// # Parameters
+///
+/// * `start`: The directory to start searching from.
+///
+/// # Returns
+///
+/// The version including the "go" prefix and where it was found, or an error
+/// if no `go.mod` is found or it has neither directive.
+pub async fn go_mod_version(start: &Path) -> Res<ProjectVersion> {
+    let path = match find_file_upwards(start, config::GO_MOD_FILE) {
+        Some(path) => path,
+        None => error!(
+            "No {} found in {} or its parent directories.",
+            config::GO_MOD_FILE,
+            start.display()
+        ),
+    };
+    let content = match async_fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(e) => error!("Failed to read {}: {}", path.display(), e),
+    };
+
+    let (version, source) = match parse_go_mod(&content) {
+        (_, Some(toolchain)) => (toolchain, ProjectVersionSource::Toolchain),
+        (Some(go_directive), None) => {
+            let (parts, suffix) = parse_version_parts(&go_directive);
+            let version = if parts.len() >= 2 && suffix.is_empty() {
+                format!("go{}.{}", parts[0], parts[1])
+            } else {
+                go_directive
+            };
+            (version, ProjectVersionSource::GoDirective)
+        }
+        (None, None) => error!("No go or toolchain directive found in {}.", path.display()),
+    };
+    Ok(ProjectVersion {
+        version,
+        source,
+        path,
+    })
+}
+
+/// Helpers shared by the unit tests.
+#[cfg(test)]
+pub(crate) mod test_support {
+    use std::{cell::RefCell, fmt, fmt::Debug, path::PathBuf, sync::OnceLock};
+
+    use super::Verbosity;
+    use crate::{config, Res};
+
+    thread_local! {
+        /// The verbosity and the lines of a running `capture_log_lines`.
+        static LOG_CAPTURE: RefCell<Option<(Verbosity, Vec<String>)>> =
+            const { RefCell::new(None) };
+    }
+
+    /// Runs `f` at `verbosity` and returns the lines the log macros printed.
+    pub(crate) fn capture_log_lines(verbosity: Verbosity, f: impl FnOnce()) -> Vec<String> {
+        LOG_CAPTURE.with(|capture| *capture.borrow_mut() = Some((verbosity, Vec::new())));
+        f();
+        LOG_CAPTURE
+            .with(|capture| capture.borrow_mut().take())
+            .map(|(_, lines)| lines)
+            .unwrap_or_default()
+    }
+
+    /// Records a line of `print_log` while `capture_log_lines` runs.
+    ///
+    /// Returns `false` if nothing is being captured, so the line is printed.
+    pub(super) fn capture_log(level: Verbosity, line: fmt::Arguments) -> bool {
+        LOG_CAPTURE.with(|capture| match capture.borrow_mut().as_mut() {
+            Some((verbosity, lines)) => {
+                if verbosity.shows(level) {
+                    lines.push(line.to_string());
+                }
+                true
+            }
+            None => false,
+        })
+    }
+
+    /// Names that point outside of the directory they are joined to, or are
+    /// no file name at all. The escaping ones end in `ESCAPE_NAME`.
+    pub(crate) const MALICIOUS_NAMES: &[&str] = &[
+        "",
+        ".",
+        "..",
+        "/",
+        "../../etc",
+        "../../gvm-escape",
+        "/tmp/gvm-escape",
+        "go1.22.0/../../../gvm-escape",
+        "a\\..\\gvm-escape",
+        "go1.22.0\0",
+        "\0",
+    ];
+
+    /// Last component of the escaping `MALICIOUS_NAMES`.
+    const ESCAPE_NAME: &str = "gvm-escape";
+
+    /// Asserts that a command rejected a malicious name and that nothing was
+    /// created where the name points.
+    pub(crate) fn assert_rejected<T: Debug>(name: &str, result: Res<T>) {
+        let err = match result {
+            Ok(value) => panic!("{:?} was accepted: {:?}", name, value),
+            Err(e) => e.to_string(),
+        };
+        assert!(err.starts_with("Invalid"), "{:?}: {}", name, err);
+
+        let root = gvm_root();
+        for dir in root.ancestors().take(4) {
+            assert!(!dir.join(ESCAPE_NAME).exists(), "{:?} escaped", name);
+        }
+        assert!(!PathBuf::from("/tmp").join(ESCAPE_NAME).exists());
+    }
+
+    /// Serializes tests that change the active version or the `default` alias.
+    pub(crate) async fn lock_activation() -> tokio::sync::MutexGuard<'static, ()> {
+        static LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+        LOCK.lock().await
+    }
+
+    /// Removes the active, previous and `go.env` files and the default alias.
+    pub(crate) fn clear_activation() {
+        let version_dir = gvm_root().join(config::GVM_VERSION_PATH);
+        let _ = std::fs::remove_file(version_dir.join("active"));
+        let _ = std::fs::remove_file(version_dir.join("previous"));
+        let _ = std::fs::remove_file(gvm_root().join(config::GVM_ENVIRONMENT_PATH).join("go.env"));
+        let default = gvm_root().join(config::GVM_ALIAS_PATH).join("default");
+        let _ = std::fs::remove_file(&default);
+        let _ = std::fs::remove_dir_all(&default);
+    }
+
+    /// Creates an installed version with a `go` binary that prints its version.
+    pub(crate) fn fake_version(version: &str) -> PathBuf {
+        let version_dir = gvm_root().join(config::GVM_VERSION_PATH).join(version);
+        let bin_dir = version_dir.join("bin");
+        std::fs::create_dir_all(&bin_dir).unwrap();
+        let go = bin_dir.join("go");
+        std::fs::write(&go, format!("#!/bin/sh\necho go version {}\n", version)).unwrap();
+        std::fs::set_permissions(&go, std::os::unix::fs::PermissionsExt::from_mode(0o755)).unwrap();
+        version_dir
+    }
+
+    /// Creates an alias pointing to a version, or to another alias with `to_alias`.
+    pub(crate) fn fake_alias(alias: &str, target: &str, to_alias: bool) -> PathBuf {
+        let alias_dir = gvm_root().join(config::GVM_ALIAS_PATH);
+        let target = if to_alias {
+            alias_dir.join(target)
+        } else {
+            gvm_root().join(config::GVM_VERSION_PATH).join(target)
+        };
+        let alias_path = alias_dir.join(alias);
+        let _ = std::fs::remove_file(&alias_path);
+        std::os::unix::fs::symlink(target, &alias_path).unwrap();
+        alias_path
+    }
+
+    /// Returns whether an alias exists, even if it no longer resolves.
+    pub(crate) fn alias_exists(alias: &str) -> bool {
+        gvm_root()
+            .join(config::GVM_ALIAS_PATH)
+            .join(alias)
+            .symlink_metadata()
+            .is_ok()
+    }
+
+    /// Returns the base directory used by the unit tests instead of `~/.gvm`.
+    ///
+    /// It is created once per test process with the directories `gvm init`
+    /// creates, and contains a space, so paths derived from it need quoting.
+    pub(crate) fn gvm_root() -> PathBuf {
+        static ROOT: OnceLock<tempfile::TempDir> = OnceLock::new();
+        ROOT.get_or_init(|| {
+            let root = tempfile::Builder::new()
+                .prefix("gvm test ")
+                .tempdir()
+                .unwrap();
+            for dir in [
+                config::GVM_ARCHIVE_PATH,
+                config::GVM_CACHE_PATH,
+                config::GVM_ENVIRONMENT_PATH,
+                config::GVM_VERSION_PATH,
+                config::GVM_PACKAGE_PATH,
+                config::GVM_ALIAS_PATH,
+            ] {
+                std::fs::create_dir_all(root.path().join(dir)).unwrap();
+            }
+            root
+        })
+        .path()
+        .to_path_buf()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::clear_activation;
+
+    /// Names of the hidden entries left in `dir`, such as temporary files.
+    fn hidden_entries(dir: &Path) -> Vec<String> {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.starts_with('.'))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn replace_symlink_leaves_unrelated_tmp_file_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let link = dir.path().join("default");
+        fs::write(dir.path().join("default.tmp"), "mine").unwrap();
+
+        replace_symlink("go1.22.0", &link).await.unwrap();
+        replace_symlink("go1.23.0", &link).await.unwrap();
+
+        assert_eq!(fs::read_link(&link).unwrap(), Path::new("go1.23.0"));
+        assert_eq!(
+            fs::read_to_string(dir.path().join("default.tmp")).unwrap(),
+            "mine"
+        );
+        assert!(hidden_entries(dir.path()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn replace_symlink_cleans_up_when_rename_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        // a symlink cannot be renamed over a directory
+        let link = dir.path().join("default");
+        fs::create_dir(&link).unwrap();
+
+        assert!(replace_symlink("go1.22.0", &link).await.is_err());
+        assert!(link.is_dir());
+        assert!(hidden_entries(dir.path()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn write_file_atomic_leaves_unrelated_tmp_file_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("go.env");
+        fs::write(dir.path().join("go.tmp"), "mine").unwrap();
+
+        write_file_atomic(&path, "GOROOT=/a\n").await.unwrap();
+        write_file_atomic(&path, "GOROOT=/b\n").await.unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "GOROOT=/b\n");
+        assert_eq!(
+            fs::read_to_string(dir.path().join("go.tmp")).unwrap(),
+            "mine"
+        );
+        assert!(hidden_entries(dir.path()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn write_file_atomic_cleans_up_when_rename_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("go.env");
+        fs::create_dir(&path).unwrap();
+
+        assert!(write_file_atomic(&path, "GOROOT=/a\n").await.is_err());
+        assert!(path.is_dir());
+        assert!(hidden_entries(dir.path()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn create_symlink_always_resolves_and_is_idempotent() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("go1.22.0");
+        let b = dir.path().join("go1.23.0");
+        fs::create_dir(&a).unwrap();
+        fs::create_dir(&b).unwrap();
+        let link = dir.path().join("default");
+        create_symlink(&a, &link).await.unwrap();
+
+        // watch the link from another thread while it is being replaced
+        let done = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let watcher = {
+            let link = link.clone();
+            let done = done.clone();
+            std::thread::spawn(move || {
+                let mut misses = 0;
+                while !done.load(atomic::Ordering::Relaxed) {
+                    if !link.is_dir() {
+                        misses += 1;
+                    }
+                }
+                misses
+            })
+        };
+        for i in 0..200 {
+            let target = if i % 2 == 0 { &b } else { &a };
+            create_symlink(target, &link).await.unwrap();
+            assert_eq!(&fs::read_link(&link).unwrap(), target);
+        }
+        done.store(true, atomic::Ordering::Relaxed);
+        assert_eq!(watcher.join().unwrap(), 0);
+
+        // re-running with the same target changes nothing
+        create_symlink(&a, &link).await.unwrap();
+        create_symlink(&a, &link).await.unwrap();
+        assert_eq!(fs::read_link(&link).unwrap(), a);
+        assert!(link.is_dir());
+        assert!(hidden_entries(dir.path()).is_empty());
+    }
+
+    /// Values with characters that mean something to a shell.
+    const NASTY_VALUES: &[&str] = &[
+        "plain",
+        "",
+        "with space",
+        "dollar $HOME ${PATH}",
+        "back`id`tick",
+        "sub $(echo pwned)",
+        "single 'quote'",
+        "double \"quote\"",
+        "back\\slash \\n",
+        "new\nline",
+        "glob * ? [a]",
+        "mixed '\"$`\\ end",
+    ];
+
+    /// A version name that ends up in every path derived from it.
+    const NASTY_VERSION: &str = "go1.22.0 $HOME `id` 'a' \"b\" \\c";
+
+    /// Runs `script` with `sh` and splits what it prints on NUL.
+    fn sh_values(script: &str) -> Vec<String> {
+        let output = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .env("HOME", "/home/nobody")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let mut values: Vec<String> = stdout.split('\0').map(String::from).collect();
+        assert_eq!(values.pop().as_deref(), Some(""));
+        values
+    }
+
+    /// Undoes `fish_quote` the way fish reads a single-quoted word.
+    fn fish_unquote(word: &str) -> String {
+        let inner = word.strip_prefix('\'').unwrap().strip_suffix('\'').unwrap();
+        let mut value = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some(c @ ('\\' | '\'')) => value.push(c),
+                    Some(c) => {
+                        value.push('\\');
+                        value.push(c);
+                    }
+                    None => value.push('\\'),
+                },
+                '\'' => panic!("unescaped quote in {}", word),
+                c => value.push(c),
+            }
+        }
+        value
+    }
+
+    fn env_values(version: &str) -> Vec<String> {
+        go_env_vars(version)
+            .into_iter()
+            .map(|(_, path)| path.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn shell_quote_round_trips_through_sh() {
+        let words: Vec<String> = NASTY_VALUES.iter().map(|v| shell_quote(v)).collect();
+        let script = format!("printf '%s\\0' {}", words.join(" "));
+
+        assert_eq!(sh_values(&script), NASTY_VALUES);
+    }
+
+    #[test]
+    fn shell_quote_leaves_safe_values_alone() {
+        assert_eq!(
+            shell_quote("/home/me/.gvm/go1.22.0"),
+            "/home/me/.gvm/go1.22.0"
+        );
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+
+    #[test]
+    fn fish_quote_round_trips() {
+        for value in NASTY_VALUES {
+            assert_eq!(&fish_unquote(&fish_quote(value)), value);
+        }
+        assert_eq!(fish_quote("a\\'b"), "'a\\\\\\'b'");
+    }
+
+    #[test]
+    fn go_env_content_round_trips_through_sh() {
+        let script = format!(
+            "{}printf '%s\\0' \"$GOROOT\" \"$GOCACHE\" \"$GOPATH\" \"$GOENV\"",
+            go_env_content(NASTY_VERSION)
+        );
+
+        assert_eq!(sh_values(&script), env_values(NASTY_VERSION));
+    }
+
+    #[test]
+    fn render_env_script_round_trips_through_sh() {
+        let script = render_env_script(NASTY_VERSION, ShellSyntax::Bash);
+        // evaluated twice to check that PATH is not extended again
+        let script = format!(
+            "PATH=/usr/bin:/bin\n{0}{0}printf '%s\\0' \"$GOROOT\" \"$GOCACHE\" \"$GOPATH\" \"$GOENV\" \"$PATH\"",
+            script
+        );
+
+        let mut values = sh_values(&script);
+        let path = values.pop().unwrap();
+        assert_eq!(values, env_values(NASTY_VERSION));
+        let entries: Vec<String> = go_path_entries(NASTY_VERSION)
+            .iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(path, format!("{}:/usr/bin:/bin", entries.join(":")));
+    }
+
+    #[test]
+    fn render_env_script_quotes_fish_values() {
+        let script = render_env_script(NASTY_VERSION, ShellSyntax::Fish);
+        let values: Vec<String> = script
+            .lines()
+            .filter_map(|line| line.strip_prefix("set -gx "))
+            .filter(|line| !line.starts_with("PATH "))
+            .map(|line| fish_unquote(line.split_once(' ').unwrap().1))
+            .collect();
+
+        assert_eq!(values, env_values(NASTY_VERSION));
+    }
+
+    #[test]
+    fn go_arch_name_maps_rust_architectures() {
+        for (arch, go_arch) in [
+            ("x86_64", "amd64"),
+            ("x86", "386"),
+            ("aarch64", "arm64"),
+            ("arm", "armv6l"),
+            ("loongarch64", "loong64"),
+            ("riscv64", "riscv64"),
+            ("s390x", "s390x"),
+        ] {
+            assert_eq!(go_arch_name_for_endian(arch, true), go_arch);
+        }
+    }
+
+    #[test]
+    fn mac_targets_map_to_darwin_archives() {
+        assert_eq!(go_os_name("macos"), "darwin");
+        assert_eq!(go_os_name("linux"), "linux");
+        assert_eq!(go_os_name("freebsd"), "freebsd");
+
+        // Apple silicon and Intel Macs, as reported by std::env::consts
+        for (arch, platform) in [("aarch64", "darwin-arm64"), ("x86_64", "darwin-amd64")] {
+            let name = format!("{}-{}", go_os_name("macos"), go_arch_name(arch));
+            assert_eq!(name, platform);
+            assert_eq!(
+                archive_platform(&format!("https://go.dev/dl/go1.22.0.{}.tar.gz", name)),
+                Some(("darwin".to_string(), go_arch_name(arch).to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn go_arch_name_depends_on_byte_order() {
+        assert_eq!(go_arch_name_for_endian("powerpc64", true), "ppc64le");
+        assert_eq!(go_arch_name_for_endian("powerpc64", false), "ppc64");
+        assert_eq!(go_arch_name_for_endian("mips64", true), "mips64le");
+        assert_eq!(go_arch_name_for_endian("mips64", false), "mips64");
+        assert_eq!(go_arch_name_for_endian("mips", true), "mipsle");
+        assert_eq!(go_arch_name_for_endian("mips", false), "mips");
+        assert_eq!(
+            go_arch_name("x86_64"),
+            go_arch_name_for_endian("x86_64", cfg!(target_endian = "little"))
+        );
+    }
+
+    #[test]
+    fn validate_name_rejects_malicious_names() {
+        for name in test_support::MALICIOUS_NAMES {
+            assert!(validate_name("version", name).is_err(), "{:?}", name);
+            assert!(validate_alias_name(name).is_err(), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn validate_name_accepts_plain_names() {
+        for name in [
+            "go1.22.0",
+            "go1.23rc1",
+            "go1.22.0.darwin-arm64",
+            "stable",
+            "v1.21",
+        ] {
+            assert!(validate_name("version", name).is_ok(), "{:?}", name);
+            assert!(validate_alias_name(name).is_ok(), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn validate_alias_name_rejects_reserved_and_hidden_names() {
+        for name in config::RESERVED_ALIAS_NAMES {
+            assert!(validate_alias_name(name).is_err(), "{:?}", name);
+        }
+        assert!(validate_alias_name(".stable").is_err());
+        assert!(validate_alias_name(".default.123.0.tmp").is_err());
+    }
+
+    #[test]
+    fn parse_version_arg_rejects_malicious_names() {
+        for name in test_support::MALICIOUS_NAMES {
+            assert!(parse_version_arg(name.to_string()).is_err(), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn expand_path_expands_home() {
+        let home = dirs::home_dir().unwrap();
+
+        assert_eq!(expand_path("~").unwrap(), home);
+        assert_eq!(expand_path("~/sdks/gvm").unwrap(), home.join("sdks/gvm"));
+        // only a leading `~` followed by a separator means the home directory
+        assert_eq!(expand_path("~gvm").unwrap(), Path::new("~gvm"));
+        assert_eq!(expand_path("/a/~/b").unwrap(), Path::new("/a/~/b"));
+    }
+
+    #[test]
+    fn expand_path_expands_variables() {
+        let home = env::var("HOME").unwrap();
+
+        assert_eq!(
+            expand_path("$HOME/gvm").unwrap(),
+            Path::new(&format!("{}/gvm", home))
+        );
+        assert_eq!(
+            expand_path("${HOME}gvm").unwrap(),
+            Path::new(&format!("{}gvm", home))
+        );
+        assert_eq!(expand_path("/a/$/b").unwrap(), Path::new("/a/$/b"));
+        assert!(expand_path("$GVM_TEST_SURELY_UNSET_VARIABLE/gvm").is_err());
+    }
+
+    #[test]
+    fn parse_gvm_root_rejects_relative_paths() {
+        let home = dirs::home_dir().unwrap();
+
+        assert_eq!(
+            parse_gvm_root(OsStr::new("~/gvm")).unwrap(),
+            home.join("gvm")
+        );
+        assert_eq!(
+            parse_gvm_root(OsStr::new("$HOME/gvm")).unwrap(),
+            Path::new(&env::var("HOME").unwrap()).join("gvm")
+        );
+        assert!(parse_gvm_root(OsStr::new("/opt/gvm")).is_ok());
+        assert!(parse_gvm_root(OsStr::new("gvm")).is_err());
+        assert!(parse_gvm_root(OsStr::new("./gvm")).is_err());
+        assert!(parse_gvm_root(OsStr::new("../gvm")).is_err());
+        assert!(parse_gvm_root(OsStr::new("~gvm")).is_err());
+    }
+
+    #[test]
+    fn gvm_base_file_path_prefers_gvm_root() {
+        let home = Some(PathBuf::from("/home/me"));
+
+        assert_eq!(
+            gvm_base_file_path(Some(OsStr::new("/opt/gvm")), home.clone()),
+            Path::new("/opt/gvm")
+        );
+        assert_eq!(
+            gvm_base_file_path(Some(OsStr::new("")), home.clone()),
+            Path::new("/home/me").join(config::GVM_MAIN_PATH)
+        );
+        assert_eq!(
+            gvm_base_file_path(None, home),
+            Path::new("/home/me").join(config::GVM_MAIN_PATH)
+        );
+    }
+
+    #[test]
+    fn gvm_base_file_path_falls_back_to_the_temp_dir_without_home() {
+        assert_eq!(
+            gvm_base_file_path(None, None),
+            env::temp_dir().join(config::GVM_FALLBACK_PATH)
+        );
+        assert_eq!(
+            gvm_base_file_path(Some(OsStr::new("")), None),
+            env::temp_dir().join(config::GVM_FALLBACK_PATH)
+        );
+        assert_eq!(
+            gvm_base_file_path(Some(OsStr::new("/opt/gvm")), None),
+            Path::new("/opt/gvm")
+        );
+    }
+
+    #[test]
+    fn derived_paths_are_rooted_in_the_gvm_root() {
+        let root = test_support::gvm_root();
+        assert_eq!(get_gvm_base_file_path(), root);
+
+        for (path, dir) in [
+            (get_cache_dir(), config::GVM_CACHE_PATH),
+            (get_environment_file_path(), config::GVM_ENVIRONMENT_PATH),
+            (get_version_file_path(), config::GVM_VERSION_PATH),
+            (get_package_file_path(), config::GVM_PACKAGE_PATH),
+            (get_alias_file_path(), config::GVM_ALIAS_PATH),
+        ] {
+            assert_eq!(path, root.join(dir));
+        }
+        for path in [
+            get_release_cache_file_path(),
+            get_archive_file_path(),
+            get_archive_cache_path(),
+            get_shims_path(),
+        ] {
+            assert!(path.starts_with(&root), "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn go_env_vars_follow_the_gvm_layout() {
+        let root = test_support::gvm_root();
+
+        assert_eq!(
+            go_env_vars("go1.22.0"),
+            [
+                ("GOROOT", root.join("version/go1.22.0")),
+                ("GOCACHE", root.join("cache/go1.22.0/go-build")),
+                ("GOPATH", root.join("package/go1.22.0")),
+                ("GOENV", root.join("environment/go.env")),
+            ]
+        );
+        assert_eq!(
+            go_path_entries("go1.22.0"),
+            [
+                root.join("version/go1.22.0/bin"),
+                root.join("package/go1.22.0/bin"),
+            ]
+        );
+    }
+
+    #[test]
+    fn shared_gopaths_are_the_same_for_every_version() {
+        let package = get_package_file_path();
+
+        assert_eq!(gopath("go1.22.0", false), package.join("go1.22.0"));
+        assert_eq!(gopath("go1.23.1", false), package.join("go1.23.1"));
+        assert_eq!(
+            gopath("go1.22.0", true),
+            package.join(config::SHARED_GOPATH_NAME)
+        );
+        assert_eq!(gopath("go1.22.0", true), gopath("go1.23.1", true));
+        assert_ne!(gopath("go1.22.0", true), gopath("go1.22.0", false));
+    }
+
+    #[test]
+    fn unique_tmp_path_is_hidden_and_unique() {
+        let path = Path::new("/tmp/gvm/alias/default");
+        let a = unique_tmp_path(path);
+        let b = unique_tmp_path(path);
+
+        assert_ne!(a, b);
+        assert_eq!(a.parent(), path.parent());
+        let name = a.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with(".default."));
+        assert!(name.ends_with(".tmp"));
+    }
+
+    /// Reads the active, previous and `go.env` files and the default alias.
+    fn activation_files() -> [Option<String>; 4] {
+        let read = |path: PathBuf| fs::read_to_string(path).ok();
+        [
+            read(get_version_file_path().join("active")),
+            read(get_version_file_path().join("previous")),
+            read(get_environment_file_path().join("go.env")),
+            fs::read_link(get_alias_file_path().join("default"))
+                .ok()
+                .map(|target| target.display().to_string()),
+        ]
+    }
+
+    #[tokio::test]
+    async fn activate_version_records_the_switch() {
+        let _lock = test_support::lock_activation().await;
+        clear_activation();
+        test_support::fake_version("go1.15.1");
+        let release_dir = test_support::fake_version("go1.15.2");
+
+        activate_version("go1.15.1".to_string(), true)
+            .await
+            .unwrap();
+        activate_version("go1.15.2".to_string(), true)
+            .await
+            .unwrap();
+
+        let [active, previous, go_env, default] = activation_files();
+        assert_eq!(active.as_deref(), Some("go1.15.2"));
+        assert_eq!(previous.as_deref(), Some("go1.15.1"));
+        assert!(go_env.unwrap().contains("go1.15.2"));
+        assert_eq!(default, Some(release_dir.display().to_string()));
+        clear_activation();
+    }
+
+    #[tokio::test]
+    async fn activate_version_repoints_a_conflicting_default_alias() {
+        let _lock = test_support::lock_activation().await;
+        clear_activation();
+        test_support::fake_version("go1.15.5");
+        let release_dir = test_support::fake_version("go1.15.6");
+        test_support::fake_alias("default", "go1.15.5", false);
+
+        activate_version("go1.15.6".to_string(), true)
+            .await
+            .unwrap();
+
+        let default = fs::read_link(get_alias_file_path().join("default")).unwrap();
+        assert_eq!(default, release_dir);
+        clear_activation();
+    }
+
+    #[tokio::test]
+    async fn activate_version_restores_the_previous_state_on_failure() {
+        let _lock = test_support::lock_activation().await;
+        clear_activation();
+        test_support::fake_version("go1.15.3");
+        test_support::fake_version("go1.15.4");
+        activate_version("go1.15.3".to_string(), false)
+            .await
+            .unwrap();
+        fs::write(get_version_file_path().join("previous"), "go1.15.0").unwrap();
+        // the default alias is the last file written; a directory in its
+        // place makes the switch fail after the other files were replaced
+        fs::create_dir(get_alias_file_path().join("default")).unwrap();
+        let before = activation_files();
+
+        let err = activate_version("go1.15.4".to_string(), true)
+            .await
+            .unwrap_err();
+
+        assert!(
+            err.to_string().contains("Previous state restored"),
+            "{}",
+            err
+        );
+        assert_eq!(activation_files(), before);
+        assert_eq!(read_active_file().await.as_deref(), Some("go1.15.3"));
+        clear_activation();
+    }
+
+    #[tokio::test]
+    async fn list_installed_versions_skips_non_version_entries() {
+        let _lock = test_support::lock_activation().await;
+        clear_activation();
+        test_support::fake_version("go1.16.4");
+        fs::write(get_version_file_path().join("active"), "go1.16.4").unwrap();
+        let leftover = get_version_file_path().join("go");
+        fs::create_dir_all(&leftover).unwrap();
+
+        let versions = list_installed_versions().await.unwrap();
+        fs::remove_dir(&leftover).unwrap();
+        clear_activation();
+
+        assert!(versions.contains(&"go1.16.4".to_string()), "{:?}", versions);
+        assert!(!versions.contains(&"active".to_string()), "{:?}", versions);
+        assert!(!versions.contains(&"go".to_string()), "{:?}", versions);
+    }
+
+    #[tokio::test]
+    async fn get_active_version_ignores_a_removed_version() {
+        let _lock = test_support::lock_activation().await;
+        clear_activation();
+        fs::write(get_version_file_path().join("active"), "go1.16.404").unwrap();
+
+        assert_eq!(read_active_file().await.as_deref(), Some("go1.16.404"));
+        assert_eq!(get_active_version().await, None);
+        assert!(!is_version_active("go1.16.404").await);
+        clear_activation();
+    }
+
+    #[tokio::test]
+    async fn get_active_version_ignores_surrounding_whitespace() {
+        let _lock = test_support::lock_activation().await;
+        clear_activation();
+        test_support::fake_version("go1.16.7");
+        let active = get_version_file_path().join("active");
+
+        for contents in ["go1.16.7", "go1.16.7\n", " go1.16.7\r\n"] {
+            fs::write(&active, contents).unwrap();
+            assert_eq!(
+                get_active_version().await.as_deref(),
+                Some("go1.16.7"),
+                "{:?}",
+                contents
+            );
+            assert!(is_version_active("go1.16.7").await);
+        }
+        clear_activation();
+    }
+
+    #[test]
+    fn quiet_suppresses_steps_and_verbose_adds_detail() {
+        use Verbosity::{Normal, Quiet, Verbose};
+        let lines = |verbosity| {
+            test_support::capture_log_lines(verbosity, || {
+                info!("t301 step");
+                success!("t301 done");
+                crate::debug!("t301 detail");
+            })
+        };
+
+        assert!(lines(Quiet).is_empty());
+        let normal = lines(Normal);
+        assert_eq!(normal.len(), 2, "{:?}", normal);
+        assert!(normal[0].ends_with("t301 step"));
+        assert!(normal[1].ends_with("t301 done"));
+        let verbose = lines(Verbose);
+        assert_eq!(verbose.len(), 3, "{:?}", verbose);
+        assert!(verbose[2].ends_with("t301 detail"));
+        assert_eq!(get_verbosity(), Normal);
+    }
+
+    #[test]
+    fn mirror_urls_prefer_the_environment_over_the_file() {
+        let mirror = "https://golang.google.cn/dl";
+        let env_value = |value: &str| Some(value.to_string());
+
+        let base = download_base_from(env_value(mirror), Some("https://example.com/go/")).unwrap();
+        assert_eq!(base.as_str(), "https://golang.google.cn/dl/");
+        let base = download_base_from(env_value(" "), Some("https://example.com/go")).unwrap();
+        assert_eq!(base.as_str(), "https://example.com/go/");
+        let base = download_base_from(None, None).unwrap();
+        assert_eq!(base.as_str(), config::DEFAULT_DOWNLOAD_BASE);
+        let err = download_base_from(env_value("ftp://golang.google.cn/dl/"), None).unwrap_err();
+        assert!(err.to_string().contains("GVM_DOWNLOAD_BASE"), "{}", err);
+
+        let releases_url =
+            releases_url_from(None, None, || download_base_from(env_value(mirror), None));
+        assert_eq!(
+            releases_url.unwrap().as_str(),
+            "https://golang.google.cn/dl/?mode=json&include=all"
+        );
+        let releases_url =
+            releases_url_from(env_value("https://example.com/releases.json"), None, || {
+                panic!("the download base is not needed")
+            });
+        assert_eq!(
+            releases_url.unwrap().as_str(),
+            "https://example.com/releases.json"
+        );
+    }
+
+    #[test]
+    fn settings_prefer_the_environment_over_the_file() {
+        assert_eq!(setting_from::<u64>(None, None, 24), 24);
+        assert_eq!(setting_from(None, Some(6), 24), 6);
+        assert_eq!(setting_from(Some(" 2 "), Some(6), 24), 2);
+        assert_eq!(setting_from(Some(" 2 "), None, 24), 2);
+        assert_eq!(setting_from(Some("soon"), Some(6), 24), 6);
+        assert_eq!(setting_from(Some(""), None, 24), 24);
+        assert!(setting_from(Some("true"), Some(false), false));
+    }
+
+    #[test]
+    fn confirm_requires_yes_without_a_terminal() {
+        let confirm = |yes, interactive, answer: &str| {
+            confirm_from("Remove go1.21.5?", yes, interactive, &mut answer.as_bytes())
+        };
+
+        assert!(confirm(true, false, "").unwrap());
+        assert!(confirm(true, true, "n\n").unwrap());
+        let err = confirm(false, false, "y\n").unwrap_err();
+        assert!(err.to_string().contains("Pass --yes"), "{}", err);
+
+        for (answer, confirmed) in [
+            ("y\n", true),
+            ("YES\n", true),
+            (" yes \n", true),
+            ("n\n", false),
+            ("\n", false),
+            ("", false),
+            ("sure\n", false),
+        ] {
+            assert_eq!(
+                confirm(false, true, answer).unwrap(),
+                confirmed,
+                "{:?}",
+                answer
+            );
+        }
+    }
+
+    #[test]
+    fn parse_go_mod_reads_the_go_and_toolchain_directives() {
+        let parse = |content: &str| {
+            let (go, toolchain) = parse_go_mod(content);
+            (go.unwrap_or_default(), toolchain.unwrap_or_default())
+        };
+
+        assert_eq!(
+            parse("module example.com/app\n\ngo 1.22\n"),
+            ("go1.22".to_string(), String::new())
+        );
+        assert_eq!(
+            parse("module example.com/app\n\ngo 1.22.0 // minimum\ntoolchain go1.23.4\n\nrequire (\n\tgolang.org/x/text v0.14.0\n)\n"),
+            ("go1.22.0".to_string(), "go1.23.4".to_string())
+        );
+        assert_eq!(
+            parse("go 1.21rc2\ntoolchain default\n"),
+            ("go1.21rc2".to_string(), String::new())
+        );
+        assert_eq!(
+            parse("module example.com/app\n// go 1.20\n"),
+            (String::new(), String::new())
+        );
+    }
+
+    #[tokio::test]
+    async fn go_mod_version_prefers_the_toolchain() {
+        let project = tempfile::tempdir().unwrap();
+        let go_mod = project.path().join(config::GO_MOD_FILE);
+        let nested = project.path().join("internal");
+        fs::create_dir(&nested).unwrap();
+
+        fs::write(&go_mod, "module m\ngo 1.22.3\n").unwrap();
+        let version = go_mod_version(&nested).await.unwrap();
+        assert_eq!(version.version, "go1.22");
+        assert_eq!(version.source, ProjectVersionSource::GoDirective);
+        assert_eq!(version.path, go_mod);
+
+        fs::write(&go_mod, "module m\ngo 1.22.3\ntoolchain go1.23.1\n").unwrap();
+        let version = go_mod_version(&nested).await.unwrap();
+        assert_eq!(version.version, "go1.23.1");
+        assert_eq!(version.source, ProjectVersionSource::Toolchain);
+
+        fs::write(&go_mod, "module m\n").unwrap();
+        assert!(go_mod_version(&nested).await.is_err());
+    }
+
+    #[test]
+    fn path_position_parses_known_values_only() {
+        assert_eq!(PathPosition::parse("append"), Some(PathPosition::Append));
+        assert_eq!(PathPosition::parse(" Append\n"), Some(PathPosition::Append));
+        assert_eq!(PathPosition::parse("prepend"), Some(PathPosition::Prepend));
+        assert_eq!(PathPosition::parse("PREPEND"), Some(PathPosition::Prepend));
+        for value in ["", "after", "appendix", "1", "end"] {
+            assert_eq!(PathPosition::parse(value), None, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn login_shell_is_detected_from_the_shell_path() {
+        for (shell, expected) in [
+            ("/bin/bash", Some(LoginShell::Bash)),
+            ("/usr/local/bin/zsh", Some(LoginShell::Zsh)),
+            ("/usr/bin/fish", Some(LoginShell::Fish)),
+            ("/opt/homebrew/bin/fish", Some(LoginShell::Fish)),
+            ("/usr/bin/fishy", None),
+            ("fish", None),
+            ("/bin/sh", None),
+            ("", None),
+        ] {
+            assert_eq!(LoginShell::from_path(shell), expected, "{:?}", shell);
+        }
+
+        let home = Path::new("/home/me");
+        assert_eq!(
+            LoginShell::Fish.config_file_path(home),
+            Path::new("/home/me/.config/fish/config.fish")
+        );
+        assert_eq!(
+            LoginShell::Zsh.config_file_path(home),
+            Path::new("/home/me/.zshrc")
+        );
+    }
+
+    #[test]
+    fn latest_stable_version_skips_prereleases_and_foreign_installs() {
+        let versions = [
+            "go1.21.13",
+            "go1.22.10",
+            "go1.22.9",
+            "go1.23rc2",
+            "go1.23beta1",
+            "go1.23.0.darwin-arm64",
+        ];
+        assert_eq!(
+            latest_stable_version(versions).as_deref(),
+            Some("go1.22.10")
+        );
+        assert_eq!(latest_stable_version(["go1.23rc1"]), None);
+        assert_eq!(latest_stable_version([]), None);
+
+        assert!(is_latest_keyword("latest"));
+        assert!(is_latest_keyword("latest-stable"));
+        assert!(!is_latest_keyword("go1.22"));
+    }
+
+    /// Writes a release cache holding `versions` for the target platform.
+    fn write_cache(path: &Path, versions: &[&str]) {
+        let releases: Vec<FilteredRelease> = versions
+            .iter()
+            .map(|version| FilteredRelease {
+                version: version.to_string(),
+                url: format!("https://go.dev/dl/{}.tar.gz", version),
+                sha256: None,
+                os: get_target_os(),
+                arch: get_target_arch(),
+            })
+            .collect();
+        fs::write(path, serde_json::to_string(&releases).unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn list_cached_versions_filters_by_stability_and_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_file = dir.path().join("releases.json");
+        write_cache(
+            &cache_file,
+            &["go1.22.1", "go1.21.5", "go1.22rc1", "go1.22.0", "go1.23rc2"],
+        );
+        let list = |filter: Option<&str>, stable_only| {
+            let cache_file = cache_file.clone();
+            let filter = filter.map(str::to_string);
+            async move {
+                list_cached_versions(cache_file, filter, stable_only)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|r| r.version)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(list(None, true).await, ["go1.21.5", "go1.22.0", "go1.22.1"]);
+        assert_eq!(
+            list(None, false).await,
+            ["go1.21.5", "go1.22rc1", "go1.22.0", "go1.22.1", "go1.23rc2"]
+        );
+        assert_eq!(list(Some("1.22.*"), true).await, ["go1.22.0", "go1.22.1"]);
+        assert_eq!(
+            list(Some("go1.22*"), false).await,
+            ["go1.22rc1", "go1.22.0", "go1.22.1"]
+        );
+        assert_eq!(list(Some("1.22.1"), true).await, ["go1.22.1"]);
+        assert!(list(Some("1.23rc2"), true).await.is_empty());
+    }
+
+    #[test]
+    fn latest_patch_version_resolves_a_minor_version() {
+        let versions = [
+            "go1.21.13",
+            "go1.22",
+            "go1.22.6",
+            "go1.22.10",
+            "go1.22rc1",
+            "go1.23rc1",
+            "go1.220.1",
+        ];
+
+        assert_eq!(
+            latest_patch_version("go1.22", versions).as_deref(),
+            Some("go1.22.10")
+        );
+        assert_eq!(
+            latest_patch_version("go1.21", versions).as_deref(),
+            Some("go1.21.13")
+        );
+        assert_eq!(latest_patch_version("go1.23", versions), None);
+        assert_eq!(latest_patch_version("go1.22.6", versions), None);
+        assert_eq!(latest_patch_version("go1.22rc1", versions), None);
+    }
+
+    #[test]
+    fn old_release_caches_get_the_platform_from_the_url() {
+        let old = r#"[
+            {"version": "go1.22.0", "url": "https://go.dev/dl/go1.22.0.linux-arm64.tar.gz"},
+            {"version": "go1.21.0", "url": "https://mirror.example/go1.21.0.tgz"}
+        ]"#;
+
+        let releases = parse_release_cache(old).unwrap();
+        let platforms: Vec<(&str, &str)> = releases
+            .iter()
+            .map(|r| (r.os.as_str(), r.arch.as_str()))
+            .collect();
+        assert_eq!(platforms, [("linux", "arm64"), ("linux", "amd64")]);
+        assert!(releases.iter().all(|r| r.sha256.is_none()));
+    }
+
+    #[test]
+    fn new_release_caches_keep_the_recorded_platform() {
+        let digest = "ab".repeat(32);
+        let new = format!(
+            r#"[{{"version": "go1.22.0", "url": "https://go.dev/dl/go1.22.0.linux-amd64.tar.gz", "sha256": "{}", "os": "darwin", "arch": "arm64"}}]"#,
+            digest
+        );
+
+        let releases = parse_release_cache(&new).unwrap();
+        assert_eq!(releases[0].os, "darwin");
+        assert_eq!(releases[0].arch, "arm64");
+        assert_eq!(releases[0].sha256.as_deref(), Some(digest.as_str()));
+        assert!(parse_release_cache(r#"{"version": "go1.22.0"}"#).is_err());
+    }
+
+    #[test]
+    fn cmp_versions_orders_prereleases_numerically_by_kind() {
+        assert_eq!(cmp_versions("go1.24rc2", "go1.24rc10"), Ordering::Less);
+        assert_eq!(cmp_versions("go1.24rc10", "go1.24rc2"), Ordering::Greater);
+        assert_eq!(cmp_versions("go1.24beta1", "go1.24rc1"), Ordering::Less);
+        assert_eq!(cmp_versions("go1.24alpha2", "go1.24beta1"), Ordering::Less);
+        assert_eq!(cmp_versions("go1.24rc1", "go1.24rc1"), Ordering::Equal);
+
+        let mut versions = vec![
+            "go1.24.0",
+            "go1.24rc10",
+            "go1.23.4",
+            "go1.24rc2",
+            "go1.24beta1",
+            "go1.24alpha1",
+        ];
+        versions.sort_by(|a, b| cmp_versions(a, b));
+        assert_eq!(
+            versions,
+            [
+                "go1.23.4",
+                "go1.24alpha1",
+                "go1.24beta1",
+                "go1.24rc2",
+                "go1.24rc10",
+                "go1.24.0"
+            ]
+        );
+    }
 }